@@ -1,8 +1,13 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use jjpwrgem_parse::format::LineEnding;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use jjpwrgem_parse::{
+    Dialect, DuplicateKeys, ErrorCode, error::diagnostics::Severity, format::LineEnding,
+    lint::builtin_rules,
+};
+use std::{num::NonZeroUsize, path::PathBuf};
 
 use crate::{
     docs::{indent, strip_front_matter},
+    documents::Documents,
     get_docs_snapshot,
 };
 
@@ -25,6 +30,10 @@ use crate::{
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Exit with a failure when anything is reported as a warning
+    #[arg(long, global = true)]
+    pub deny_warnings: bool,
 }
 
 #[derive(Subcommand)]
@@ -36,18 +45,70 @@ pub enum Commands {
         indent(strip_front_matter(get_docs_snapshot!("format_uglify"))),
     ))]
     Format {
+        /// Files or directories to format. Reads from stdin if none are given.
+        /// With more than one file, each one's output starts with a
+        /// `==> path <==` header
+        paths: Vec<PathBuf>,
+
+        /// Allow comments and trailing commas, keeping comments in the output.
+        /// Enabled automatically for files like tsconfig.json and .vscode/settings.json
+        #[arg(long)]
+        jsonc: bool,
+
+        /// Allow json5 syntax such as unquoted keys, single quoted strings and
+        /// hexadecimal numbers. Enabled automatically for .json5 files
+        #[arg(long, conflicts_with = "jsonc")]
+        json5: bool,
+
+        /// Dialect to write, converting json5 to plain json syntax and dropping
+        /// comments for json [default: the input dialect]
+        #[arg(value_enum, long, value_name = "DIALECT")]
+        to: Option<DialectArg>,
+
         /// Removes all insignificant whitespace instead of pretty printing,
-        /// also known as minifying. Cannot be combined with --preferred-width
-        #[arg(short, long, conflicts_with = "preferred_width")]
+        /// also known as minifying. Cannot be combined with --preferred-width.
+        /// Stdin is uglified as it is read, so invalid input can leave the
+        /// output written before the error
+        #[arg(short, long, conflicts_with_all = ["preferred_width", "indent_style", "indent_width"])]
         uglify: bool,
 
-        /// Preferred maximum line width. Note this is not a hard maximum width
-        #[arg(long, default_value_t = 80, conflicts_with = "uglify")]
-        preferred_width: usize,
+        // defaults are resolved after merging with config files, so they are
+        // documented by hand instead of with `default_value_t`
+        /// Preferred maximum line width. Note this is not a hard maximum width [default: 80]
+        #[arg(long)]
+        preferred_width: Option<usize>,
+
+        /// Line ending to use when formatting output [default: lf]
+        #[arg(value_enum, long, visible_alias = "eol")]
+        end_of_line: Option<LineEndingArg>,
+
+        /// Character to indent nested values with [default: space]
+        #[arg(value_enum, long)]
+        indent_style: Option<IndentStyleArg>,
+
+        /// Number of spaces per indentation level. Ignored for tabs [default: 2]
+        #[arg(long)]
+        indent_width: Option<usize>,
+
+        /// Whether to end the output with a newline [default: true]
+        #[arg(long, value_name = "BOOL")]
+        final_newline: Option<bool>,
+
+        /// What to do with objects that use a key more than once. keep-first
+        /// and keep-last drop the other entries [default: keep-all]
+        #[arg(value_enum, long, value_name = "POLICY")]
+        duplicate_keys: Option<DuplicateKeysArg>,
 
-        /// Line ending to use when formatting output
-        #[arg(value_enum, long, visible_alias = "eol", default_value_t)]
-        end_of_line: LineEndingArg,
+        /// Accept `\u` escapes of unpaired surrogates, which RFC 8259 allows
+        /// but which can't be decoded to utf8
+        #[arg(long)]
+        allow_lone_surrogates: bool,
+
+        #[command(flatten)]
+        documents: DocumentArgs,
+
+        #[command(flatten)]
+        files: FileArgs,
     },
     #[command(after_help = format!(
         "Examples:\n{}\n\n{}",
@@ -55,7 +116,152 @@ pub enum Commands {
         indent(strip_front_matter(get_docs_snapshot!("check_failure"))),
     ))]
     /// Validates json syntax
-    Check,
+    Check {
+        /// Files or directories to check. Reads from stdin if none are given
+        paths: Vec<PathBuf>,
+
+        /// Allow comments and trailing commas, also known as json with comments.
+        /// Enabled automatically for files like tsconfig.json and .vscode/settings.json
+        #[arg(long)]
+        jsonc: bool,
+
+        /// Allow json5 syntax such as unquoted keys, single quoted strings and
+        /// hexadecimal numbers. Enabled automatically for .json5 files
+        #[arg(long, conflicts_with = "jsonc")]
+        json5: bool,
+
+        /// Also check the rules of a stricter profile, reporting every
+        /// violation. i-json (RFC 7493) rejects duplicate keys, numbers that
+        /// IEEE 754 doubles can't hold exactly and noncharacters
+        #[arg(value_enum, long, value_name = "PROFILE")]
+        profile: Option<ProfileArg>,
+
+        /// Fail on or warn about objects that use a key more than once, the
+        /// other policies accept them [default: keep-all]
+        #[arg(value_enum, long, value_name = "POLICY")]
+        duplicate_keys: Option<DuplicateKeysArg>,
+
+        /// Accept `\u` escapes of unpaired surrogates, which RFC 8259 allows
+        /// but which can't be decoded to utf8
+        #[arg(long)]
+        allow_lone_surrogates: bool,
+
+        #[command(flatten)]
+        documents: DocumentArgs,
+
+        #[command(flatten)]
+        files: FileArgs,
+    },
+    /// Check json against style and correctness rules
+    #[command(after_help = rules_help())]
+    Lint {
+        /// Files or directories to lint. Reads from stdin if none are given
+        paths: Vec<PathBuf>,
+
+        /// Allow comments and trailing commas, also known as json with comments.
+        /// Enabled automatically for files like tsconfig.json and .vscode/settings.json
+        #[arg(long)]
+        jsonc: bool,
+
+        /// Allow json5 syntax such as unquoted keys, single quoted strings and
+        /// hexadecimal numbers. Enabled automatically for .json5 files
+        #[arg(long, conflicts_with = "jsonc")]
+        json5: bool,
+
+        /// Set how serious a rule is, such as `camel-case-keys=off`. Overrides
+        /// the `lint` table of config files. Can be repeated
+        #[arg(long, value_name = "RULE=LEVEL", value_parser = parse_rule_level)]
+        rule: Vec<(String, LintLevelArg)>,
+
+        /// Print the input with every suggested fix applied, reporting only
+        /// what is left
+        #[arg(long)]
+        fix: bool,
+
+        #[command(flatten)]
+        files: FileArgs,
+    },
+    /// Rewrite json5 or jsonc as another dialect, warning about each construct
+    /// that had to change
+    Convert {
+        /// Files or directories to convert. Reads from stdin if none are given
+        paths: Vec<PathBuf>,
+
+        /// Dialect of the input [default: based on the file name, json for stdin]
+        #[arg(value_enum, long, value_name = "DIALECT")]
+        from: Option<DialectArg>,
+
+        /// Dialect to write
+        #[arg(value_enum, long, value_name = "DIALECT", default_value = "json")]
+        to: DialectArg,
+
+        #[command(flatten)]
+        files: FileArgs,
+    },
+    /// Explain an error code in depth, with examples of what causes it and
+    /// how to fix it
+    Explain {
+        /// The code shown in the error, such as JJP0001
+        #[arg(value_parser = parse_error_code)]
+        code: ErrorCode,
+    },
+}
+
+/// controls which files are picked up from directories and how they are
+/// processed. Directories are walked honoring `.gitignore` and `.jjpignore`
+/// files
+#[derive(Args, Debug, Default)]
+pub struct FileArgs {
+    /// Only pick up files matching this glob from directories. Can be repeated [default: *.json, *.jsonc, *.json5]
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories matching this glob, in addition to .git, node_modules, and target.
+    /// Can be repeated
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Number of files to process in parallel [default: number of cpus]
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Run again whenever a matched file changes, until interrupted
+    #[arg(short, long, requires = "paths")]
+    pub watch: bool,
+}
+
+/// inputs holding more than one document. Formatted records stay one per
+/// line when uglifying and are separated by blank lines otherwise
+#[derive(Args, Debug, Default)]
+#[group(multiple = false)]
+pub struct DocumentArgs {
+    /// Treat each line as its own document, also known as ndjson
+    #[arg(long)]
+    pub jsonl: bool,
+
+    /// Read an RFC 7464 json text sequence, where every document starts with
+    /// a record separator (0x1E)
+    #[arg(long)]
+    pub seq: bool,
+
+    /// Read documents written back to back, optionally separated by whitespace
+    #[arg(long)]
+    pub concatenated: bool,
+}
+
+impl DocumentArgs {
+    /// `None` for a single document
+    pub const fn documents(&self) -> Option<Documents> {
+        if self.jsonl {
+            Some(Documents::Lines)
+        } else if self.seq {
+            Some(Documents::Sequence)
+        } else if self.concatenated {
+            Some(Documents::Concatenated)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum, Default)]
@@ -78,3 +284,132 @@ impl LineEndingArg {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum, Default)]
+pub enum IndentStyleArg {
+    #[default]
+    #[value(name = "space")]
+    Space,
+    #[value(name = "tab")]
+    Tab,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum, Default)]
+pub enum DuplicateKeysArg {
+    #[value(name = "error")]
+    Error,
+    #[value(name = "warn")]
+    Warn,
+    #[value(name = "keep-first")]
+    KeepFirst,
+    #[value(name = "keep-last")]
+    KeepLast,
+    #[default]
+    #[value(name = "keep-all")]
+    KeepAll,
+}
+
+impl DuplicateKeysArg {
+    pub const fn into_parse(self) -> DuplicateKeys {
+        match self {
+            Self::Error => DuplicateKeys::Error,
+            Self::Warn => DuplicateKeys::Warn,
+            Self::KeepFirst => DuplicateKeys::KeepFirst,
+            Self::KeepLast => DuplicateKeys::KeepLast,
+            Self::KeepAll => DuplicateKeys::KeepAll,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum LintLevelArg {
+    #[value(name = "off")]
+    Off,
+    #[value(name = "hint")]
+    Hint,
+    #[value(name = "info")]
+    Info,
+    #[value(name = "warning")]
+    Warning,
+    #[value(name = "error")]
+    Error,
+}
+
+impl LintLevelArg {
+    /// `None` when the rule is off
+    pub const fn into_parse(self) -> Option<Severity> {
+        match self {
+            Self::Off => None,
+            Self::Hint => Some(Severity::Hint),
+            Self::Info => Some(Severity::Info),
+            Self::Warning => Some(Severity::Warning),
+            Self::Error => Some(Severity::Error),
+        }
+    }
+}
+
+pub fn is_builtin_rule(id: &str) -> bool {
+    builtin_rules().iter().any(|rule| rule.id() == id)
+}
+
+/// reads `RULE=LEVEL` for a rule that exists
+fn parse_rule_level(arg: &str) -> Result<(String, LintLevelArg), String> {
+    let (rule, level) = arg
+        .split_once('=')
+        .ok_or("expected RULE=LEVEL, such as camel-case-keys=off")?;
+    if !is_builtin_rule(rule) {
+        return Err(format!("unknown rule `{rule}`"));
+    }
+    let level = LintLevelArg::from_str(level, false).map_err(|_| {
+        format!("unknown level `{level}`, expected off, hint, info, warning or error")
+    })?;
+    Ok((rule.to_owned(), level))
+}
+
+/// reads a code that some error has
+fn parse_error_code(arg: &str) -> Result<ErrorCode, String> {
+    ErrorCode::parse(arg)
+        .ok_or_else(|| format!("unknown error code `{arg}`, codes look like JJP0001"))
+}
+
+/// every rule with its default level, for `jjp lint --help`
+fn rules_help() -> String {
+    let rules: Vec<String> = builtin_rules()
+        .iter()
+        .map(|rule| {
+            format!(
+                "  {:<18} {:<8} {}",
+                rule.id(),
+                rule.default_severity(),
+                rule.description()
+            )
+        })
+        .collect();
+    format!("Rules:\n{}", rules.join("\n"))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum ProfileArg {
+    #[value(name = "i-json")]
+    IJson,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum DialectArg {
+    #[value(name = "json")]
+    Json,
+    #[value(name = "jsonc")]
+    Jsonc,
+    #[value(name = "json5")]
+    Json5,
+}
+
+impl DialectArg {
+    pub const fn into_parse(self) -> Dialect {
+        match self {
+            Self::Json => Dialect::Json,
+            Self::Jsonc => Dialect::Jsonc,
+            Self::Json5 => Dialect::Json5,
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{
+    Error, Result,
+    commands::{DuplicateKeysArg, IndentStyleArg, LineEndingArg, LintLevelArg, is_builtin_rule},
+    editorconfig::EditorConfigResolver,
+    globs::PathGlobs,
+};
+use clap::ValueEnum;
+use jjpwrgem_parse::{
+    DuplicateKeys,
+    ast::{ObjectEntries, Value, parse_str},
+    format::{FormatOptions, LineEnding},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// config files looked for in each directory, in order of precedence
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["jjp.toml", ".jjprc.json"];
+
+pub const DEFAULT_PREFERRED_WIDTH: usize = 80;
+pub const DEFAULT_INDENT_WIDTH: usize = 2;
+
+/// formatting settings where `None` means the layer did not set it
+///
+/// layers are merged from most to least specific with [`FormatSettings::or`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FormatSettings {
+    pub uglify: Option<bool>,
+    pub preferred_width: Option<usize>,
+    pub end_of_line: Option<LineEndingArg>,
+    pub indent_style: Option<IndentStyleArg>,
+    pub indent_width: Option<usize>,
+    pub final_newline: Option<bool>,
+    pub duplicate_keys: Option<DuplicateKeysArg>,
+    pub allow_lone_surrogates: Option<bool>,
+}
+
+impl FormatSettings {
+    /// fills in settings that are unset in `self` from `fallback`
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            uglify: self.uglify.or(fallback.uglify),
+            preferred_width: self.preferred_width.or(fallback.preferred_width),
+            end_of_line: self.end_of_line.or(fallback.end_of_line),
+            indent_style: self.indent_style.or(fallback.indent_style),
+            indent_width: self.indent_width.or(fallback.indent_width),
+            final_newline: self.final_newline.or(fallback.final_newline),
+            duplicate_keys: self.duplicate_keys.or(fallback.duplicate_keys),
+            allow_lone_surrogates: self
+                .allow_lone_surrogates
+                .or(fallback.allow_lone_surrogates),
+        }
+    }
+
+    pub fn uglify(&self) -> bool {
+        self.uglify.unwrap_or_default()
+    }
+
+    /// the line ending output ends with, if it ends with one
+    pub fn final_newline(&self) -> Option<LineEnding> {
+        self.final_newline
+            .unwrap_or(true)
+            .then(|| self.line_ending())
+    }
+
+    pub fn preferred_width(&self) -> usize {
+        self.preferred_width.unwrap_or(DEFAULT_PREFERRED_WIDTH)
+    }
+
+    pub fn line_ending(&self) -> LineEnding {
+        self.end_of_line.unwrap_or_default().into_parse()
+    }
+
+    pub fn duplicate_keys(&self) -> DuplicateKeys {
+        self.duplicate_keys.unwrap_or_default().into_parse()
+    }
+
+    pub fn allow_lone_surrogates(&self) -> bool {
+        self.allow_lone_surrogates.unwrap_or_default()
+    }
+
+    pub fn format_options(&self) -> FormatOptions {
+        let indent = match self.indent_style.unwrap_or_default() {
+            IndentStyleArg::Space => (' ', self.indent_width.unwrap_or(DEFAULT_INDENT_WIDTH)),
+            IndentStyleArg::Tab => ('\t', 1),
+        };
+        FormatOptions::new(Some((' ', 1)), Some(indent), self.line_ending())
+            .with_duplicate_keys(self.duplicate_keys())
+            .allow_lone_surrogates(self.allow_lone_surrogates())
+    }
+
+    /// sets `key` from `value`, returning `Ok(false)` if `key` is not a setting
+    fn set(&mut self, key: &str, value: &Value) -> std::result::Result<bool, String> {
+        match key {
+            "uglify" => self.uglify = Some(expect_bool(key, value)?),
+            "preferred_width" => self.preferred_width = Some(expect_usize(key, value)?),
+            "end_of_line" => self.end_of_line = Some(expect_enum(key, value)?),
+            "indent_style" => self.indent_style = Some(expect_enum(key, value)?),
+            "indent_width" => self.indent_width = Some(expect_usize(key, value)?),
+            "final_newline" => self.final_newline = Some(expect_bool(key, value)?),
+            "duplicate_keys" => self.duplicate_keys = Some(expect_enum(key, value)?),
+            "allow_lone_surrogates" => {
+                self.allow_lone_surrogates = Some(expect_bool(key, value)?);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn expect_bool(key: &str, value: &Value) -> std::result::Result<bool, String> {
+    match value {
+        Value::Boolean(b) => Ok(*b),
+        _ => Err(format!("`{key}` should be a boolean")),
+    }
+}
+
+fn expect_usize(key: &str, value: &Value) -> std::result::Result<usize, String> {
+    match value {
+        Value::Number(n) => n.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("`{key}` should be a non-negative integer"))
+}
+
+fn expect_str<'a>(key: &str, value: &'a Value) -> std::result::Result<&'a str, String> {
+    match value {
+        Value::String(s) => Ok(s),
+        _ => Err(format!("`{key}` should be a string")),
+    }
+}
+
+fn expect_enum<T: ValueEnum>(key: &str, value: &Value) -> std::result::Result<T, String> {
+    let s = expect_str(key, value)?;
+    T::from_str(s, false).map_err(|_| {
+        let possible = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| format!("`{}`", v.get_name()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("`{key}` should be one of {possible}, found `{s}`")
+    })
+}
+
+/// settings for files matching `globs`, relative to the directory of the
+/// config file
+#[derive(Debug)]
+struct Override {
+    globs: PathGlobs,
+    settings: FormatSettings,
+}
+
+/// levels of lint rules, in the order they were set
+pub type LintLevels = Vec<(String, LintLevelArg)>;
+
+#[derive(Debug)]
+pub struct Config {
+    dir: PathBuf,
+    settings: FormatSettings,
+    overrides: Vec<Override>,
+    lint: LintLevels,
+}
+
+impl Config {
+    /// `display` is the path shown in errors
+    pub fn load(path: &Path, display: String) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| Error::ReadFile {
+            path: display.clone(),
+            reason: e.to_string(),
+        })?;
+        let invalid = |reason: String| Error::InvalidConfig {
+            path: display.clone(),
+            reason,
+        };
+
+        let (settings, overrides, lint) = if path.extension().is_some_and(|ext| ext == "toml") {
+            let table = match text.parse::<toml::Table>() {
+                Ok(table) => table,
+                Err(e) => {
+                    return Err(Error::TomlConfigSyntax {
+                        path: display,
+                        message: e.message().to_owned(),
+                        span: e.span(),
+                        text,
+                    });
+                }
+            };
+            let entries = toml_table_to_entries(&table);
+            read_config(&entries).map_err(invalid)?
+        } else {
+            match parse_str(&text) {
+                Ok(Value::Object(entries)) => read_config(&entries).map_err(invalid)?,
+                Ok(_) => return Err(invalid("expected an object at the top level".into())),
+                Err(_) => {
+                    return Err(Error::ConfigSyntax {
+                        path: display,
+                        text,
+                    });
+                }
+            }
+        };
+
+        Ok(Self {
+            dir: path.parent().map(Path::to_owned).unwrap_or_default(),
+            settings,
+            overrides,
+            lint,
+        })
+    }
+
+    /// settings for `path`, with matching overrides applied in order
+    pub fn settings_for(&self, path: Option<&Path>) -> FormatSettings {
+        let relative = path.map(|p| p.strip_prefix(&self.dir).unwrap_or(p));
+        self.overrides
+            .iter()
+            .filter(|o| relative.is_some_and(|r| o.globs.matches(r)))
+            .fold(self.settings, |acc, o| o.settings.or(acc))
+    }
+}
+
+fn read_config(
+    entries: &ObjectEntries,
+) -> std::result::Result<(FormatSettings, Vec<Override>, LintLevels), String> {
+    let mut settings = FormatSettings::default();
+    let mut overrides = Vec::new();
+    let mut lint = Vec::new();
+    for (key, value) in &entries.0 {
+        match (key.as_ref(), value) {
+            ("lint", Value::Object(rules)) => {
+                for (rule, level) in &rules.0 {
+                    if !is_builtin_rule(rule) {
+                        return Err(format!("unknown lint rule `{rule}`"));
+                    }
+                    lint.push((rule.to_string(), expect_enum(rule, level)?));
+                }
+            }
+            ("lint", _) => return Err("`lint` should be a table of rules and levels".into()),
+            ("overrides", Value::Array(items)) => {
+                for item in items {
+                    overrides.push(read_override(item)?);
+                }
+            }
+            ("overrides", _) => return Err("`overrides` should be an array".into()),
+            _ => {
+                if !settings.set(key, value)? {
+                    return Err(format!("unknown setting `{key}`"));
+                }
+            }
+        }
+    }
+    Ok((settings, overrides, lint))
+}
+
+fn read_override(value: &Value) -> std::result::Result<Override, String> {
+    let Value::Object(entries) = value else {
+        return Err("each item in `overrides` should be an object".into());
+    };
+    let mut settings = FormatSettings::default();
+    let mut globs = None;
+    for (key, value) in &entries.0 {
+        match (key.as_ref(), value) {
+            ("files", Value::Array(items)) => {
+                let mut files = PathGlobs::default();
+                for item in items {
+                    let glob = expect_str("files", item)?;
+                    files
+                        .push(glob)
+                        .map_err(|e| format!("invalid glob `{glob}`: {}", e.kind()))?;
+                }
+                globs = Some(files);
+            }
+            ("files", _) => return Err("`files` should be an array of globs".into()),
+            _ => {
+                if !settings.set(key, value)? {
+                    return Err(format!("unknown override setting `{key}`"));
+                }
+            }
+        }
+    }
+    let globs = globs.ok_or("each item in `overrides` needs a `files` array")?;
+    Ok(Override { globs, settings })
+}
+
+fn toml_to_value(value: &toml::Value) -> Value<'_> {
+    match value {
+        toml::Value::String(s) => Value::String(Cow::Borrowed(s)),
+        toml::Value::Integer(i) => Value::Number(Cow::Owned(i.to_string())),
+        toml::Value::Float(f) => Value::Number(Cow::Owned(f.to_string())),
+        toml::Value::Boolean(b) => Value::Boolean(*b),
+        toml::Value::Array(items) => Value::Array(items.iter().map(toml_to_value).collect()),
+        toml::Value::Table(table) => Value::Object(toml_table_to_entries(table)),
+        // no setting accepts a date, so this is reported as the wrong type
+        toml::Value::Datetime(_) => Value::Null,
+    }
+}
+
+fn toml_table_to_entries(table: &toml::Table) -> ObjectEntries<'_> {
+    table
+        .iter()
+        .map(|(k, v)| (k.as_str(), toml_to_value(v)))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// finds the closest config file for each input, caching lookups per directory
+///
+/// settings from `.editorconfig` files are used for anything the config file
+/// leaves unset
+#[derive(Debug, Default)]
+pub struct ConfigResolver {
+    dirs: HashMap<PathBuf, Option<Arc<Config>>>,
+    editorconfig: EditorConfigResolver,
+}
+
+impl ConfigResolver {
+    pub fn find(&mut self, dir: &Path) -> Result<Option<Arc<Config>>> {
+        if let Some(found) = self.dirs.get(dir) {
+            return Ok(found.clone());
+        }
+
+        let found = match CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+        {
+            Some(path) => {
+                // keep paths in errors short when the config is under the current directory
+                let display = std::env::current_dir()
+                    .ok()
+                    .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_owned))
+                    .unwrap_or_else(|| path.clone());
+                Some(Arc::new(Config::load(
+                    &path,
+                    display.display().to_string(),
+                )?))
+            }
+            None => match dir.parent() {
+                Some(parent) => self.find(parent)?,
+                None => None,
+            },
+        };
+
+        self.dirs.insert(dir.to_owned(), found.clone());
+        Ok(found)
+    }
+
+    /// the config file closest to `path`, or to the current directory when
+    /// reading from stdin, along with the absolute `path`
+    fn config_for(
+        &mut self,
+        path: Option<&Path>,
+    ) -> Result<(Option<Arc<Config>>, Option<PathBuf>)> {
+        let absolute = match path {
+            Some(path) => Some(std::path::absolute(path).map_err(|e| Error::ReadFile {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            })?),
+            None => None,
+        };
+        let dir = match absolute.as_deref().and_then(Path::parent) {
+            Some(dir) => dir.to_owned(),
+            None => std::env::current_dir().map_err(|e| Error::ReadFile {
+                path: ".".into(),
+                reason: e.to_string(),
+            })?,
+        };
+        Ok((self.find(&dir)?, absolute))
+    }
+
+    /// lint rule levels from the config file closest to `path`
+    pub fn lint_levels_for(&mut self, path: Option<&Path>) -> Result<LintLevels> {
+        let (config, _) = self.config_for(path)?;
+        Ok(config.map(|config| config.lint.clone()).unwrap_or_default())
+    }
+
+    /// settings from the config file closest to `path`, or to the current
+    /// directory when reading from stdin
+    pub fn settings_for(&mut self, path: Option<&Path>) -> Result<FormatSettings> {
+        let (config, absolute) = self.config_for(path)?;
+        let settings = config
+            .map(|config| config.settings_for(absolute.as_deref()))
+            .unwrap_or_default();
+        // editorconfig sections match file names, so there is nothing to match for stdin
+        let editorconfig = absolute
+            .map(|path| self.editorconfig.settings_for(&path))
+            .unwrap_or_default();
+
+        Ok(settings.or(editorconfig))
+    }
+}
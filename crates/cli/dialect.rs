@@ -0,0 +1,33 @@
+use crate::globs::PathGlobs;
+use jjpwrgem_parse::Dialect;
+use std::{path::Path, sync::LazyLock};
+
+/// files that are conventionally json with comments
+pub const JSONC_FILE_GLOBS: [&str; 9] = [
+    "*.jsonc",
+    "tsconfig.json",
+    "tsconfig.*.json",
+    "jsconfig.json",
+    "jsconfig.*.json",
+    "devcontainer.json",
+    ".devcontainer.json",
+    ".eslintrc.json",
+    "**/.vscode/*.json",
+];
+
+static JSONC_FILES: LazyLock<PathGlobs> = LazyLock::new(|| {
+    let mut globs = PathGlobs::default();
+    for glob in JSONC_FILE_GLOBS {
+        globs.push(glob).expect("built in globs should be valid");
+    }
+    globs
+});
+
+/// the dialect to parse `path` with when none is requested, stdin is always json
+pub fn detect(path: Option<&Path>) -> Dialect {
+    match path {
+        Some(path) if path.extension().is_some_and(|ext| ext == "json5") => Dialect::Json5,
+        Some(path) if JSONC_FILES.matches(path) => Dialect::Jsonc,
+        _ => Dialect::Json,
+    }
+}
@@ -0,0 +1,104 @@
+use crate::config::FormatSettings;
+use jjpwrgem_parse::{
+    Dialect, Error,
+    error::Positions,
+    format::LineEnding,
+    lines,
+    sequence::{self, RECORD_SEPARATOR, Values},
+    tokens::TokenStream,
+    validate_document, validate_finite_numbers_at,
+};
+use std::ops::Range;
+
+/// how the documents in an input are laid out
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Documents {
+    /// one document per line, see <https://jsonlines.org>
+    Lines,
+    /// each document starts with a record separator, see RFC 7464
+    Sequence,
+    /// documents written back to back
+    Concatenated,
+}
+
+impl Documents {
+    /// where the values are in `input`, or an error for every invalid document
+    pub fn split(
+        self,
+        input: &str,
+        from: Dialect,
+        to: Dialect,
+        allow_lone_surrogates: bool,
+    ) -> Result<Vec<Range<usize>>, Vec<Error<'_>>> {
+        let mut positions = Positions::new(input);
+        let mut validate = move |range: Range<usize>| {
+            let start = positions.at(range.start);
+            validate_document(
+                &mut TokenStream::with_dialect_at(&input[range], from, start)
+                    .allow_lone_surrogates(allow_lone_surrogates),
+            )
+        };
+        let values: Box<dyn Iterator<Item = _>> = match self {
+            Self::Lines => Box::new(lines::lines(input).map(|line| validate(line.range()))),
+            Self::Sequence => {
+                Box::new(sequence::records(input).map(|record| record.and_then(&mut validate)))
+            }
+            Self::Concatenated => Box::new(Values::new(
+                TokenStream::with_dialect(input, from).allow_lone_surrogates(allow_lone_surrogates),
+            )),
+        };
+
+        let (mut ranges, mut errors) = (Vec::new(), Vec::new());
+        let mut positions = Positions::new(input);
+        for value in values {
+            let representable = value.and_then(|range| {
+                if from.is_json5() && !to.is_json5() {
+                    let start = positions.at(range.start);
+                    validate_finite_numbers_at(&input[range.clone()], start, from)?;
+                }
+                Ok(range)
+            });
+            match representable {
+                Ok(range) => ranges.push(range),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(ranges)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// joins formatted records back into one output
+    pub fn join(self, records: &[String], settings: &FormatSettings) -> String {
+        match self {
+            // the separator ends every record, and that line feed is part of
+            // the format regardless of the configured line ending
+            Self::Sequence => records
+                .iter()
+                .map(|record| format!("{RECORD_SEPARATOR}{record}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            // uglified records are one per line, pretty ones need a blank
+            // line to tell them apart
+            Self::Lines | Self::Concatenated if settings.uglify() => records.join("\n"),
+            Self::Lines | Self::Concatenated => {
+                records.join(&settings.line_ending().as_str().repeat(2))
+            }
+        }
+    }
+
+    /// the line ending after the last record, matching the ones between
+    /// records
+    pub fn final_newline(self, settings: &FormatSettings) -> Option<LineEnding> {
+        match self {
+            Self::Sequence => Some(LineEnding::Lf),
+            Self::Lines | Self::Concatenated if settings.uglify() => {
+                settings.final_newline().map(|_| LineEnding::Lf)
+            }
+            Self::Lines | Self::Concatenated => settings.final_newline(),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    commands::{IndentStyleArg, LineEndingArg},
+    config::FormatSettings,
+};
+use clap::ValueEnum;
+use globset::{GlobBuilder, GlobMatcher};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+#[derive(Debug)]
+struct Section {
+    matcher: Option<GlobMatcher>,
+    properties: Vec<(String, String)>,
+}
+
+/// a parsed `.editorconfig`, see <https://spec.editorconfig.org>
+///
+/// invalid lines and globs are ignored as the spec recommends
+#[derive(Debug)]
+struct EditorConfig {
+    dir: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+impl EditorConfig {
+    fn parse(text: &str, dir: PathBuf) -> Self {
+        let mut root = false;
+        let mut sections: Vec<Section> = Vec::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push(Section {
+                    matcher: section_matcher(glob),
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim().to_ascii_lowercase();
+            match sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => root = value == "true",
+                None => {}
+            }
+        }
+
+        Self {
+            dir,
+            root,
+            sections,
+        }
+    }
+
+    fn properties_for<'a>(
+        &'a self,
+        path: &Path,
+    ) -> impl Iterator<Item = &'a (String, String)> + 'a {
+        let relative = path.strip_prefix(&self.dir).unwrap_or(path).to_owned();
+        self.sections
+            .iter()
+            .filter(move |section| {
+                section
+                    .matcher
+                    .as_ref()
+                    .is_some_and(|m| m.is_match(&relative))
+            })
+            .flat_map(|section| &section.properties)
+    }
+}
+
+/// globs without a slash match files in any subdirectory, others are
+/// relative to the directory of the `.editorconfig`
+fn section_matcher(glob: &str) -> Option<GlobMatcher> {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_owned(),
+        None if glob.contains('/') => glob.to_owned(),
+        None => format!("**/{glob}"),
+    };
+    GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .ok()
+        .map(|g| g.compile_matcher())
+}
+
+/// maps editorconfig properties onto format settings, skipping unknown or
+/// invalid values
+fn to_settings(properties: &HashMap<&str, &str>) -> FormatSettings {
+    fn value_enum<T: ValueEnum>(value: Option<&&str>) -> Option<T> {
+        value.and_then(|v| T::from_str(v, true).ok())
+    }
+    fn boolean(value: Option<&&str>) -> Option<bool> {
+        value.and_then(|v| v.parse().ok())
+    }
+
+    FormatSettings {
+        end_of_line: value_enum::<LineEndingArg>(properties.get("end_of_line")),
+        indent_style: value_enum::<IndentStyleArg>(properties.get("indent_style")),
+        indent_width: properties
+            .get("indent_size")
+            .and_then(|size| size.parse().ok()),
+        final_newline: boolean(properties.get("insert_final_newline")),
+        ..FormatSettings::default()
+    }
+}
+
+/// resolves `.editorconfig` properties for each input, caching parsed files
+/// per directory
+#[derive(Debug, Default)]
+pub struct EditorConfigResolver {
+    dirs: HashMap<PathBuf, Option<Arc<EditorConfig>>>,
+}
+
+impl EditorConfigResolver {
+    fn load(&mut self, dir: &Path) -> Option<Arc<EditorConfig>> {
+        self.dirs
+            .entry(dir.to_owned())
+            .or_insert_with(|| {
+                fs::read_to_string(dir.join(EDITORCONFIG_FILE_NAME))
+                    .ok()
+                    .map(|text| Arc::new(EditorConfig::parse(&text, dir.to_owned())))
+            })
+            .clone()
+    }
+
+    /// `path` should be absolute
+    pub fn settings_for(&mut self, path: &Path) -> FormatSettings {
+        let mut configs = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            if let Some(config) = self.load(dir) {
+                let root = config.root;
+                configs.push(config);
+                if root {
+                    break;
+                }
+            }
+        }
+
+        // closer files and later sections take precedence
+        let mut properties = HashMap::new();
+        for config in configs.iter().rev() {
+            for (key, value) in config.properties_for(path) {
+                if value == "unset" {
+                    properties.remove(key.as_str());
+                } else {
+                    properties.insert(key.as_str(), value.as_str());
+                }
+            }
+        }
+
+        to_settings(&properties)
+    }
+}
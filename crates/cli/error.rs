@@ -1,8 +1,9 @@
 use displaydoc::Display;
 use jjpwrgem_ui::message::BasicErrorMessage;
+use std::ops::Range;
 use thiserror::Error;
 
-use crate::{docs::strip_front_matter, get_docs_snapshot};
+use crate::{config::CONFIG_FILE_NAMES, docs::strip_front_matter, get_docs_snapshot};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -10,29 +11,52 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     /// expected non empty input from stdin
     NonEmptyStdinRequired,
+    /// could not read {path}: {reason}
+    ReadFile { path: String, reason: String },
+    /// source did not contain valid utf8
+    InvalidEncoding { path: Option<String> },
+    /// invalid json in config file {path}
+    ConfigSyntax { path: String, text: String },
+    /// {message}
+    TomlConfigSyntax {
+        path: String,
+        text: String,
+        message: String,
+        span: Option<Range<usize>>,
+    },
+    /// invalid config file {path}: {reason}
+    InvalidConfig { path: String, reason: String },
+    /// invalid glob `{glob}`: {reason}
+    InvalidGlob { glob: String, reason: String },
+    /// could not watch {path}: {reason}
+    Watch { path: String, reason: String },
 }
 
 impl Error {
     fn get_help(&self) -> Option<String> {
-        let help = match self {
-            Error::NonEmptyStdinRequired => {
-                format!(
-                    "pipe data to stdin like so\n{}",
-                    strip_front_matter(get_docs_snapshot!("check_success"))
-                )
-            }
-        };
-        Some(help)
+        match self {
+            Error::NonEmptyStdinRequired => Some(format!(
+                "pipe data to stdin like so\n{}",
+                strip_front_matter(get_docs_snapshot!("check_success"))
+            )),
+            Error::InvalidConfig { .. } => Some(format!(
+                "config files ({}) accept the same settings as `jjp format --help` in snake_case, plus `overrides` and a `lint` table of rule levels",
+                CONFIG_FILE_NAMES.join(", ")
+            )),
+            Error::ReadFile { .. }
+            | Error::InvalidEncoding { .. }
+            | Error::ConfigSyntax { .. }
+            | Error::TomlConfigSyntax { .. }
+            | Error::InvalidGlob { .. }
+            | Error::Watch { .. } => None,
+        }
     }
 }
 impl From<Error> for BasicErrorMessage {
     fn from(value: Error) -> Self {
-        let error = value.to_string();
-        match value {
-            Error::NonEmptyStdinRequired => BasicErrorMessage {
-                error,
-                help: value.get_help(),
-            },
+        BasicErrorMessage {
+            error: value.to_string(),
+            help: value.get_help(),
         }
     }
 }
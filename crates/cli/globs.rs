@@ -0,0 +1,27 @@
+use globset::{Glob, GlobMatcher};
+use std::path::Path;
+
+/// a list of globs where globs without a slash match the file name and
+/// others match the whole relative path
+#[derive(Debug, Default, Clone)]
+pub struct PathGlobs(Vec<(String, GlobMatcher)>);
+
+impl PathGlobs {
+    pub fn push(&mut self, glob: &str) -> Result<(), globset::Error> {
+        let matcher = Glob::new(glob)?.compile_matcher();
+        self.0.push((glob.to_owned(), matcher));
+        Ok(())
+    }
+
+    pub fn matches(&self, relative: &Path) -> bool {
+        self.0.iter().any(|(glob, matcher)| {
+            if glob.contains('/') {
+                matcher.is_match(relative)
+            } else {
+                relative
+                    .file_name()
+                    .is_some_and(|name| matcher.is_match(name))
+            }
+        })
+    }
+}
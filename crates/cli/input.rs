@@ -0,0 +1,79 @@
+use crate::{Error, Result};
+use std::{
+    fs,
+    io::{BufRead, IsTerminal, Read, StdinLock},
+    path::{Path, PathBuf},
+};
+
+/// json text to process and where it came from
+#[derive(Debug)]
+pub struct Input {
+    /// `None` when read from stdin
+    pub path: Option<PathBuf>,
+    pub text: String,
+}
+
+impl Input {
+    /// name shown in diagnostics
+    pub fn source_name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "stdin".into(),
+        }
+    }
+}
+
+pub fn read_stdin() -> Result<Input> {
+    let mut stdin = std::io::stdin();
+
+    if stdin.is_terminal() {
+        return Err(Error::NonEmptyStdinRequired);
+    }
+
+    let mut buf = vec![];
+    stdin
+        .read_to_end(&mut buf)
+        .expect("Failed to read from stdin");
+
+    if buf.is_empty() {
+        return Err(Error::NonEmptyStdinRequired);
+    }
+
+    let text = String::from_utf8(buf).map_err(|_| Error::InvalidEncoding { path: None })?;
+    Ok(Input { path: None, text })
+}
+
+/// stdin to be read a piece at a time, failing like [`read_stdin`] when there
+/// is nothing to read
+pub fn lock_stdin() -> Result<StdinLock<'static>> {
+    let stdin = std::io::stdin();
+
+    if stdin.is_terminal() {
+        return Err(Error::NonEmptyStdinRequired);
+    }
+
+    let mut lock = stdin.lock();
+    let buf = lock.fill_buf().expect("Failed to read from stdin");
+
+    if buf.is_empty() {
+        return Err(Error::NonEmptyStdinRequired);
+    }
+
+    Ok(lock)
+}
+
+pub fn read_file(path: &Path) -> Result<Input> {
+    let display = path.display().to_string();
+    let buf = fs::read(path).map_err(|e| Error::ReadFile {
+        path: display.clone(),
+        reason: e.to_string(),
+    })?;
+    let text = String::from_utf8(buf).map_err(|_| Error::InvalidEncoding {
+        path: Some(display),
+    })?;
+
+    Ok(Input {
+        path: Some(path.to_owned()),
+        text,
+    })
+}
@@ -1,111 +1,771 @@
 mod commands;
+mod config;
+mod dialect;
+mod documents;
+mod editorconfig;
 mod error;
+mod globs;
+mod input;
 mod output;
+mod walk;
+mod watch;
 pub use error::{Error, Result};
 
 use clap::Parser;
 use jjpwrgem_parse::{
-    error::diagnostics::{self, Diagnostic, Source},
+    Dialect, DuplicateKeys, ErrorKind,
+    error::{
+        Position, Positions,
+        diagnostics::{Diagnostic, Severity, Source},
+    },
     format::{self, LineEnding},
-    validate_str,
+    lint::{self, Linter, Node},
+    tokens::{ReadTokens, TokenStream},
+    validate_finite_numbers, validate_ijson, validate_ijson_at, validate_read_tokens,
+    validate_token_stream,
 };
 use jjpwrgem_ui::{Color, Style};
-use std::io::{IsTerminal, Read};
-use std::process::ExitCode;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{
+    io::{BufWriter, Read, Write},
+    num::NonZeroUsize,
+    ops::Range,
+    path::Path,
+    process::ExitCode,
+    sync::Mutex,
+};
 
-use crate::commands::Commands;
+use crate::commands::{Commands, DialectArg, DuplicateKeysArg, ProfileArg};
+use crate::config::{ConfigResolver, FormatSettings};
+use crate::documents::Documents;
+use crate::input::Input;
 use crate::output::Output;
+use crate::walk::Walker;
 
 fn main() -> ExitCode {
     let cli = commands::Cli::parse();
 
     let style = Style::Pretty(Color::Plain);
 
-    let buf = {
-        let mut stdin = std::io::stdin();
-
-        if stdin.is_terminal() {
-            anstream::eprintln!(
-                "{}",
-                style.render_message(Error::NonEmptyStdinRequired.into())
-            );
-            return ExitCode::FAILURE;
+    let (paths, files) = match &cli.command {
+        Commands::Format { paths, files, .. }
+        | Commands::Check { paths, files, .. }
+        | Commands::Lint { paths, files, .. }
+        | Commands::Convert { paths, files, .. } => (paths, files),
+        Commands::Explain { code } => {
+            let explanation = code.explanation().expect("parsed codes are explained");
+            let output = Output {
+                // explanations end with their own newline
+                final_newline: None,
+                ..Output::success(explanation)
+            };
+            return print_outputs(&[output], cli.deny_warnings);
         }
+    };
 
-        let mut buf = vec![];
-        stdin
-            .read_to_end(&mut buf)
-            .expect("Failed to read from stdin");
+    if paths.is_empty() {
+        let configs = Mutex::default();
+        let output = stream_stdin(&cli.command, &configs, style)
+            .unwrap_or_else(|| process(&cli.command, input::read_stdin(), &configs, style));
+        return print_outputs(&[output], cli.deny_warnings);
+    }
 
-        if buf.is_empty() {
-            anstream::eprintln!(
-                "{}",
-                style.render_message(Error::NonEmptyStdinRequired.into())
-            );
-            return ExitCode::FAILURE;
+    let walker = match Walker::new(files) {
+        Ok(walker) => walker,
+        Err(error) => {
+            return print_outputs(&[Output::failure_error(error, style)], cli.deny_warnings);
+        }
+    };
+    // 0 lets rayon pick based on the number of cpus
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(files.jobs.map_or(0, NonZeroUsize::get))
+        .build()
+        .expect("thread pool should be created");
+    let run_paths = || {
+        // config files can change between runs in watch mode, so lookups are
+        // only cached for a single run
+        let configs = Mutex::default();
+        let paths = walker.expand(paths);
+        let headers = paths.len() > 1;
+        // outputs are collected in input order so runs are deterministic
+        let outputs: Vec<Output> = pool.install(|| {
+            paths
+                .into_par_iter()
+                .map(|path| {
+                    let name = path.as_ref().ok().map(|path| path.display().to_string());
+                    let input = path.and_then(|path| input::read_file(&path));
+                    let output = process(&cli.command, input, &configs, style);
+                    match name {
+                        Some(name) if headers => output.with_header(&name),
+                        _ => output,
+                    }
+                })
+                .collect()
+        });
+        print_outputs(&outputs, cli.deny_warnings)
+    };
+
+    if files.watch {
+        match watch::watch(paths, &walker, run_paths) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => print_outputs(&[Output::failure_error(error, style)], cli.deny_warnings),
         }
+    } else {
+        run_paths()
+    }
+}
+
+fn process(
+    command: &Commands,
+    input: Result<Input>,
+    configs: &Mutex<ConfigResolver>,
+    style: Style,
+) -> Output {
+    input
+        .and_then(|input| run(command, &input, configs, style))
+        .unwrap_or_else(|error| Output::failure_error(error, style))
+}
 
-        buf
+/// checks or formats stdin straight to stdout, without holding all of the
+/// input or output in memory when possible. `None` when the command needs to
+/// collect its output first
+fn stream_stdin(
+    command: &Commands,
+    configs: &Mutex<ConfigResolver>,
+    style: Style,
+) -> Option<Output> {
+    let output = match command {
+        Commands::Check {
+            jsonc,
+            json5,
+            profile: None,
+            documents,
+            duplicate_keys,
+            ..
+        } if documents.documents().is_none()
+            && !duplicate_keys_policy(*duplicate_keys).is_reported() =>
+        {
+            let dialect = dialect(*jsonc, *json5, None);
+            settings_for(command, configs, None).and_then(|settings| {
+                let allow_lone_surrogates = settings.allow_lone_surrogates();
+                input::lock_stdin()
+                    .map(|stdin| check_reader(stdin, style, dialect, allow_lone_surrogates))
+            })
+        }
+        Commands::Format {
+            jsonc,
+            json5,
+            to,
+            documents,
+            ..
+        } if documents.documents().is_none() => {
+            let from = dialect(*jsonc, *json5, None);
+            let to = to.map_or(from, DialectArg::into_parse);
+            let settings = settings_for(command, configs, None);
+            // infinities and nans can only be found once the whole input is
+            // read, after some of it would already be uglified
+            let checks_numbers = from.is_json5() && !to.is_json5();
+            match settings {
+                // duplicates are only reported or dropped once the input is read
+                Ok(settings) if settings.duplicate_keys() != DuplicateKeys::KeepAll => {
+                    return None;
+                }
+                Ok(settings) if settings.uglify() && !checks_numbers => {
+                    input::lock_stdin().map(|stdin| uglify_reader(stdin, style, &settings, from))
+                }
+                Ok(settings) if !settings.uglify() && to == Dialect::Json => input::read_stdin()
+                    .map(|input| format_to_stdout(&input, style, &settings, from)),
+                Ok(_) => return None,
+                Err(error) => Err(error),
+            }
+        }
+        _ => return None,
     };
-    let json = match String::from_utf8(buf) {
-        Err(_) => {
-            anstream::eprintln!(
-                "{}",
-                style.render_diagnostic(diagnostics::invalid_encoding(Source::Stdin("")))
+    Some(output.unwrap_or_else(|error| Output::failure_error(error, style)))
+}
+
+/// prints outputs in order, failing if any of them failed or, with
+/// `deny_warnings`, warned
+fn print_outputs(outputs: &[Output], deny_warnings: bool) -> ExitCode {
+    let mut exit_code = ExitCode::SUCCESS;
+    for output in outputs {
+        print_output(output);
+        if output.exit_code == ExitCode::FAILURE || (deny_warnings && output.warned) {
+            exit_code = ExitCode::FAILURE;
+        }
+    }
+    exit_code
+}
+
+fn run(
+    command: &Commands,
+    input: &Input,
+    configs: &Mutex<ConfigResolver>,
+    style: Style,
+) -> Result<Output> {
+    let output = match command {
+        Commands::Format {
+            jsonc,
+            json5,
+            to,
+            documents,
+            ..
+        } => {
+            let settings = settings_for(command, configs, input.path.as_deref())?;
+            let from = dialect(*jsonc, *json5, input.path.as_deref());
+            let to = to.map_or(from, DialectArg::into_parse);
+            if let Some(documents) = documents.documents() {
+                format_documents(input, style, &settings, documents, from, to)
+            } else {
+                format(input, style, &settings, from, to)
+            }
+        }
+        Commands::Check {
+            jsonc,
+            json5,
+            profile,
+            documents,
+            duplicate_keys,
+            ..
+        } => {
+            let dialect = dialect(*jsonc, *json5, input.path.as_deref());
+            let duplicate_keys = duplicate_keys_policy(*duplicate_keys);
+            let allow_lone_surrogates =
+                settings_for(command, configs, input.path.as_deref())?.allow_lone_surrogates();
+            if let Some(documents) = documents.documents() {
+                check_documents(
+                    input,
+                    style,
+                    documents,
+                    dialect,
+                    *profile,
+                    duplicate_keys,
+                    allow_lone_surrogates,
+                )
+            } else {
+                check(
+                    input,
+                    style,
+                    dialect,
+                    *profile,
+                    duplicate_keys,
+                    allow_lone_surrogates,
+                )
+            }
+        }
+        Commands::Lint {
+            jsonc,
+            json5,
+            rule,
+            fix,
+            ..
+        } => {
+            let configured = configs
+                .lock()
+                .expect("config resolver should not be poisoned")
+                .lint_levels_for(input.path.as_deref())?;
+            let mut linter = Linter::default();
+            // flags come last so they win over config files
+            for (id, level) in configured.iter().chain(rule) {
+                linter.configure(id, level.into_parse());
+            }
+            let dialect = dialect(*jsonc, *json5, input.path.as_deref());
+            lint(input, style, dialect, &linter, *fix)
+        }
+        Commands::Convert { from, to, .. } => {
+            let settings = configs
+                .lock()
+                .expect("config resolver should not be poisoned")
+                .settings_for(input.path.as_deref())?;
+            let from = from.map_or_else(
+                || dialect::detect(input.path.as_deref()),
+                DialectArg::into_parse,
             );
-            return ExitCode::FAILURE;
+            convert(input, style, &settings, from, to.into_parse())
         }
-        Ok(s) => s,
+        Commands::Explain { .. } => unreachable!("explain doesn't read any input"),
     };
-    let output = match &cli.command {
+    Ok(output)
+}
+
+/// settings for `path` from config files, overridden by the command line
+fn settings_for(
+    command: &Commands,
+    configs: &Mutex<ConfigResolver>,
+    path: Option<&Path>,
+) -> Result<FormatSettings> {
+    let settings = configs
+        .lock()
+        .expect("config resolver should not be poisoned")
+        .settings_for(path)?;
+    Ok(format_flags(command).or(settings))
+}
+
+/// settings given on the command line, which override config files
+fn format_flags(command: &Commands) -> FormatSettings {
+    match command {
         Commands::Format {
             uglify,
             preferred_width,
             end_of_line,
-        } => format(
-            json,
-            style,
-            *uglify,
-            *preferred_width,
-            end_of_line.into_parse(),
-        ),
-        Commands::Check => check(json, style),
-    };
+            indent_style,
+            indent_width,
+            final_newline,
+            duplicate_keys,
+            allow_lone_surrogates,
+            ..
+        } => FormatSettings {
+            uglify: uglify.then_some(true),
+            preferred_width: *preferred_width,
+            end_of_line: *end_of_line,
+            indent_style: *indent_style,
+            indent_width: *indent_width,
+            final_newline: *final_newline,
+            duplicate_keys: *duplicate_keys,
+            allow_lone_surrogates: allow_lone_surrogates.then_some(true),
+        },
+        Commands::Check {
+            allow_lone_surrogates,
+            ..
+        } => FormatSettings {
+            allow_lone_surrogates: allow_lone_surrogates.then_some(true),
+            ..FormatSettings::default()
+        },
+        Commands::Lint { .. } | Commands::Convert { .. } | Commands::Explain { .. } => {
+            FormatSettings::default()
+        }
+    }
+}
 
-    print_output(&output);
+fn duplicate_keys_policy(arg: Option<DuplicateKeysArg>) -> DuplicateKeys {
+    arg.unwrap_or_default().into_parse()
+}
 
-    output.exit_code
+/// `--jsonc` and `--json5` force a dialect, otherwise it depends on the file name
+fn dialect(jsonc: bool, json5: bool, path: Option<&Path>) -> Dialect {
+    if jsonc {
+        Dialect::Jsonc
+    } else if json5 {
+        Dialect::Json5
+    } else {
+        dialect::detect(path)
+    }
 }
 
+/// formats input written in `from` as `to`
 pub fn format(
-    json: String,
+    input: &Input,
+    style: Style,
+    settings: &FormatSettings,
+    from: Dialect,
+    to: Dialect,
+) -> Output {
+    let json = input.text.as_str();
+    let result =
+        representable(json, from, to).and_then(|()| format_document(json, settings, from, to));
+
+    match result {
+        Ok(pretty) => {
+            let found = find_duplicate_keys(
+                json,
+                Position::START,
+                from,
+                settings.duplicate_keys(),
+                settings.allow_lone_surrogates(),
+            );
+            Output {
+                final_newline: settings.final_newline(),
+                ..Output::success_with_warnings(pretty, render_warnings(input, style, found))
+            }
+        }
+        Err(error) => {
+            let error = error.with_source_name(input.source_name());
+            Output::failure_diagnostic(Diagnostic::from(&error), style)
+        }
+    }
+}
+
+/// formats input as json straight to stdout, so the output is never held in
+/// memory. Nothing is written for invalid input
+pub fn format_to_stdout(
+    input: &Input,
+    style: Style,
+    settings: &FormatSettings,
+    from: Dialect,
+) -> Output {
+    let json = input.text.as_str();
+    let (options, width) = (settings.format_options(), settings.preferred_width());
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    let result = representable(json, from, Dialect::Json)
+        .and_then(|()| format::format_to_writer(json, from, options, width, &mut stdout))
+        .and_then(|()| end_stdout(&mut stdout, settings));
+
+    match result {
+        Ok(()) => Output::written(),
+        Err(error) => {
+            let error = error.with_source_name(input.source_name());
+            Output::failure_diagnostic(Diagnostic::from(&error), style)
+        }
+    }
+}
+
+/// json5 numbers that `to` has no way to write are errors
+fn representable(json: &str, from: Dialect, to: Dialect) -> jjpwrgem_parse::Result<'_, ()> {
+    if from.is_json5() && !to.is_json5() {
+        validate_finite_numbers(json, from)
+    } else {
+        Ok(())
+    }
+}
+
+fn format_document<'a>(
+    json: &'a str,
+    settings: &FormatSettings,
+    from: Dialect,
+    to: Dialect,
+) -> jjpwrgem_parse::Result<'a, String> {
+    let (options, width) = (settings.format_options(), settings.preferred_width());
+    match to {
+        _ if settings.uglify() => format::uglify_token_stream(&mut options.tokens(json, from)),
+        Dialect::Json => format::format_str_with_dialect(json, from, options, width),
+        Dialect::Jsonc | Dialect::Json5 => {
+            format::format_str_with_comments(json, from, options, width)
+        }
+    }
+}
+
+/// formats every document of the input as its own record
+pub fn format_documents(
+    input: &Input,
+    style: Style,
+    settings: &FormatSettings,
+    documents: Documents,
+    from: Dialect,
+    to: Dialect,
+) -> Output {
+    let json = input.text.as_str();
+    let ranges = match documents.split(json, from, to, settings.allow_lone_surrogates()) {
+        Ok(ranges) => ranges,
+        Err(errors) => return failure_diagnostics(input, style, errors),
+    };
+    let policy = settings.duplicate_keys();
+    let mut positions = Positions::new(json);
+    let found: Vec<_> = ranges
+        .iter()
+        .flat_map(|range| {
+            let (document, start) = (&json[range.clone()], positions.at(range.start));
+            let allow_lone_surrogates = settings.allow_lone_surrogates();
+            find_duplicate_keys(document, start, from, policy, allow_lone_surrogates)
+        })
+        .collect();
+    if policy == DuplicateKeys::Error && !found.is_empty() {
+        return failure_diagnostics(input, style, found);
+    }
+
+    let records: Vec<String> = ranges
+        .into_iter()
+        .map(|range| {
+            format_document(&json[range], settings, from, to).expect("documents were validated")
+        })
+        .collect();
+    Output {
+        final_newline: documents.final_newline(settings),
+        ..Output::success_with_warnings(
+            documents.join(&records, settings),
+            render_warnings(input, style, found),
+        )
+    }
+}
+
+/// writes the converted input to stdout and a warning for each change to stderr
+pub fn convert(
+    input: &Input,
     style: Style,
-    uglify: bool,
-    preferred_width: usize,
-    line_ending: LineEnding,
+    settings: &FormatSettings,
+    from: Dialect,
+    to: Dialect,
 ) -> Output {
-    let result = if uglify {
-        format::uglify_str(&json)
+    let json = input.text.as_str();
+    let (options, width) = (settings.format_options(), settings.preferred_width());
+    let conversion = match format::convert_str(json, from, to, options, width) {
+        Ok(conversion) => conversion,
+        Err(error) => {
+            let error = error.with_source_name(input.source_name());
+            return Output::failure_diagnostic(Diagnostic::from(&error), style);
+        }
+    };
+
+    let source = match &input.path {
+        Some(path) => Source::File { source: json, path },
+        None => Source::Stdin(json),
+    };
+    let warnings: Vec<String> = conversion
+        .changes
+        .into_iter()
+        .map(|change| {
+            let message = if change.replacement.is_empty() {
+                change.kind.to_string()
+            } else {
+                format!("{}, now `{}`", change.kind, change.replacement)
+            };
+            let diagnostic = Diagnostic::new(message, vec![], vec![], source, Some(change.range))
+                .with_severity(Severity::Warning);
+            style.render_diagnostic(diagnostic)
+        })
+        .collect();
+
+    Output {
+        final_newline: settings.final_newline(),
+        ..Output::success_with_warnings(
+            conversion.json,
+            (!warnings.is_empty()).then(|| warnings.join("\n")),
+        )
+    }
+}
+
+/// reports the findings of `linter`, failing if any of them are errors.
+/// Warnings only fail with `--deny-warnings`.
+/// With `fix`, prints the input with fixes applied and only reports findings
+/// without one
+pub fn lint(input: &Input, style: Style, dialect: Dialect, linter: &Linter, fix: bool) -> Output {
+    let json = input.text.as_str();
+    let node = match Node::parse(json, dialect) {
+        Ok(node) => node,
+        Err(error) => {
+            let error = error.with_source_name(input.source_name());
+            return Output::failure_diagnostic(Diagnostic::from(&error), style);
+        }
+    };
+    let findings = linter.lint(&node);
+    let (stdout, reported): (Option<String>, Vec<_>) = if fix {
+        let fixed = lint::apply_fixes(json, &findings);
+        (
+            Some(fixed),
+            findings.iter().filter(|f| f.fix.is_none()).collect(),
+        )
     } else {
-        format::prettify_str(&json, preferred_width, line_ending)
+        (None, findings.iter().collect())
+    };
+
+    let source = match &input.path {
+        Some(path) => Source::File { source: json, path },
+        None => Source::Stdin(json),
     };
+    let rendered: Vec<String> = reported
+        .iter()
+        .map(|finding| style.render_diagnostic(finding.to_diagnostic(source)))
+        .collect();
+    let failed = reported.iter().any(|f| f.severity == Severity::Error);
+    let warned = reported.iter().any(|f| f.severity == Severity::Warning);
+    Output {
+        stdout,
+        stderr: (!rendered.is_empty()).then(|| rendered.join("\n")),
+        exit_code: if failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        },
+        // fixed input keeps its own final newline
+        final_newline: (!fix).then_some(LineEnding::Lf),
+        warned,
+    }
+}
+
+/// the profile reports duplicate keys itself, so `duplicate_keys` only
+/// applies without one
+pub fn check(
+    input: &Input,
+    style: Style,
+    dialect: Dialect,
+    profile: Option<ProfileArg>,
+    duplicate_keys: DuplicateKeys,
+    allow_lone_surrogates: bool,
+) -> Output {
+    if let Some(ProfileArg::IJson) = profile {
+        return match validate_ijson(&input.text, dialect) {
+            Ok(()) => Output::silent(None),
+            Err(errors) => failure_diagnostics(input, style, errors),
+        };
+    }
+    let mut tokens = TokenStream::with_dialect(&input.text, dialect)
+        .with_duplicate_keys(duplicate_keys)
+        .allow_lone_surrogates(allow_lone_surrogates);
+    match validate_token_stream(&mut tokens) {
+        Ok(()) => Output::silent(render_warnings(input, style, tokens.warnings().to_vec())),
+        Err(error) => {
+            let error = error.with_source_name(input.source_name());
+            Output::failure_diagnostic(Diagnostic::from(&error), style)
+        }
+    }
+}
+
+/// checks input as it is read
+pub fn check_reader(
+    reader: impl Read,
+    style: Style,
+    dialect: Dialect,
+    allow_lone_surrogates: bool,
+) -> Output {
+    let tokens = ReadTokens::new(reader, dialect).allow_lone_surrogates(allow_lone_surrogates);
+    match validate_read_tokens(tokens) {
+        Ok(()) => Output::silent(None),
+        Err(error) => failure_streamed(error, style),
+    }
+}
 
+/// uglifies input to stdout as it is read. Invalid input leaves whatever was
+/// written before the error
+pub fn uglify_reader(
+    reader: impl Read,
+    style: Style,
+    settings: &FormatSettings,
+    dialect: Dialect,
+) -> Output {
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    let tokens =
+        ReadTokens::new(reader, dialect).allow_lone_surrogates(settings.allow_lone_surrogates());
+    let result = format::uglify_read_tokens(tokens, &mut stdout)
+        .and_then(|()| end_stdout(&mut stdout, settings));
     match result {
-        Ok(pretty) => Output::success(pretty),
-        Err(error) => Output::failure_diagnostic(Diagnostic::from(&error), style),
+        Ok(()) => Output::written(),
+        Err(error) => {
+            // whatever was written so far still goes out ahead of the error
+            let _ = stdout.flush();
+            failure_streamed(error, style)
+        }
+    }
+}
+
+/// writes the final newline if there is one and flushes
+fn end_stdout(
+    stdout: &mut impl Write,
+    settings: &FormatSettings,
+) -> jjpwrgem_parse::Result<'static, ()> {
+    let newline = settings.final_newline().map_or("", LineEnding::as_str);
+    stdout
+        .write_all(newline.as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|error| {
+            jjpwrgem_parse::Error::from_unterminated(ErrorKind::WriteFailed(error.to_string()), "")
+        })
+}
+
+/// a failure for an error found while streaming stdin
+fn failure_streamed(error: jjpwrgem_parse::Error, style: Style) -> Output {
+    if error.kind() == &ErrorKind::InvalidEncoding {
+        return Output::failure_error(Error::InvalidEncoding { path: None }, style);
     }
+    Output::failure_diagnostic(Diagnostic::from(&error), style)
+}
+
+/// checks every document of the input, reporting each invalid one
+pub fn check_documents(
+    input: &Input,
+    style: Style,
+    documents: Documents,
+    dialect: Dialect,
+    profile: Option<ProfileArg>,
+    duplicate_keys: DuplicateKeys,
+    allow_lone_surrogates: bool,
+) -> Output {
+    let json = input.text.as_str();
+    let split = documents.split(json, dialect, dialect, allow_lone_surrogates);
+    let mut positions = Positions::new(json);
+    let documents_at = move |ranges: Vec<Range<usize>>| {
+        ranges
+            .into_iter()
+            .map(move |range| (&json[range.clone()], positions.at(range.start)))
+    };
+    let errors = match (split, profile) {
+        (Ok(ranges), None) => {
+            let found: Vec<_> = documents_at(ranges)
+                .flat_map(|(document, start)| {
+                    find_duplicate_keys(
+                        document,
+                        start,
+                        dialect,
+                        duplicate_keys,
+                        allow_lone_surrogates,
+                    )
+                })
+                .collect();
+            if duplicate_keys == DuplicateKeys::Warn {
+                return Output::silent(render_warnings(input, style, found));
+            }
+            found
+        }
+        (Ok(ranges), Some(ProfileArg::IJson)) => documents_at(ranges)
+            .filter_map(|(document, start)| validate_ijson_at(document, start, dialect).err())
+            .flatten()
+            .collect(),
+        (Err(errors), _) => errors,
+    };
+    if errors.is_empty() {
+        Output::silent(None)
+    } else {
+        failure_diagnostics(input, style, errors)
+    }
+}
+
+/// the duplicate keys of `document`, which is at `start` in the input, that
+/// `policy` reports, which is only the first one for [`DuplicateKeys::Error`]
+fn find_duplicate_keys<'a>(
+    document: &'a str,
+    start: Position,
+    dialect: Dialect,
+    policy: DuplicateKeys,
+    allow_lone_surrogates: bool,
+) -> Vec<jjpwrgem_parse::Error<'a>> {
+    if !policy.is_reported() {
+        return Vec::new();
+    }
+    let mut tokens = TokenStream::with_dialect_at(document, dialect, start)
+        .with_duplicate_keys(policy)
+        .allow_lone_surrogates(allow_lone_surrogates);
+    match validate_token_stream(&mut tokens) {
+        Ok(()) => tokens.warnings().to_vec(),
+        Err(error) => vec![error],
+    }
+}
+
+/// renders each error as a warning, `None` without any
+fn render_warnings(
+    input: &Input,
+    style: Style,
+    warnings: Vec<jjpwrgem_parse::Error>,
+) -> Option<String> {
+    let rendered: Vec<String> = warnings
+        .into_iter()
+        .map(|warning| {
+            let warning = warning.with_source_name(input.source_name());
+            style.render_diagnostic(Diagnostic::from(&warning).with_severity(Severity::Warning))
+        })
+        .collect();
+    (!rendered.is_empty()).then(|| rendered.join("\n"))
 }
 
-pub fn check(json: String, style: Style) -> Output {
-    match validate_str(&json) {
-        Ok(_) => Output::success(""),
-        Err(error) => Output::failure_diagnostic(Diagnostic::from(&error), style),
+/// a failure listing every error
+fn failure_diagnostics(input: &Input, style: Style, errors: Vec<jjpwrgem_parse::Error>) -> Output {
+    let rendered: Vec<String> = errors
+        .into_iter()
+        .map(|error| {
+            let error = error.with_source_name(input.source_name());
+            style.render_diagnostic(Diagnostic::from(&error))
+        })
+        .collect();
+    Output {
+        stdout: None,
+        stderr: Some(rendered.join("\n")),
+        exit_code: ExitCode::FAILURE,
+        final_newline: Some(LineEnding::Lf),
+        warned: false,
     }
 }
 
 fn print_output(output: &Output) {
     if let Some(stdout) = &output.stdout {
-        println!("{stdout}");
+        let newline = output.final_newline.map_or("", LineEnding::as_str);
+        print!("{stdout}{newline}");
     }
     if let Some(stderr) = &output.stderr {
         anstream::eprintln!("{stderr}");
@@ -1,12 +1,21 @@
+use crate::Error;
 use core::fmt::Debug;
-use jjpwrgem_parse::error::diagnostics::Diagnostic;
+use jjpwrgem_parse::{
+    error::diagnostics::{self, Diagnostic, Source},
+    format::LineEnding,
+    validate_str,
+};
 use jjpwrgem_ui::Style;
-use std::process::ExitCode;
+use std::{path::Path, process::ExitCode};
 
 pub struct Output {
     pub stdout: Option<String>,
     pub stderr: Option<String>,
     pub exit_code: ExitCode,
+    /// the line ending stdout is followed by, if any
+    pub final_newline: Option<LineEnding>,
+    /// whether stderr reports warnings, which fail with `--deny-warnings`
+    pub warned: bool,
 }
 
 impl Output {
@@ -15,14 +24,95 @@ impl Output {
             stdout: Some(stdout.into()),
             stderr: None,
             exit_code: ExitCode::SUCCESS,
+            final_newline: Some(LineEnding::Lf),
+            warned: false,
         }
     }
 
+    /// success that reports `warnings` on stderr, if there are any
+    pub fn success_with_warnings(stdout: impl Into<String>, warnings: Option<String>) -> Self {
+        Output {
+            warned: warnings.is_some(),
+            stderr: warnings,
+            ..Self::success(stdout)
+        }
+    }
+
+    /// success that prints nothing but `warnings`, if there are any
+    pub fn silent(warnings: Option<String>) -> Self {
+        Output {
+            stdout: None,
+            ..Self::success_with_warnings("", warnings)
+        }
+    }
+
+    /// success for a command that already wrote its output
+    pub fn written() -> Self {
+        Output {
+            stdout: None,
+            ..Self::success("")
+        }
+    }
+
+    /// labels stdout with the file it came from, so the output for several
+    /// files can be split back up
+    pub fn with_header(mut self, name: &str) -> Self {
+        let newline = self.final_newline.unwrap_or(LineEnding::Lf).as_str();
+        self.stdout = self
+            .stdout
+            .map(|stdout| format!("==> {name} <=={newline}{stdout}"));
+        self
+    }
+
     pub fn failure_diagnostic(diagnostic: Diagnostic, style: Style) -> Self {
         Output {
             stdout: None,
             stderr: Some(style.render_diagnostic(diagnostic)),
             exit_code: ExitCode::FAILURE,
+            final_newline: Some(LineEnding::Lf),
+            warned: false,
+        }
+    }
+
+    pub fn failure_error(error: Error, style: Style) -> Self {
+        match &error {
+            Error::InvalidEncoding { path } => {
+                let source = match path {
+                    Some(path) => Source::File {
+                        source: "",
+                        path: Path::new(path),
+                    },
+                    None => Source::Stdin(""),
+                };
+                Self::failure_diagnostic(diagnostics::invalid_encoding(source), style)
+            }
+            Error::ConfigSyntax { path, text } => {
+                let parse_error = validate_str(text)
+                    .expect_err("config syntax errors come from invalid json")
+                    .with_source_name(path);
+                Self::failure_diagnostic(Diagnostic::from(&parse_error), style)
+            }
+            Error::TomlConfigSyntax {
+                path,
+                text,
+                message,
+                span,
+            } => {
+                let source = Source::File {
+                    source: text,
+                    path: Path::new(path),
+                };
+                let diagnostic =
+                    Diagnostic::new(message.clone(), vec![], vec![], source, span.clone());
+                Self::failure_diagnostic(diagnostic, style)
+            }
+            _ => Output {
+                stdout: None,
+                stderr: Some(style.render_message(error.into())),
+                exit_code: ExitCode::FAILURE,
+                final_newline: Some(LineEnding::Lf),
+                warned: false,
+            },
         }
     }
 }
@@ -0,0 +1,103 @@
+use crate::{Error, Result, commands::FileArgs, globs::PathGlobs};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// gitignore style file read in every directory on top of `.gitignore`
+pub const IGNORE_FILE_NAME: &str = ".jjpignore";
+
+/// directories that are never walked into, even without an ignore file
+pub const DEFAULT_EXCLUDES: [&str; 3] = [".git", "node_modules", "target"];
+
+/// files picked up from directories when no `--include` is given
+pub const DEFAULT_INCLUDES: [&str; 3] = ["*.json", "*.jsonc", "*.json5"];
+
+/// expands directories into the files to process
+#[derive(Debug)]
+pub struct Walker {
+    include: PathGlobs,
+    exclude: PathGlobs,
+}
+
+impl Walker {
+    pub fn new(args: &FileArgs) -> Result<Self> {
+        fn globs<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<PathGlobs> {
+            let mut globs = PathGlobs::default();
+            for glob in patterns {
+                globs.push(glob).map_err(|e| Error::InvalidGlob {
+                    glob: glob.to_owned(),
+                    reason: e.kind().to_string(),
+                })?;
+            }
+            Ok(globs)
+        }
+
+        let include = if args.include.is_empty() {
+            globs(DEFAULT_INCLUDES)?
+        } else {
+            globs(args.include.iter().map(String::as_str))?
+        };
+        let exclude = globs(
+            DEFAULT_EXCLUDES
+                .into_iter()
+                .chain(args.exclude.iter().map(String::as_str)),
+        )?;
+
+        Ok(Self { include, exclude })
+    }
+
+    /// files are kept as given, even if excluded, while directories are
+    /// replaced by the included files inside them in a stable order
+    pub fn expand(&self, paths: &[PathBuf]) -> Vec<Result<PathBuf>> {
+        paths
+            .iter()
+            .flat_map(|path| {
+                if path.is_dir() {
+                    self.walk(path)
+                } else {
+                    vec![Ok(path.clone())]
+                }
+            })
+            .collect()
+    }
+
+    /// whether walking a directory could pick up `relative`, a path relative
+    /// to the directory. Ignore files are not consulted
+    pub fn could_include(&self, relative: &Path) -> bool {
+        let excluded = relative
+            .ancestors()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .any(|dir| self.exclude.matches(dir));
+        !excluded && self.include.matches(relative)
+    }
+
+    fn walk(&self, root: &Path) -> Vec<Result<PathBuf>> {
+        let exclude = self.exclude.clone();
+        let filter_root = root.to_owned();
+        let walk = WalkBuilder::new(root)
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(IGNORE_FILE_NAME)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                entry.depth() == 0 || !exclude.matches(relative(&filter_root, entry.path()))
+            })
+            .build();
+
+        walk.filter_map(|entry| match entry {
+            Ok(entry) => {
+                let is_file = entry.file_type().is_some_and(|t| t.is_file());
+                (is_file && self.include.matches(relative(root, entry.path())))
+                    .then(|| Ok(entry.into_path()))
+            }
+            Err(e) => Some(Err(Error::ReadFile {
+                path: root.display().to_string(),
+                reason: e.to_string(),
+            })),
+        })
+        .collect()
+    }
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
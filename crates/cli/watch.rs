@@ -0,0 +1,128 @@
+use crate::{
+    Error, Result, config::CONFIG_FILE_NAMES, editorconfig::EDITORCONFIG_FILE_NAME,
+    walk::IGNORE_FILE_NAME, walk::Walker,
+};
+use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode};
+use std::{
+    fs,
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+/// editors often save with several writes, so events closer together than
+/// this are handled as one change
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// files that change how other files are picked up or formatted
+fn is_settings_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            CONFIG_FILE_NAMES.contains(&name)
+                || [EDITORCONFIG_FILE_NAME, IGNORE_FILE_NAME, ".gitignore"].contains(&name)
+        })
+}
+
+/// whether `path` was written or removed since `since`. Reading a file also
+/// sends an event, including the reads of the run itself, so those are
+/// told apart by the modification time
+fn changed_since(path: &Path, since: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_or(true, |modified| modified >= since)
+}
+
+/// a path given on the command line
+struct Root {
+    path: PathBuf,
+    is_dir: bool,
+}
+
+impl Root {
+    fn is_affected_by(&self, changed: &Path, walker: &Walker) -> bool {
+        if !self.is_dir {
+            // the file's directory is watched, so settings next to it are
+            // seen too
+            return changed == self.path
+                || (is_settings_file(changed) && changed.parent() == self.path.parent());
+        }
+        changed
+            .strip_prefix(&self.path)
+            .is_ok_and(|relative| walker.could_include(relative) || is_settings_file(changed))
+    }
+}
+
+/// calls `run` once, then again each time a file it could pick up changes
+///
+/// only returns early if watching fails
+pub fn watch(paths: &[PathBuf], walker: &Walker, mut run: impl FnMut() -> ExitCode) -> Result<()> {
+    let roots = paths
+        .iter()
+        .map(|path| {
+            std::path::absolute(path)
+                .map(|absolute| Root {
+                    is_dir: absolute.is_dir(),
+                    path: absolute,
+                })
+                .map_err(|e| Error::Watch {
+                    path: path.display().to_string(),
+                    reason: e.to_string(),
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(DEBOUNCE, tx).map_err(|e| Error::Watch {
+        path: ".".into(),
+        reason: e.to_string(),
+    })?;
+    for root in &roots {
+        // editors often save by replacing the file, which ends watches on the
+        // file itself, so files are watched through their directory
+        let (target, mode) = match (root.is_dir, root.path.parent()) {
+            (false, Some(parent)) => (parent, RecursiveMode::NonRecursive),
+            _ => (root.path.as_path(), RecursiveMode::Recursive),
+        };
+        debouncer
+            .watcher()
+            .watch(target, mode)
+            .map_err(|e| Error::Watch {
+                path: root.path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+    }
+
+    let mut last_run = rerun(&mut run);
+    for events in rx {
+        let events = events.map_err(|e| Error::Watch {
+            path: ".".into(),
+            reason: e.to_string(),
+        })?;
+        let affected = events.iter().any(|event| {
+            roots.iter().any(|r| r.is_affected_by(&event.path, walker))
+                && changed_since(&event.path, last_run)
+        });
+        if affected {
+            last_run = rerun(&mut run);
+        }
+    }
+
+    Ok(())
+}
+
+/// returns when the run started
+fn rerun(run: &mut impl FnMut() -> ExitCode) -> SystemTime {
+    let started = SystemTime::now();
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        // clear the screen and move the cursor to the top left
+        let _ = write!(stdout, "\x1b[2J\x1b[H");
+        let _ = stdout.flush();
+    }
+    run();
+    anstream::eprintln!("watching for changes, press ctrl+c to stop");
+    started
+}
@@ -1,21 +1,31 @@
-use crate::{Result, tokens::TokenStream, traverse::parse_tokens};
+pub mod spanned;
+
+use crate::{
+    Result,
+    tokens::{Dialect, TokenStream, unescape},
+    traverse::parse_tokens,
+};
 use std::borrow::Cow;
 use visitor::AstVisitor;
 
 #[derive(Debug, Clone, Default, Eq)]
-pub struct ObjectEntries<'a>(pub Vec<(&'a str, Value<'a>)>);
+pub struct ObjectEntries<'a>(pub Vec<(Cow<'a, str>, Value<'a>)>);
 
 impl<'a> ObjectEntries<'a> {
     pub fn new() -> Self {
         Self(Vec::new())
     }
 
-    pub fn push(&mut self, k: &'a str, v: Value<'a>) {
-        self.0.push((k, v));
+    pub fn push(&mut self, k: impl Into<Cow<'a, str>>, v: Value<'a>) {
+        self.0.push((k.into(), v));
     }
 
-    pub fn get(&self, k: &'a str) -> Option<&Value<'a>> {
-        self.0.iter().find_map(|(k2, v)| (k == *k2).then_some(v))
+    /// the value of the first entry whose decoded key is `k`, so `"caf\u00e9"`
+    /// is found by `get("café")`
+    pub fn get(&self, k: &str) -> Option<&Value<'a>> {
+        self.0
+            .iter()
+            .find_map(|(k2, v)| (unescape(k2) == k).then_some(v))
     }
 
     pub fn len(&self) -> usize {
@@ -27,9 +37,15 @@ impl<'a> ObjectEntries<'a> {
     }
 }
 
+impl<'a> From<Vec<(Cow<'a, str>, Value<'a>)>> for ObjectEntries<'a> {
+    fn from(value: Vec<(Cow<'a, str>, Value<'a>)>) -> Self {
+        ObjectEntries(value)
+    }
+}
+
 impl<'a> From<Vec<(&'a str, Value<'a>)>> for ObjectEntries<'a> {
     fn from(value: Vec<(&'a str, Value<'a>)>) -> Self {
-        ObjectEntries(value)
+        ObjectEntries(value.into_iter().map(|(k, v)| (k.into(), v)).collect())
     }
 }
 
@@ -39,23 +55,48 @@ impl<'a> PartialEq for ObjectEntries<'a> {
             return false;
         }
 
-        self.0.iter().all(|(k, v)| other.get(k) == Some(v))
+        self.0
+            .iter()
+            .all(|(k, v)| other.get(&unescape(k)) == Some(v))
     }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Value<'a> {
     Null,
-    String(&'a str),
+    /// the escaped contents, as written between the quotes. See [`Value::as_str`]
+    String(Cow<'a, str>),
     Number(Cow<'a, str>),
     Object(ObjectEntries<'a>),
     Array(Vec<Value<'a>>),
     Boolean(bool),
 }
 
+impl Value<'_> {
+    /// the decoded contents of a string
+    pub fn as_str(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::String(s) => Some(unescape(s)),
+            _ => None,
+        }
+    }
+}
+
 pub fn parse_str<'a>(json: &'a str) -> Result<'a, Value<'a>> {
-    let mut ast = AstVisitor::new();
-    parse_tokens(&mut TokenStream::new(json), json, true, &mut ast)?;
+    parse_str_with_dialect(json, Dialect::Json)
+}
+
+pub fn parse_str_with_dialect<'a>(json: &'a str, dialect: Dialect) -> Result<'a, Value<'a>> {
+    parse_token_stream(&mut TokenStream::with_dialect(json, dialect))
+}
+
+/// parses the value lexed by `tokens`, for lexers configured with more than a
+/// dialect such as [`TokenStream::allow_lone_surrogates`]. Entries with
+/// duplicate keys are kept or dropped following
+/// [`TokenStream::with_duplicate_keys`]
+pub fn parse_token_stream<'a>(tokens: &mut TokenStream<'a>) -> Result<'a, Value<'a>> {
+    let mut ast = AstVisitor::new(tokens.duplicate_keys());
+    parse_tokens(tokens, true, &mut ast)?;
     Ok(ast
         .finish()
         .expect("visitor should error if empty or unfinished"))
@@ -64,21 +105,34 @@ pub fn parse_str<'a>(json: &'a str) -> Result<'a, Value<'a>> {
 mod visitor {
     use crate::{
         ast::{ObjectEntries, Value},
+        events::DuplicateKeys,
+        tokens::unescape,
         traverse::Visitor,
     };
-    use std::borrow::Cow;
+    use core::{
+        convert::Infallible,
+        ops::{ControlFlow, Range},
+    };
+    use std::{
+        borrow::Cow,
+        collections::{HashMap, hash_map::Entry},
+    };
 
     #[derive(Debug, Default)]
     pub struct AstVisitor<'a> {
         stack: Vec<AstFrame<'a>>,
         result: Option<Value<'a>>,
+        duplicate_keys: DuplicateKeys,
     }
 
     #[derive(Debug)]
     enum AstFrame<'a> {
         Object {
             entries: ObjectEntries<'a>,
-            current_key: Option<&'a str>,
+            current_key: Option<Cow<'a, str>>,
+            /// where each decoded key is in `entries`, only kept when
+            /// duplicates are dropped
+            positions: HashMap<String, usize>,
         },
         Array {
             items: Vec<Value<'a>>,
@@ -86,10 +140,11 @@ mod visitor {
     }
 
     impl<'a> AstVisitor<'a> {
-        pub fn new() -> Self {
+        pub fn new(duplicate_keys: DuplicateKeys) -> Self {
             Self {
                 stack: Vec::new(),
                 result: None,
+                duplicate_keys,
             }
         }
 
@@ -104,11 +159,29 @@ mod visitor {
                     AstFrame::Object {
                         entries,
                         current_key,
+                        positions,
                     } => {
                         let k = current_key
                             .take()
                             .expect("the traverser should not emit a value before the key");
-                        entries.push(k, value);
+                        if !matches!(
+                            self.duplicate_keys,
+                            DuplicateKeys::KeepFirst | DuplicateKeys::KeepLast
+                        ) {
+                            entries.push(k, value);
+                            return;
+                        }
+                        match positions.entry(unescape(&k).into_owned()) {
+                            Entry::Vacant(entry) => {
+                                entry.insert(entries.len());
+                                entries.push(k, value);
+                            }
+                            Entry::Occupied(entry) => {
+                                if self.duplicate_keys == DuplicateKeys::KeepLast {
+                                    entries.0[*entry.get()] = (k, value);
+                                }
+                            }
+                        }
                     }
                 },
             }
@@ -120,22 +193,27 @@ mod visitor {
     }
 
     impl<'a> Visitor<'a> for AstVisitor<'a> {
-        fn on_object_open(&mut self) {
+        type Break = Infallible;
+
+        fn on_object_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
             self.stack.push(AstFrame::Object {
                 entries: ObjectEntries::new(),
                 current_key: None,
+                positions: HashMap::new(),
             });
+            ControlFlow::Continue(())
         }
 
-        fn on_object_key(&mut self, key: &'a str) {
+        fn on_object_key(&mut self, key: Cow<'a, str>, _: Range<usize>) -> ControlFlow<Infallible> {
             if let Some(AstFrame::Object { current_key, .. }) = self.stack.last_mut() {
                 *current_key = Some(key);
             } else {
                 unreachable!("must be in object for object key")
             }
+            ControlFlow::Continue(())
         }
 
-        fn on_object_close(&mut self) {
+        fn on_object_close(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
             let frame = self
                 .stack
                 .pop()
@@ -145,12 +223,14 @@ mod visitor {
             } else {
                 unreachable!("must be an object to close object")
             }
+            ControlFlow::Continue(())
         }
 
-        fn on_array_open(&mut self) {
+        fn on_array_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
             self.stack.push(AstFrame::Array { items: Vec::new() });
+            ControlFlow::Continue(())
         }
-        fn on_array_close(&mut self) {
+        fn on_array_close(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
             let frame = self
                 .stack
                 .pop()
@@ -160,32 +240,35 @@ mod visitor {
             } else {
                 unreachable!("must be an array to close array")
             }
+            ControlFlow::Continue(())
         }
 
-        fn on_null(&mut self) {
+        fn on_null(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
             self.emit_value(Value::Null);
+            ControlFlow::Continue(())
         }
 
-        fn on_string(&mut self, s: &'a str) {
+        fn on_string(&mut self, s: Cow<'a, str>, _: Range<usize>) -> ControlFlow<Infallible> {
             self.emit_value(Value::String(s));
+            ControlFlow::Continue(())
         }
 
-        fn on_number(&mut self, n: Cow<'a, str>) {
+        fn on_number(&mut self, n: Cow<'a, str>, _: Range<usize>) -> ControlFlow<Infallible> {
             self.emit_value(Value::Number(n));
+            ControlFlow::Continue(())
         }
 
-        fn on_boolean(&mut self, b: bool) {
+        fn on_boolean(&mut self, b: bool, _: Range<usize>) -> ControlFlow<Infallible> {
             self.emit_value(Value::Boolean(b));
+            ControlFlow::Continue(())
         }
-
-        fn on_object_key_val_delim(&mut self) {}
-        fn on_item_delim(&mut self) {}
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::DuplicateKeys;
 
     fn kv_to_map<'a>(tuples: &[(&'a str, Value<'a>)]) -> Value<'a> {
         Value::Object(tuples.to_vec().into())
@@ -200,7 +283,7 @@ mod tests {
     fn one_key_value_pair() {
         assert_eq!(
             parse_str(r#"{"hi":"bye"}"#).unwrap(),
-            kv_to_map(&[("hi", Value::String("bye"))])
+            kv_to_map(&[("hi", Value::String("bye".into()))])
         );
     }
 
@@ -222,7 +305,7 @@ mod tests {
             "#
             )
             .unwrap(),
-            nested(nested(nested(nested(Value::String("rust")))))
+            nested(nested(nested(nested(Value::String("rust".into())))))
         );
     }
 
@@ -251,4 +334,94 @@ mod tests {
     fn primitives(#[case] json: &str, #[case] expected: Value) {
         assert_eq!(parse_str(json), Ok(expected));
     }
+
+    #[test]
+    fn keys_are_compared_decoded() {
+        let Value::Object(entries) = parse_str(r#"{"caf\u00e9": 1, "\ud83e\udd80": 2}"#).unwrap()
+        else {
+            panic!("expected an object");
+        };
+        assert_eq!(entries.get("café"), Some(&Value::Number("1".into())));
+        assert_eq!(entries.get("🦀"), Some(&Value::Number("2".into())));
+        assert_eq!(
+            parse_str(r#"{"café": 1, "🦀": 2}"#).unwrap(),
+            Value::Object(entries)
+        );
+    }
+
+    #[test]
+    fn strings_decode() {
+        let value = parse_str(r#""tab\there""#).unwrap();
+        assert_eq!(value, Value::String(r"tab\there".into()));
+        assert_eq!(value.as_str().as_deref(), Some("tab\there"));
+        assert_eq!(Value::Null.as_str(), None);
+    }
+
+    #[rstest::rstest]
+    #[case(DuplicateKeys::KeepAll, &[("a", "1"), ("b", "2"), ("a", "3")])]
+    #[case(DuplicateKeys::Warn, &[("a", "1"), ("b", "2"), ("a", "3")])]
+    #[case(DuplicateKeys::KeepFirst, &[("a", "1"), ("b", "2")])]
+    #[case(DuplicateKeys::KeepLast, &[("a", "3"), ("b", "2")])]
+    fn duplicate_key_policies(
+        #[case] duplicate_keys: DuplicateKeys,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let mut tokens =
+            TokenStream::new(r#"{"a": 1, "b": 2, "a": 3}"#).with_duplicate_keys(duplicate_keys);
+        let Value::Object(entries) = parse_token_stream(&mut tokens).unwrap() else {
+            panic!("expected an object");
+        };
+        let entries: Vec<_> = entries
+            .0
+            .iter()
+            .map(|(k, v)| match v {
+                Value::Number(n) => (k.as_ref(), n.as_ref()),
+                _ => panic!("expected numbers"),
+            })
+            .collect();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn duplicate_keys_are_found_decoded() {
+        let mut tokens = TokenStream::new(r#"{"\u0061": 1, "a": 2}"#)
+            .with_duplicate_keys(DuplicateKeys::KeepLast);
+        assert_eq!(
+            parse_token_stream(&mut tokens).unwrap(),
+            kv_to_map(&[("a", Value::Number("2".into()))])
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("[1, 2,]")]
+    #[case("[1, 2, ]")]
+    #[case("[1, /* two */ 2 // trailing\n,]")]
+    fn jsonc_array_trailing_comma(#[case] json: &str) {
+        assert_eq!(
+            parse_str_with_dialect(json, Dialect::Jsonc).unwrap(),
+            Value::Array(vec![Value::Number("1".into()), Value::Number("2".into())])
+        );
+    }
+
+    #[test]
+    fn jsonc_object_trailing_comma_and_comments() {
+        let json = r#"{
+            // compiler options
+            "strict": true, /* inline */
+            "paths": {},
+        }"#;
+        assert_eq!(
+            parse_str_with_dialect(json, Dialect::Jsonc).unwrap(),
+            kv_to_map(&[("strict", Value::Boolean(true)), ("paths", kv_to_map(&[])),])
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("[,]")]
+    #[case("[1,,]")]
+    #[case("{,}")]
+    #[case(r#"{"a": 1,,}"#)]
+    fn jsonc_only_allows_a_single_trailing_comma(#[case] json: &str) {
+        assert!(parse_str_with_dialect(json, Dialect::Jsonc).is_err());
+    }
 }
@@ -0,0 +1,188 @@
+//! a value tree that keeps where each part was found, for tools that point
+//! back into the input like the linter and the formatter for json with
+//! comments
+
+use crate::{
+    Result,
+    tokens::{Dialect, TokenStream},
+    traverse::parse_tokens,
+};
+use core::ops::Range;
+use std::borrow::Cow;
+use visitor::NodeVisitor;
+
+/// a value and where it was found. Containers cover their delimiters
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Node<'a> {
+    pub value: NodeValue<'a>,
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NodeValue<'a> {
+    Null,
+    Boolean(bool),
+    Number(Cow<'a, str>),
+    /// the escaped contents, as written between the quotes
+    String(Cow<'a, str>),
+    Array(Vec<Node<'a>>),
+    Object(Vec<Member<'a>>),
+}
+
+/// an object entry, with every entry kept even if its key was used before
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Member<'a> {
+    /// the escaped key, as written between the quotes
+    pub key: Cow<'a, str>,
+    pub key_range: Range<usize>,
+    pub value: Node<'a>,
+}
+
+impl<'a> Node<'a> {
+    pub fn parse(json: &'a str, dialect: Dialect) -> Result<'a, Self> {
+        Self::from_token_stream(&mut TokenStream::with_dialect(json, dialect))
+    }
+
+    /// [`Node::parse`] for lexers configured with more than a dialect
+    pub fn from_token_stream(tokens: &mut TokenStream<'a>) -> Result<'a, Self> {
+        let mut visitor = NodeVisitor::default();
+        parse_tokens(tokens, true, &mut visitor)?;
+        Ok(visitor
+            .finish()
+            .expect("visitor should error if empty or unfinished"))
+    }
+}
+
+mod visitor {
+    use super::{Member, Node, NodeValue};
+    use crate::traverse::Visitor;
+    use core::{
+        convert::Infallible,
+        ops::{ControlFlow, Range},
+    };
+    use std::borrow::Cow;
+
+    #[derive(Debug, Default)]
+    pub struct NodeVisitor<'a> {
+        stack: Vec<Frame<'a>>,
+        result: Option<Node<'a>>,
+    }
+
+    #[derive(Debug)]
+    enum Frame<'a> {
+        Object {
+            members: Vec<Member<'a>>,
+            key: Option<(Cow<'a, str>, Range<usize>)>,
+        },
+        Array {
+            items: Vec<Node<'a>>,
+        },
+    }
+
+    impl<'a> NodeVisitor<'a> {
+        fn emit(&mut self, value: NodeValue<'a>, range: Range<usize>) {
+            let node = Node { value, range };
+            match self.stack.last_mut() {
+                None => self.result = Some(node),
+                Some(Frame::Array { items }) => items.push(node),
+                Some(Frame::Object { members, key }) => {
+                    let (key, key_range) = key
+                        .take()
+                        .expect("the traverser should not emit a value before the key");
+                    members.push(Member {
+                        key,
+                        key_range,
+                        value: node,
+                    });
+                }
+            }
+        }
+
+        pub fn finish(self) -> Option<Node<'a>> {
+            self.result
+        }
+    }
+
+    impl<'a> Visitor<'a> for NodeVisitor<'a> {
+        type Break = Infallible;
+
+        fn on_object_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
+            self.stack.push(Frame::Object {
+                members: Vec::new(),
+                key: None,
+            });
+            ControlFlow::Continue(())
+        }
+
+        fn on_object_key(
+            &mut self,
+            new_key: Cow<'a, str>,
+            range: Range<usize>,
+        ) -> ControlFlow<Infallible> {
+            if let Some(Frame::Object { key, .. }) = self.stack.last_mut() {
+                *key = Some((new_key, range));
+            } else {
+                unreachable!("must be in object for object key")
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn on_object_close(&mut self, range: Range<usize>) -> ControlFlow<Infallible> {
+            match self.stack.pop() {
+                Some(Frame::Object { members, .. }) => self.emit(NodeValue::Object(members), range),
+                _ => unreachable!("must be an object to close object"),
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn on_array_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
+            self.stack.push(Frame::Array { items: Vec::new() });
+            ControlFlow::Continue(())
+        }
+
+        fn on_array_close(&mut self, range: Range<usize>) -> ControlFlow<Infallible> {
+            match self.stack.pop() {
+                Some(Frame::Array { items }) => self.emit(NodeValue::Array(items), range),
+                _ => unreachable!("must be an array to close array"),
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn on_null(&mut self, range: Range<usize>) -> ControlFlow<Infallible> {
+            self.emit(NodeValue::Null, range);
+            ControlFlow::Continue(())
+        }
+
+        fn on_string(&mut self, s: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Infallible> {
+            self.emit(NodeValue::String(s), range);
+            ControlFlow::Continue(())
+        }
+
+        fn on_number(&mut self, n: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Infallible> {
+            self.emit(NodeValue::Number(n), range);
+            ControlFlow::Continue(())
+        }
+
+        fn on_boolean(&mut self, b: bool, range: Range<usize>) -> ControlFlow<Infallible> {
+            self.emit(NodeValue::Boolean(b), range);
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nodes_keep_ranges() {
+        let json = r#"{"a": [1, null]}"#;
+        let node = Node::parse(json, Dialect::Json).unwrap();
+        assert_eq!(node.range, 0..json.len());
+        let NodeValue::Object(members) = &node.value else {
+            panic!("expected an object");
+        };
+        assert_eq!(&json[members[0].key_range.clone()], r#""a""#);
+        assert_eq!(&json[members[0].value.range.clone()], "[1, null]");
+    }
+}
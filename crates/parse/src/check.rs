@@ -1,30 +1,92 @@
-use std::borrow;
+use core::{convert::Infallible, ops::Range};
+use std::io::Read;
 
 use crate::{
-    Result,
-    tokens::TokenStream,
-    traverse::{Visitor, parse_tokens},
+    ErrorKind, Result,
+    error::Position,
+    tokens::{
+        Dialect, ReadTokens, Token, TokenStream, TokenWithContext,
+        json5::{INFINITY, NAN},
+    },
+    traverse::{Tokens, Visitor, parse_tokens},
 };
 
 #[derive(Debug)]
 pub struct NoopVisitor;
 
-impl<'a> Visitor<'a> for NoopVisitor {
-    fn on_object_open(&mut self) {}
-    fn on_object_key(&mut self, _key: &'a str) {}
-    fn on_object_close(&mut self) {}
-    fn on_array_open(&mut self) {}
-    fn on_array_close(&mut self) {}
-    fn on_null(&mut self) {}
-    fn on_string(&mut self, _value: &'a str) {}
-    fn on_number(&mut self, _value: borrow::Cow<'a, str>) {}
-    fn on_boolean(&mut self, _value: bool) {}
-    fn on_object_key_val_delim(&mut self) {}
-    fn on_item_delim(&mut self) {}
+impl Visitor<'_> for NoopVisitor {
+    type Break = Infallible;
 }
 
 pub fn validate_str<'a>(json: &'a str) -> Result<'a, ()> {
-    let mut visitor = NoopVisitor;
-    parse_tokens(&mut TokenStream::new(json), json, true, &mut visitor)?;
+    validate_str_with_dialect(json, Dialect::Json)
+}
+
+pub fn validate_str_with_dialect<'a>(json: &'a str, dialect: Dialect) -> Result<'a, ()> {
+    validate_token_stream(&mut TokenStream::with_dialect(json, dialect))
+}
+
+/// validates the value lexed by `tokens`, for lexers configured with more
+/// than a dialect such as [`TokenStream::allow_lone_surrogates`]
+pub fn validate_token_stream<'a>(tokens: &mut TokenStream<'a>) -> Result<'a, ()> {
+    parse_tokens(tokens, true, &mut NoopVisitor)?;
+    Ok(())
+}
+
+/// validates input read a piece at a time, so it can be larger than memory.
+/// Errors point into the whole input but only keep the text around them
+pub fn validate_reader(reader: impl Read, dialect: Dialect) -> Result<'static, ()> {
+    validate_read_tokens(ReadTokens::new(reader, dialect))
+}
+
+/// [`validate_reader`] for readers configured with more than a dialect
+pub fn validate_read_tokens(mut tokens: ReadTokens<impl Read>) -> Result<'static, ()> {
+    parse_tokens(&mut tokens, true, &mut NoopVisitor)?;
+    Ok(())
+}
+
+/// errors on the first `Infinity` or `NaN`, which json5 allows but json and
+/// jsonc can't represent
+pub fn validate_finite_numbers<'a>(json: &'a str, dialect: Dialect) -> Result<'a, ()> {
+    finite_numbers(TokenStream::with_dialect(json, dialect))
+}
+
+/// validates `document`, which is at `start` in a larger input, returning the
+/// range of its value. Ranges and line numbers refer to the whole input
+pub fn validate_at<'a>(
+    document: &'a str,
+    start: Position,
+    dialect: Dialect,
+) -> Result<'a, Range<usize>> {
+    validate_document(&mut TokenStream::with_dialect_at(document, dialect, start))
+}
+
+/// [`validate_at`] for lexers configured with more than a dialect, such as
+/// ones made with [`TokenStream::with_dialect_at`]
+pub fn validate_document<'a>(tokens: &mut TokenStream<'a>) -> Result<'a, Range<usize>> {
+    parse_tokens(tokens, true, &mut NoopVisitor)
+}
+
+/// [`validate_finite_numbers`] for `document`, which is at `start` in a larger
+/// input
+pub fn validate_finite_numbers_at<'a>(
+    document: &'a str,
+    start: Position,
+    dialect: Dialect,
+) -> Result<'a, ()> {
+    finite_numbers(TokenStream::with_dialect_at(document, dialect, start))
+}
+
+/// strings are lexed allowing lone surrogates, since only numbers are checked
+fn finite_numbers(tokens: TokenStream<'_>) -> Result<'_, ()> {
+    let mut tokens = tokens.allow_lone_surrogates(true);
+    while let Some(token) = tokens.next() {
+        let TokenWithContext { token, range } = token?;
+        if let Token::Number(n) = &token
+            && (n.ends_with(INFINITY) || n == NAN)
+        {
+            return Err(tokens.error(ErrorKind::NonFiniteNumber(token), range));
+        }
+    }
     Ok(())
 }
@@ -1,11 +1,17 @@
+pub mod codes;
 pub mod diagnostics;
 
+pub use codes::ErrorCode;
+
 use crate::tokens::CharWithContext;
+use crate::tokens::javascript::JavaScriptSyntax;
+use crate::tokens::json5::Json5Syntax;
 use crate::tokens::lexical::trim_end_whitespace;
 use crate::tokens::{JsonCharOption, Token, TokenOption, TokenWithContext, lexical::JsonChar};
 use core::fmt::Display;
 use core::ops::{Deref, Range};
 use displaydoc::Display;
+use std::borrow::Cow;
 use thiserror::Error;
 
 pub type Result<'a, T> = std::result::Result<T, Error<'a>>;
@@ -66,6 +72,11 @@ pub enum ErrorKind<'a> {
         exponent_range: Range<usize>,
         maybe_c: JsonCharOption,
     },
+    /// expected hex digit after `0x`, found {maybe_c}
+    ExpectedHexDigitInNumber {
+        number_range: Range<usize>,
+        maybe_c: JsonCharOption,
+    },
 
     // string
     /// unexpected unescaped control character `{0}` in string literal
@@ -75,13 +86,14 @@ pub enum ErrorKind<'a> {
         open_range: Range<usize>,
         string_range: Range<usize>,
     },
-    /// expected hex digit {digit_idx} of 4 in escape, found {maybe_c}
+    /// expected hex digit {digit_idx} of {digit_count} in escape, found {maybe_c}
     ExpectedHexDigit {
         quote_range: Range<usize>,
         slash_range: Range<usize>,
         u_range: Range<usize>,
         maybe_c: JsonCharOption,
         digit_idx: usize,
+        digit_count: usize,
     },
     /** expected escapable sequence, found {maybe_c}.
     valid escapes are `\"`, `\\`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t` or `\uXXXX` (4 hex digits) */
@@ -91,14 +103,77 @@ pub enum ErrorKind<'a> {
         string_range: Range<usize>,
         quote_range: Range<usize>,
     },
+    /// high surrogate escape is not followed by a low surrogate escape
+    LoneHighSurrogate,
+    /// low surrogate escape is not preceded by a high surrogate escape
+    LoneLowSurrogate,
+    /// mismatched surrogate escapes, a high surrogate must be directly followed by a low surrogate
+    MismatchedSurrogates {
+        /// the escape before the one the error is on
+        first: Range<usize>,
+        /// whether `first` is a high surrogate, otherwise it is a low surrogate
+        /// followed by a high one
+        first_high: bool,
+    },
+
+    // i-json
+    /// duplicate key `{key}`, keys have to be unique
+    DuplicateKey {
+        /// the escaped contents of the key
+        key: Cow<'a, str>,
+        /// the key it duplicates
+        first: Range<usize>,
+    },
+    /// number is outside the range of an IEEE 754 double, so it is read differently everywhere
+    NumberOutOfRange,
+    /// number is read as {read_as} by implementations that use IEEE 754 doubles
+    NumberLosesPrecision { read_as: String },
+    /// string contains the noncharacter U+{0:04X}, which is reserved for use inside programs
+    Noncharacter(u32),
+
+    // comment
+    /// comments are not allowed in json
+    CommentNotAllowed,
+    /// unterminated block comment
+    UnterminatedBlockComment,
+
+    // json5
+    /// {syntax} is json5 syntax, did you mean to use --json5?
+    Json5Syntax {
+        syntax: Json5Syntax,
+        /// the json equivalent, if json can represent it
+        replacement: Option<String>,
+    },
+    /// {0} can't be represented in json
+    NonFiniteNumber(Token<'a>),
+
+    // javascript
+    /// {syntax} is only allowed in javascript
+    JavaScriptSyntax {
+        syntax: JavaScriptSyntax,
+        /// the json equivalent, if json can represent it
+        replacement: Option<String>,
+    },
 
     // misc
     /// source did not contain valid utf8
     InvalidEncoding,
+    /// failed to read input: {0}
+    ReadFailed(String),
+    /// failed to write output: {0}
+    WriteFailed(String),
     /// unexpected character `{0}`. expected start of a json value
     UnexpectedCharacter(JsonChar),
+    /// `{found}` is not a json value, did you mean `{keyword}`?
+    MisspelledKeyword {
+        found: Cow<'a, str>,
+        /// the keyword it most likely was meant to be
+        keyword: &'static str,
+    },
     /// unexpected token {0} after json finished
     TokenAfterEnd(Token<'a>),
+    /// expected record separator before json text
+    ExpectedRecordSeparator,
 }
 
 impl<'a> ErrorKind<'a> {
@@ -114,6 +189,189 @@ impl<'a> ErrorKind<'a> {
             }
         })
     }
+
+    /// applies `f` to every range the kind refers to, copying any borrowed
+    /// tokens
+    pub(crate) fn map_ranges(
+        self,
+        mut f: impl FnMut(Range<usize>) -> Range<usize>,
+    ) -> ErrorKind<'static> {
+        fn ctx(
+            f: &mut impl FnMut(Range<usize>) -> Range<usize>,
+            TokenWithContext { token, range }: TokenWithContext,
+        ) -> TokenWithContext<'static> {
+            TokenWithContext {
+                token: token.into_owned(),
+                range: f(range),
+            }
+        }
+
+        match self {
+            ErrorKind::ExpectedKey(ctx_, found) => {
+                ErrorKind::ExpectedKey(ctx(&mut f, ctx_), found.into_owned())
+            }
+            ErrorKind::ExpectedColon(ctx_, found) => {
+                ErrorKind::ExpectedColon(ctx(&mut f, ctx_), found.into_owned())
+            }
+            ErrorKind::ExpectedValue(ctx_, found) => {
+                ErrorKind::ExpectedValue(ctx_.map(|c| ctx(&mut f, c)), found.into_owned())
+            }
+            ErrorKind::ExpectedEntryOrClosedDelimiter {
+                open_ctx,
+                expected,
+                found,
+            } => ErrorKind::ExpectedEntryOrClosedDelimiter {
+                open_ctx: ctx(&mut f, open_ctx),
+                expected,
+                found: found.into_owned(),
+            },
+            ErrorKind::ExpectedCommaOrClosedCurlyBrace {
+                range,
+                open_ctx,
+                found,
+            } => {
+                let open_ctx = ctx(&mut f, open_ctx);
+                ErrorKind::ExpectedCommaOrClosedCurlyBrace {
+                    range: f(range),
+                    open_ctx,
+                    found: found.into_owned(),
+                }
+            }
+            ErrorKind::ExpectedOpenBrace {
+                expected,
+                context,
+                found,
+            } => ErrorKind::ExpectedOpenBrace {
+                expected,
+                context: context.map(|c| ctx(&mut f, c)),
+                found: found.into_owned(),
+            },
+            ErrorKind::ExpectedDigitFollowingMinus(range, found) => {
+                ErrorKind::ExpectedDigitFollowingMinus(f(range), found)
+            }
+            ErrorKind::UnexpectedLeadingZero { initial, extra } => {
+                ErrorKind::UnexpectedLeadingZero {
+                    initial: f(initial),
+                    extra: f(extra),
+                }
+            }
+            ErrorKind::ExpectedDigitAfterDot {
+                number_range,
+                dot_range,
+                maybe_c,
+            } => ErrorKind::ExpectedDigitAfterDot {
+                number_range: f(number_range),
+                dot_range: f(dot_range),
+                maybe_c,
+            },
+            ErrorKind::ExpectedPlusOrMinusOrDigitAfterE {
+                number_range,
+                e_range,
+                maybe_c,
+            } => ErrorKind::ExpectedPlusOrMinusOrDigitAfterE {
+                number_range: f(number_range),
+                e_range: f(e_range),
+                maybe_c,
+            },
+            ErrorKind::ExpectedDigitAfterE {
+                number_range,
+                exponent_range,
+                maybe_c,
+            } => ErrorKind::ExpectedDigitAfterE {
+                number_range: f(number_range),
+                exponent_range: f(exponent_range),
+                maybe_c,
+            },
+            ErrorKind::ExpectedHexDigitInNumber {
+                number_range,
+                maybe_c,
+            } => ErrorKind::ExpectedHexDigitInNumber {
+                number_range: f(number_range),
+                maybe_c,
+            },
+            ErrorKind::ExpectedQuote {
+                open_range,
+                string_range,
+            } => ErrorKind::ExpectedQuote {
+                open_range: f(open_range),
+                string_range: f(string_range),
+            },
+            ErrorKind::ExpectedHexDigit {
+                quote_range,
+                slash_range,
+                u_range,
+                maybe_c,
+                digit_idx,
+                digit_count,
+            } => ErrorKind::ExpectedHexDigit {
+                quote_range: f(quote_range),
+                slash_range: f(slash_range),
+                u_range: f(u_range),
+                maybe_c,
+                digit_idx,
+                digit_count,
+            },
+            ErrorKind::ExpectedEscape {
+                maybe_c,
+                slash_range,
+                string_range,
+                quote_range,
+            } => ErrorKind::ExpectedEscape {
+                maybe_c,
+                slash_range: f(slash_range),
+                string_range: f(string_range),
+                quote_range: f(quote_range),
+            },
+            ErrorKind::LoneHighSurrogate => ErrorKind::LoneHighSurrogate,
+            ErrorKind::LoneLowSurrogate => ErrorKind::LoneLowSurrogate,
+            ErrorKind::MismatchedSurrogates { first, first_high } => {
+                ErrorKind::MismatchedSurrogates {
+                    first: f(first),
+                    first_high,
+                }
+            }
+            ErrorKind::DuplicateKey { key, first } => ErrorKind::DuplicateKey {
+                key: Cow::Owned(key.into_owned()),
+                first: f(first),
+            },
+            ErrorKind::NumberOutOfRange => ErrorKind::NumberOutOfRange,
+            ErrorKind::NumberLosesPrecision { read_as } => {
+                ErrorKind::NumberLosesPrecision { read_as }
+            }
+            ErrorKind::Noncharacter(code) => ErrorKind::Noncharacter(code),
+            ErrorKind::ExpectedMinusOrDigit(c) => ErrorKind::ExpectedMinusOrDigit(c),
+            ErrorKind::UnexpectedControlCharacterInString(c) => {
+                ErrorKind::UnexpectedControlCharacterInString(c)
+            }
+            ErrorKind::CommentNotAllowed => ErrorKind::CommentNotAllowed,
+            ErrorKind::UnterminatedBlockComment => ErrorKind::UnterminatedBlockComment,
+            ErrorKind::Json5Syntax {
+                syntax,
+                replacement,
+            } => ErrorKind::Json5Syntax {
+                syntax,
+                replacement,
+            },
+            ErrorKind::NonFiniteNumber(token) => ErrorKind::NonFiniteNumber(token.into_owned()),
+            ErrorKind::JavaScriptSyntax {
+                syntax,
+                replacement,
+            } => ErrorKind::JavaScriptSyntax {
+                syntax,
+                replacement,
+            },
+            ErrorKind::InvalidEncoding => ErrorKind::InvalidEncoding,
+            ErrorKind::ReadFailed(reason) => ErrorKind::ReadFailed(reason),
+            ErrorKind::WriteFailed(reason) => ErrorKind::WriteFailed(reason),
+            ErrorKind::UnexpectedCharacter(c) => ErrorKind::UnexpectedCharacter(c),
+            ErrorKind::MisspelledKeyword { found, keyword } => ErrorKind::MisspelledKeyword {
+                found: Cow::Owned(found.into_owned()),
+                keyword,
+            },
+            ErrorKind::TokenAfterEnd(token) => ErrorKind::TokenAfterEnd(token.into_owned()),
+            ErrorKind::ExpectedRecordSeparator => ErrorKind::ExpectedRecordSeparator,
+        }
+    }
 }
 
 fn closing_delimiter_for_open(token: &Token) -> Option<JsonChar> {
@@ -146,6 +404,73 @@ pub struct ErrorInner<'a> {
     column: usize,
     source_text: String,
     source_name: String,
+    /// where `source_text` starts in the input. Only errors found while
+    /// reading input a piece at a time, or in one of several documents, see
+    /// part of it
+    start: Position,
+    /// whether `source_text` holds less or more than the document the error
+    /// is in, so patches can't be checked by parsing it
+    partial: bool,
+}
+
+/// a place in the input
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub offset: usize,
+    /// 1 indexed line number
+    pub line: usize,
+    /// 1 indexed column number
+    pub column: usize,
+}
+
+impl Position {
+    pub const START: Self = Self {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+
+    /// the position at the end of `text`, which starts here
+    pub fn after(self, text: &str) -> Self {
+        match text.rfind('\n') {
+            Some(i) => Self {
+                offset: self.offset + text.len(),
+                line: self.line + text.matches('\n').count(),
+                column: text[i + 1..].chars().count() + 1,
+            },
+            None => Self {
+                offset: self.offset + text.len(),
+                column: self.column + text.chars().count(),
+                ..self
+            },
+        }
+    }
+}
+
+/// positions of offsets in an input, which only scans the text between each
+/// offset and the one before when they increase, like the starts of
+/// documents in order
+#[derive(Debug, Clone)]
+pub struct Positions<'a> {
+    input: &'a str,
+    last: Position,
+}
+
+impl<'a> Positions<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            last: Position::START,
+        }
+    }
+
+    pub fn at(&mut self, offset: usize) -> Position {
+        if offset < self.last.offset {
+            self.last = Position::START;
+        }
+        self.last = self.last.after(&self.input[self.last.offset..offset]);
+        self.last
+    }
 }
 
 impl<'a> From<ErrorInner<'a>> for Error<'a> {
@@ -163,7 +488,7 @@ impl<'a> Deref for Error<'a> {
 }
 
 impl<'a> Error<'a> {
-    pub fn new(kind: ErrorKind<'a>, range: Range<usize>, text: &'a str) -> Self {
+    pub fn new(kind: ErrorKind<'a>, range: Range<usize>, text: &str) -> Self {
         // TODO take this as a param or have some sort of context
         let source_name = "stdin".into();
         let (line, column) = get_line_and_column(text, range.clone());
@@ -174,11 +499,81 @@ impl<'a> Error<'a> {
             column,
             source_text: text.into(),
             source_name,
+            start: Position::START,
+            partial: false,
+        }
+        .into()
+    }
+
+    /// moves an error created against part of the input to where that part
+    /// starts
+    pub(crate) fn starting_at(mut self, start: Position) -> Self {
+        if self.0.line == 1 {
+            self.0.column += start.column - 1;
+        }
+        self.0.line += start.line - 1;
+        self.0.start = start;
+        self
+    }
+
+    /// marks an error whose text isn't exactly its document, like a window of
+    /// streamed input or concatenated documents
+    pub(crate) fn partial(mut self) -> Self {
+        self.0.partial = true;
+        self
+    }
+
+    /// copies any borrowed tokens so the error can outlive its input
+    pub fn into_owned(self) -> Error<'static> {
+        let ErrorInner {
+            kind,
+            range,
+            line,
+            column,
+            source_text,
+            source_name,
+            start,
+            partial,
+        } = *self.0;
+        ErrorInner {
+            kind: kind.map_ranges(|range| range),
+            range,
+            line,
+            column,
+            source_text,
+            source_name,
+            start,
+            partial,
         }
         .into()
     }
 
-    pub fn from_unterminated(kind: ErrorKind<'a>, text: &'a str) -> Self {
+    pub fn kind(&self) -> &ErrorKind<'a> {
+        &self.0.kind
+    }
+
+    /// byte range of the error in the input
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset + self.0.range.start..self.start.offset + self.0.range.end
+    }
+
+    /// 1 indexed line number
+    pub fn line(&self) -> usize {
+        self.0.line
+    }
+
+    /// 1 indexed column number
+    pub fn column(&self) -> usize {
+        self.0.column
+    }
+
+    /// names the source in diagnostics, for example a file path
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.0.source_name = name.into();
+        self
+    }
+
+    pub fn from_unterminated(kind: ErrorKind<'a>, text: &str) -> Self {
         let trimmed = trim_end_whitespace(text);
         // TODO handle multibyte characters properly
         // text.char_indices().rev()
@@ -249,4 +644,31 @@ mod tests {
     ) {
         assert_eq!(get_line_and_column(text, range), expected);
     }
+
+    #[test]
+    fn finds_positions_in_order_and_after_going_back() {
+        let text = "ab\ncdé\n\nf";
+        let mut positions = Positions::new(text);
+        let found: Vec<_> = [0, 1, 3, 5, 7, 8, 9, 4]
+            .into_iter()
+            .map(|offset| {
+                let position = positions.at(offset);
+                assert_eq!(position.offset, offset);
+                (position.line, position.column)
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (1, 1),
+                (1, 2),
+                (2, 1),
+                (2, 3),
+                (2, 4),
+                (3, 1),
+                (4, 1),
+                (2, 2)
+            ]
+        );
+    }
 }
@@ -0,0 +1,198 @@
+//! stable codes for every [`ErrorKind`], shown in the header of diagnostics
+//! and explained in long form by `jjp explain`. Codes are never reused, so
+//! they are safe to grep for or link to
+
+use super::ErrorKind;
+use core::fmt::Display;
+
+/// identifies a kind of error, written like `JJP0001`
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct ErrorCode(u16);
+
+/// explanations in code order, the first one is for `JJP0001`
+const EXPLANATIONS: [&str; 36] = [
+    include_str!("explanations/JJP0001.md"),
+    include_str!("explanations/JJP0002.md"),
+    include_str!("explanations/JJP0003.md"),
+    include_str!("explanations/JJP0004.md"),
+    include_str!("explanations/JJP0005.md"),
+    include_str!("explanations/JJP0006.md"),
+    include_str!("explanations/JJP0007.md"),
+    include_str!("explanations/JJP0008.md"),
+    include_str!("explanations/JJP0009.md"),
+    include_str!("explanations/JJP0010.md"),
+    include_str!("explanations/JJP0011.md"),
+    include_str!("explanations/JJP0012.md"),
+    include_str!("explanations/JJP0013.md"),
+    include_str!("explanations/JJP0014.md"),
+    include_str!("explanations/JJP0015.md"),
+    include_str!("explanations/JJP0016.md"),
+    include_str!("explanations/JJP0017.md"),
+    include_str!("explanations/JJP0018.md"),
+    include_str!("explanations/JJP0019.md"),
+    include_str!("explanations/JJP0020.md"),
+    include_str!("explanations/JJP0021.md"),
+    include_str!("explanations/JJP0022.md"),
+    include_str!("explanations/JJP0023.md"),
+    include_str!("explanations/JJP0024.md"),
+    include_str!("explanations/JJP0025.md"),
+    include_str!("explanations/JJP0026.md"),
+    include_str!("explanations/JJP0027.md"),
+    include_str!("explanations/JJP0028.md"),
+    include_str!("explanations/JJP0029.md"),
+    include_str!("explanations/JJP0030.md"),
+    include_str!("explanations/JJP0031.md"),
+    include_str!("explanations/JJP0032.md"),
+    include_str!("explanations/JJP0033.md"),
+    include_str!("explanations/JJP0034.md"),
+    include_str!("explanations/JJP0035.md"),
+    include_str!("explanations/JJP0036.md"),
+];
+
+impl ErrorCode {
+    const PREFIX: &str = "JJP";
+
+    /// reads a code like `JJP0001`, ignoring case. `None` if no error has it
+    pub fn parse(code: &str) -> Option<Self> {
+        let digits = code
+            .get(..Self::PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(Self::PREFIX))
+            .and_then(|_| code.get(Self::PREFIX.len()..))
+            .filter(|digits| digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_digit()))?;
+        let code = Self(digits.parse().ok()?);
+        code.explanation().map(|_| code)
+    }
+
+    /// every code in order
+    pub fn all() -> impl Iterator<Item = Self> {
+        (1..=EXPLANATIONS.len() as u16).map(Self)
+    }
+
+    /// a long form description of the error with examples, in markdown
+    pub fn explanation(self) -> Option<&'static str> {
+        EXPLANATIONS
+            .get(usize::from(self.0).checked_sub(1)?)
+            .copied()
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{:04}", Self::PREFIX, self.0)
+    }
+}
+
+impl ErrorKind<'_> {
+    pub const fn code(&self) -> ErrorCode {
+        ErrorCode(match self {
+            ErrorKind::ExpectedColon(..) => 1,
+            ErrorKind::ExpectedKey(..) => 2,
+            ErrorKind::ExpectedValue(..) => 3,
+            ErrorKind::ExpectedEntryOrClosedDelimiter { .. } => 4,
+            ErrorKind::ExpectedCommaOrClosedCurlyBrace { .. } => 5,
+            ErrorKind::ExpectedOpenBrace { .. } => 6,
+            ErrorKind::ExpectedDigitFollowingMinus(..) => 7,
+            ErrorKind::ExpectedMinusOrDigit(..) => 8,
+            ErrorKind::UnexpectedLeadingZero { .. } => 9,
+            ErrorKind::ExpectedDigitAfterDot { .. } => 10,
+            ErrorKind::ExpectedPlusOrMinusOrDigitAfterE { .. } => 11,
+            ErrorKind::ExpectedDigitAfterE { .. } => 12,
+            ErrorKind::ExpectedHexDigitInNumber { .. } => 13,
+            ErrorKind::UnexpectedControlCharacterInString(..) => 14,
+            ErrorKind::ExpectedQuote { .. } => 15,
+            ErrorKind::ExpectedHexDigit { .. } => 16,
+            ErrorKind::ExpectedEscape { .. } => 17,
+            ErrorKind::LoneHighSurrogate => 18,
+            ErrorKind::LoneLowSurrogate => 19,
+            ErrorKind::MismatchedSurrogates { .. } => 20,
+            ErrorKind::DuplicateKey { .. } => 21,
+            ErrorKind::NumberOutOfRange => 22,
+            ErrorKind::NumberLosesPrecision { .. } => 23,
+            ErrorKind::Noncharacter(..) => 24,
+            ErrorKind::CommentNotAllowed => 25,
+            ErrorKind::UnterminatedBlockComment => 26,
+            ErrorKind::Json5Syntax { .. } => 27,
+            ErrorKind::NonFiniteNumber(..) => 28,
+            ErrorKind::InvalidEncoding => 29,
+            ErrorKind::ReadFailed(..) => 30,
+            ErrorKind::WriteFailed(..) => 31,
+            ErrorKind::UnexpectedCharacter(..) => 32,
+            ErrorKind::TokenAfterEnd(..) => 33,
+            ErrorKind::ExpectedRecordSeparator => 34,
+            ErrorKind::MisspelledKeyword { .. } => 35,
+            ErrorKind::JavaScriptSyntax { .. } => 36,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DuplicateKeys, Error, tokens::TokenStream, validate_str, validate_token_stream};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("JJP0001", Some(1))]
+    #[case("jjp0036", Some(36))]
+    #[case("JJP0037", None)]
+    #[case("JJP0000", None)]
+    #[case("JJP1", None)]
+    #[case("JJP+001", None)]
+    #[case("0001", None)]
+    #[case("", None)]
+    fn parse(#[case] code: &str, #[case] expected: Option<u16>) {
+        assert_eq!(ErrorCode::parse(code), expected.map(ErrorCode));
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for code in ErrorCode::all() {
+            assert_eq!(ErrorCode::parse(&code.to_string()), Some(code));
+        }
+    }
+
+    #[test]
+    fn explanations_have_examples() {
+        for code in ErrorCode::all() {
+            let explanation = code.explanation().unwrap();
+            assert!(
+                explanation.contains("```json"),
+                "{code} should have examples"
+            );
+        }
+    }
+
+    fn code_of(json: &str) -> ErrorCode {
+        let error: Error = validate_str(json).unwrap_err();
+        error.kind().code()
+    }
+
+    #[rstest]
+    #[case(r#"{"name" "ferris"}"#, 1)]
+    #[case(r#"{"name": "ferris",}"#, 2)]
+    #[case(r#"{"name": }"#, 3)]
+    #[case(r#"{"name": "ferris""#, 5)]
+    #[case("-a", 7)]
+    #[case("01", 9)]
+    #[case("1.", 27)]
+    #[case("1e", 11)]
+    #[case("\"\t\"", 14)]
+    #[case(r#""ferris"#, 15)]
+    #[case(r#""\u12""#, 16)]
+    #[case(r#""\x""#, 17)]
+    #[case("// comment\n{}", 25)]
+    #[case("true false", 33)]
+    #[case("[True]", 35)]
+    #[case("[undefined]", 36)]
+    fn kinds_have_codes(#[case] json: &str, #[case] expected: u16) {
+        assert_eq!(code_of(json), ErrorCode(expected));
+    }
+
+    #[test]
+    fn duplicate_key_code() {
+        let mut tokens =
+            TokenStream::new(r#"{"a": 1, "a": 2}"#).with_duplicate_keys(DuplicateKeys::Error);
+        let error = validate_token_stream(&mut tokens).unwrap_err();
+        assert_eq!(error.kind().code().to_string(), "JJP0021");
+    }
+}
@@ -1,11 +1,18 @@
 use crate::{
-    Error, ErrorKind,
-    tokens::{JsonCharOption, Token, TokenOption, TokenWithContext, lexical::JsonChar},
+    Dialect, Error, ErrorKind,
+    tokens::{
+        JsonCharOption, Token, TokenOption, TokenWithContext,
+        javascript::JavaScriptSyntax,
+        json5::Json5Syntax,
+        lexical::{JsonChar, trim_end_whitespace},
+    },
+    validate_str_with_dialect,
 };
-use core::ops::Range;
+use core::{cmp::Reverse, ops::Range};
 use std::{borrow::Cow, path::Path};
 pub const EXPECTED_COMMA_OR_CLOSED_CURLY_MESSAGE: &str = "the preceding key/value pair";
 pub const INSERT_MISSING_CLOSED_BRACE_HELP: &str = "insert the missing closed brace";
+const REPLACEMENT_CHARACTER_ESCAPE: &str = r"\uFFFD";
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Context<'a> {
@@ -15,7 +22,7 @@ pub struct Context<'a> {
 }
 
 impl<'a> Context<'a> {
-    fn new(message: impl Into<Cow<'a, str>>, span: Range<usize>, source: Source<'a>) -> Self {
+    pub fn new(message: impl Into<Cow<'a, str>>, span: Range<usize>, source: Source<'a>) -> Self {
         Self {
             message: message.into(),
             span,
@@ -32,7 +39,7 @@ pub struct Patch<'a> {
 }
 
 impl<'a> Patch<'a> {
-    fn new(
+    pub fn new(
         message: impl Into<Cow<'a, str>>,
         span: Range<usize>,
         source: Source<'a>,
@@ -52,12 +59,45 @@ pub enum Source<'a> {
     Stdin(&'a str),
     File { source: &'a str, path: &'a Path },
 }
+
+/// how serious a [`Diagnostic`] is
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    #[default]
+    Error,
+    /// reported without failing, for example changes made while converting
+    Warning,
+    /// worth knowing about, but nothing needs to change
+    Info,
+    /// a suggestion, the least serious
+    Hint,
+}
+
+impl core::fmt::Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+            Self::Hint => "hint",
+        })
+    }
+}
+
 pub struct Diagnostic<'a> {
+    pub severity: Severity,
+    /// shown in the header, such as the [`ErrorCode`](crate::ErrorCode) of an error
+    pub code: Option<String>,
     pub message: String,
     pub range: Option<Range<usize>>,
     pub context: Vec<Context<'a>>,
     pub patches: Vec<Patch<'a>>,
     pub source: Source<'a>,
+    /// the line number `source` starts on
+    pub line_start: usize,
+    /// the column number `source` starts on, when it starts partway through
+    /// a line
+    pub column_start: usize,
 }
 
 impl<'a> Diagnostic<'a> {
@@ -69,13 +109,27 @@ impl<'a> Diagnostic<'a> {
         range: Option<Range<usize>>,
     ) -> Self {
         Self {
+            severity: Severity::Error,
+            code: None,
             message,
             context,
             patches,
             source,
             range,
+            line_start: 1,
+            column_start: 1,
         }
     }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
 }
 
 fn error_source<'a>(error: &'a Error<'a>) -> Source<'a> {
@@ -90,221 +144,490 @@ fn error_source<'a>(error: &'a Error<'a>) -> Source<'a> {
 }
 
 impl<'a> From<&'a Error<'a>> for Vec<Patch<'a>> {
+    /// every fix for the error, best first. Alternative fixes are ranked by
+    /// re-parsing the text with each applied
     fn from(error: &'a Error<'a>) -> Self {
-        let source = error_source(error);
-        match &error.kind {
-            ErrorKind::ExpectedKey(
-                TokenWithContext {
+        let patches = candidate_patches(error);
+        if patches.len() > 1 && !error.partial {
+            rank_patches(&error.source_text, patches)
+        } else {
+            patches
+        }
+    }
+}
+
+/// orders patches by how far past them the patched text parses, keeping the
+/// original order for ties. Text that becomes valid ranks first. The text is
+/// re-parsed as json5 so comments and other extensions don't stop it early,
+/// only the structure is compared
+fn rank_patches<'a>(text: &str, patches: Vec<Patch<'a>>) -> Vec<Patch<'a>> {
+    let mut ranked: Vec<_> = patches
+        .into_iter()
+        .map(|patch| {
+            let patched = [
+                &text[..patch.span.start],
+                &patch.replacement,
+                &text[patch.span.end..],
+            ]
+            .concat();
+            let patch_end = patch.span.start + patch.replacement.len();
+            let progress = match validate_str_with_dialect(&patched, Dialect::Json5) {
+                Ok(()) => usize::MAX,
+                Err(error) => error.range.start.saturating_sub(patch_end),
+            };
+            (progress, patch)
+        })
+        .collect();
+    ranked.sort_by_key(|(progress, _)| Reverse(*progress));
+    ranked.into_iter().map(|(_, patch)| patch).collect()
+}
+
+/// the end of the token before `offset`, skipping whitespace
+fn previous_token_end(text: &str, offset: usize) -> usize {
+    trim_end_whitespace(&text[..offset]).len()
+}
+
+/// removes the whitespace between two numbers, for a number typed with a
+/// space in it like `1 000`. `None` unless `found` directly continues a number
+fn join_numbers_patch<'a>(error: &'a Error<'a>, found: &Token) -> Option<Patch<'a>> {
+    let Token::Number(number) = found else {
+        return None;
+    };
+    let previous_end = previous_token_end(&error.source_text, error.range.start);
+    let previous_is_digit =
+        error.source_text[..previous_end].ends_with(|c: char| c.is_ascii_digit());
+    (previous_is_digit && number.starts_with(|c: char| c.is_ascii_digit())).then(|| {
+        Patch::new(
+            "is this one number? consider removing the space",
+            previous_end..error.range.start,
+            error_source(error),
+            "",
+        )
+    })
+}
+
+/// whether `s` starts with a `\u` escape of a low surrogate
+fn starts_with_low_surrogate(s: &str) -> bool {
+    s.strip_prefix("\\u")
+        .and_then(|rest| rest.get(..4))
+        .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .is_some_and(|code| (0xDC00..0xE000).contains(&code))
+}
+
+fn candidate_patches<'a>(error: &'a Error<'a>) -> Vec<Patch<'a>> {
+    let source = error_source(error);
+    match &error.kind {
+        ErrorKind::ExpectedKey(
+            TokenWithContext {
+                token: Token::Comma,
+                range,
+            },
+            TokenOption(Some(_)),
+        ) => {
+            vec![Patch::new(
+                "consider removing the trailing comma",
+                range.clone(),
+                source,
+                "",
+            )]
+        }
+        ErrorKind::ExpectedKey(
+            TokenWithContext {
+                token: Token::Comma,
+                range,
+            },
+            TokenOption(None),
+        ) => {
+            vec![Patch::new(
+                "consider replacing the trailing comma with a closed curly brace",
+                range.clone(),
+                source,
+                "}",
+            )]
+        }
+        ErrorKind::ExpectedColon(ctx, found) => {
+            let (message, replacement) = match found.0.as_ref() {
+                None => (
+                    "insert colon, placeholder value, and closing curly brace",
+                    r#": "garlic bread" }"#,
+                ),
+                Some(Token::Comma) | Some(Token::ClosedCurlyBrace) => {
+                    ("insert colon and placeholder value", r#": "🐟🛹""#)
+                }
+                // the key might be missing its value instead, such as
+                // `{"a" "b": 1}`
+                Some(Token::String(_)) => {
+                    let end = ctx.range.end..ctx.range.end;
+                    return vec![
+                        Patch::new("insert the missing colon", end.clone(), source, ": "),
+                        Patch::new(
+                            "insert a placeholder value and a comma",
+                            end,
+                            source,
+                            r#": "🐟🛹","#,
+                        ),
+                    ];
+                }
+                _ => ("insert the missing colon", ": "),
+            };
+
+            vec![Patch::new(
+                message,
+                ctx.range.end..ctx.range.end,
+                source,
+                replacement,
+            )]
+        }
+        ErrorKind::ExpectedEntryOrClosedDelimiter {
+            expected,
+            found: TokenOption(None),
+            ..
+        } => vec![Patch::new(
+            Cow::Owned(format!("insert the missing closed delimiter `{expected}`")),
+            error.range.end..error.range.end,
+            source,
+            expected.to_string(),
+        )],
+        ErrorKind::ExpectedEntryOrClosedDelimiter {
+            expected,
+            found: TokenOption(Some(found)),
+            ..
+        } => {
+            let previous_end = previous_token_end(&error.source_text, error.range.start);
+            let comma = (expected.0 == ']' && found.is_start_of_value()).then(|| {
+                Patch::new(
+                    "insert the missing comma",
+                    previous_end..previous_end,
+                    source,
+                    ",",
+                )
+            });
+            let mismatched = matches!(found, Token::ClosedCurlyBrace | Token::ClosedSquareBracket)
+                .then(|| {
+                    Patch::new(
+                        Cow::Owned(format!("replace it with `{expected}`")),
+                        error.range.clone(),
+                        source,
+                        expected.to_string(),
+                    )
+                });
+            comma
+                .into_iter()
+                .chain(join_numbers_patch(error, found))
+                .chain(mismatched)
+                .collect()
+        }
+        ErrorKind::ExpectedCommaOrClosedCurlyBrace { range, found, .. } => match found.0.as_ref() {
+            Some(Token::String(s)) => vec![Patch::new(
+                Cow::Owned(format!("is {s:?} a key? consider adding a comma")),
+                range.end..range.end,
+                source,
+                ",",
+            )],
+            Some(found @ Token::Number(_)) => {
+                join_numbers_patch(error, found).into_iter().collect()
+            }
+            None => vec![Patch::new(
+                INSERT_MISSING_CLOSED_BRACE_HELP,
+                range.end..range.end,
+                source,
+                "}",
+            )],
+            _ => Vec::new(),
+        },
+        ErrorKind::ExpectedValue(ctx, tok_opt) => match (ctx, tok_opt.0.as_ref()) {
+            (
+                Some(TokenWithContext {
                     token: Token::Comma,
                     range,
-                },
-                TokenOption(Some(_)),
-            ) => {
+                }),
+                Some(Token::ClosedSquareBracket),
+            ) => vec![Patch::new(
+                "consider removing the trailing comma",
+                range.clone(),
+                source,
+                "",
+            )],
+            (_, None) => vec![Patch::new(
+                "insert a placeholder value",
+                error.range.end..error.range.end,
+                source,
+                " \"rust is a must\"",
+            )],
+            (_, Some(Token::ClosedCurlyBrace)) => vec![Patch::new(
+                "consider adding the missing open curly brace",
+                error.range.end - 1..error.range.end,
+                source,
+                "{}",
+            )],
+            _ => Vec::new(),
+        },
+        ErrorKind::UnexpectedControlCharacterInString(escaped) => vec![Patch::new(
+            "replace the control character with its escaped form",
+            error.range.clone(),
+            source,
+            escaped.to_string(),
+        )],
+        ErrorKind::TokenAfterEnd(token) => vec![Patch::new(
+            format!("consider removing the trailing content (starting with {token})"),
+            error.range.start..error.source_text.len(),
+            source,
+            "",
+        )],
+        ErrorKind::ExpectedDigitFollowingMinus(range, found) => {
+            let patch_info = match found.0 {
+                None => ("insert placeholder digits after the minus sign", "194"),
+                Some(JsonChar('.')) => (
+                    "did you mean to add a fraction? consider adding a 0 before the period",
+                    "0",
+                ),
+                _ => return vec![],
+            };
+            let (message, replacement) = patch_info;
+            {
                 vec![Patch::new(
-                    "consider removing the trailing comma",
-                    range.clone(),
+                    message,
+                    range.end..range.end,
                     source,
-                    "",
+                    replacement,
                 )]
             }
-            ErrorKind::ExpectedKey(
-                TokenWithContext {
-                    token: Token::Comma,
-                    range,
-                },
-                TokenOption(None),
-            ) => {
+        }
+        ErrorKind::UnexpectedLeadingZero { extra, .. } => vec![Patch::new(
+            "remove the leading zeros",
+            extra.clone(),
+            source,
+            "",
+        )],
+        ErrorKind::ExpectedDigitAfterDot {
+            maybe_c: JsonCharOption(None),
+            number_range,
+            ..
+        } => vec![Patch::new(
+            "insert placeholder digit after the decimal point",
+            number_range.end..number_range.end,
+            source,
+            "0",
+        )],
+        ErrorKind::ExpectedPlusOrMinusOrDigitAfterE {
+            e_range,
+            maybe_c: JsonCharOption(None),
+            ..
+        } => vec![Patch::new(
+            "add placeholder exponent digits",
+            e_range.end..e_range.end,
+            source,
+            "+1",
+        )],
+        ErrorKind::ExpectedDigitAfterE {
+            maybe_c: JsonCharOption(None),
+            number_range,
+            ..
+        } => vec![Patch::new(
+            "add a digit after the exponent sign",
+            number_range.end..number_range.end,
+            source,
+            "0",
+        )],
+        ErrorKind::ExpectedQuote { string_range, .. } => vec![Patch::new(
+            "insert the missing closing quote",
+            string_range.end..string_range.end,
+            source,
+            "\"",
+        )],
+        ErrorKind::ExpectedEscape {
+            maybe_c,
+            slash_range,
+            ..
+        } => match maybe_c.0.as_ref() {
+            Some(c) if c.is_control() => {
                 vec![Patch::new(
-                    "consider replacing the trailing comma with a closed curly brace",
-                    range.clone(),
+                    "escape the control character",
+                    slash_range.start..error.range.end,
                     source,
-                    "}",
+                    c.escape(),
                 )]
             }
-            ErrorKind::ExpectedColon(ctx, found) => {
-                let (message, replacement) = match found.0.as_ref() {
-                    None => (
-                        "insert colon, placeholder value, and closing curly brace",
-                        r#": "garlic bread" }"#,
-                    ),
-                    Some(Token::Comma) | Some(Token::ClosedCurlyBrace) => {
-                        ("insert colon and placeholder value", r#": "🐟🛹""#)
-                    }
-                    _ => ("insert the missing colon", ": "),
-                };
-
+            _ => {
                 vec![Patch::new(
-                    message,
-                    ctx.range.end..ctx.range.end,
+                    "remove unnecessary escape slash",
+                    slash_range.clone(),
                     source,
-                    replacement,
+                    "",
                 )]
             }
-            ErrorKind::ExpectedEntryOrClosedDelimiter {
-                expected,
-                found: TokenOption(None),
-                ..
-            } => vec![Patch::new(
-                Cow::Owned(format!("insert the missing closed delimiter `{expected}`")),
-                error.range.end..error.range.end,
-                source,
-                expected.to_string(),
-            )],
-            ErrorKind::ExpectedCommaOrClosedCurlyBrace { range, found, .. } => {
-                match found.0.as_ref() {
-                    Some(Token::String(s)) => vec![Patch::new(
-                        Cow::Owned(format!("is {s:?} a key? consider adding a comma")),
-                        range.end..range.end,
-                        source,
-                        ",",
-                    )],
-                    None => vec![Patch::new(
-                        INSERT_MISSING_CLOSED_BRACE_HELP,
-                        range.end..range.end,
-                        source,
-                        "}",
-                    )],
-                    _ => Vec::new(),
+        },
+
+        ErrorKind::CommentNotAllowed => vec![Patch::new(
+            "consider removing the comment",
+            error.range.clone(),
+            source,
+            "",
+        )],
+        ErrorKind::UnterminatedBlockComment => vec![Patch::new(
+            "insert the missing `*/`",
+            error.source_text.len()..error.source_text.len(),
+            source,
+            "*/",
+        )],
+
+        ErrorKind::Json5Syntax {
+            syntax: Json5Syntax::Infinity | Json5Syntax::NaN,
+            ..
+        } => vec![Patch::new(
+            "consider replacing it with null",
+            error.range.clone(),
+            source,
+            "null",
+        )],
+        ErrorKind::Json5Syntax {
+            syntax,
+            replacement: Some(replacement),
+        } => {
+            let message = match syntax {
+                Json5Syntax::SingleQuotedString => "use double quotes",
+                Json5Syntax::UnquotedKey => "quote the key",
+                Json5Syntax::HexadecimalNumber => "use a decimal number",
+                Json5Syntax::LeadingDecimalPoint => "add a leading zero",
+                Json5Syntax::TrailingDecimalPoint => "remove the trailing decimal point",
+                Json5Syntax::PlusSign => "remove the plus sign",
+                Json5Syntax::Infinity | Json5Syntax::NaN => {
+                    unreachable!("json has no equivalent to replace with")
                 }
-            }
-            ErrorKind::ExpectedValue(ctx, tok_opt) => match (ctx, tok_opt.0.as_ref()) {
-                (
-                    Some(TokenWithContext {
-                        token: Token::Comma,
-                        range,
-                    }),
-                    Some(Token::ClosedSquareBracket),
-                ) => vec![Patch::new(
-                    "consider removing the trailing comma",
-                    range.clone(),
-                    source,
-                    "",
-                )],
-                (_, None) => vec![Patch::new(
-                    "insert a placeholder value",
-                    error.range.end..error.range.end,
-                    source,
-                    " \"rust is a must\"",
-                )],
-                (_, Some(Token::ClosedCurlyBrace)) => vec![Patch::new(
-                    "consider adding the missing open curly brace",
-                    error.range.end - 1..error.range.end,
-                    source,
-                    "{}",
-                )],
-                _ => Vec::new(),
-            },
-            ErrorKind::UnexpectedControlCharacterInString(escaped) => vec![Patch::new(
-                "replace the control character with its escaped form",
+            };
+            let mut patches = vec![Patch::new(
+                message,
                 error.range.clone(),
                 source,
-                escaped.to_string(),
-            )],
-            ErrorKind::TokenAfterEnd(token) => vec![Patch::new(
-                format!("consider removing the trailing content (starting with {token})"),
-                error.range.start..error.source_text.len(),
-                source,
-                "",
-            )],
-            ErrorKind::ExpectedDigitFollowingMinus(range, found) => {
-                let patch_info = match found.0 {
-                    None => ("insert placeholder digits after the minus sign", "194"),
-                    Some(JsonChar('.')) => (
-                        "did you mean to add a fraction? consider adding a 0 before the period",
-                        "0",
-                    ),
-                    _ => return vec![],
-                };
-                let (message, replacement) = patch_info;
-                {
-                    vec![Patch::new(
-                        message,
-                        range.end..range.end,
-                        source,
-                        replacement,
-                    )]
-                }
+                replacement.as_str(),
+            )];
+            if *syntax == Json5Syntax::TrailingDecimalPoint {
+                patches.push(Patch::new(
+                    "add a fraction digit",
+                    error.range.end..error.range.end,
+                    source,
+                    "0",
+                ));
             }
-            ErrorKind::UnexpectedLeadingZero { extra, .. } => vec![Patch::new(
-                "remove the leading zeros",
-                extra.clone(),
-                source,
-                "",
-            )],
-            ErrorKind::ExpectedDigitAfterDot {
-                maybe_c: JsonCharOption(None),
-                number_range,
-                ..
-            } => vec![Patch::new(
-                "insert placeholder digit after the decimal point",
-                number_range.end..number_range.end,
-                source,
-                "0",
-            )],
-            ErrorKind::ExpectedPlusOrMinusOrDigitAfterE {
-                e_range,
-                maybe_c: JsonCharOption(None),
-                ..
-            } => vec![Patch::new(
-                "add placeholder exponent digits",
-                e_range.end..e_range.end,
+            patches
+        }
+        ErrorKind::Json5Syntax {
+            replacement: None, ..
+        } => Vec::new(),
+        ErrorKind::JavaScriptSyntax {
+            syntax,
+            replacement: Some(replacement),
+        } => {
+            let message = match syntax {
+                JavaScriptSyntax::TemplateLiteral => "use double quotes",
+                JavaScriptSyntax::Undefined => "replace it with null",
+                JavaScriptSyntax::OctalNumber | JavaScriptSyntax::BinaryNumber => {
+                    "use a decimal number"
+                }
+            };
+            vec![Patch::new(
+                message,
+                error.range.clone(),
                 source,
-                "+1",
-            )],
-            ErrorKind::ExpectedDigitAfterE {
-                maybe_c: JsonCharOption(None),
-                number_range,
-                ..
-            } => vec![Patch::new(
-                "add a digit after the exponent sign",
-                number_range.end..number_range.end,
+                replacement.as_str(),
+            )]
+        }
+        ErrorKind::JavaScriptSyntax {
+            replacement: None, ..
+        } => Vec::new(),
+        ErrorKind::NonFiniteNumber(_) => vec![Patch::new(
+            "consider replacing it with null",
+            error.range.clone(),
+            source,
+            "null",
+        )],
+        ErrorKind::ExpectedRecordSeparator => Vec::new(),
+        ErrorKind::NumberOutOfRange | ErrorKind::NumberLosesPrecision { .. } => error
+            .source_text
+            .get(error.range.clone())
+            .map(|number| {
+                Patch::new(
+                    "quote the number to keep its exact value",
+                    error.range.clone(),
+                    source,
+                    format!("\"{number}\""),
+                )
+            })
+            .into_iter()
+            .collect(),
+        ErrorKind::DuplicateKey { .. } => Vec::new(),
+        ErrorKind::LoneHighSurrogate | ErrorKind::LoneLowSurrogate | ErrorKind::Noncharacter(_) => {
+            vec![Patch::new(
+                "replace it with the replacement character",
+                error.range.clone(),
                 source,
-                "0",
-            )],
-            ErrorKind::ExpectedQuote { string_range, .. } => vec![Patch::new(
-                "insert the missing closing quote",
-                string_range.end..string_range.end,
+                REPLACEMENT_CHARACTER_ESCAPE,
+            )]
+        }
+        ErrorKind::MismatchedSurrogates {
+            first,
+            first_high: true,
+        } => vec![Patch::new(
+            "replace the high surrogate with the replacement character",
+            first.clone(),
+            source,
+            REPLACEMENT_CHARACTER_ESCAPE,
+        )],
+        // the high surrogate already pairs with the escape after it, so
+        // swapping would leave the low surrogate on its own
+        ErrorKind::MismatchedSurrogates {
+            first,
+            first_high: false,
+        } if error
+            .source_text
+            .get(error.range.end..)
+            .is_some_and(starts_with_low_surrogate) =>
+        {
+            vec![Patch::new(
+                "replace the low surrogate with the replacement character",
+                first.clone(),
                 source,
-                "\"",
-            )],
-            ErrorKind::ExpectedEscape {
-                maybe_c,
-                slash_range,
-                ..
-            } => match maybe_c.0.as_ref() {
-                Some(c) if c.is_control() => {
-                    vec![Patch::new(
-                        "escape the control character",
-                        slash_range.start..error.range.end,
-                        source,
-                        c.escape(),
-                    )]
-                }
-                _ => {
-                    vec![Patch::new(
-                        "remove unnecessary escape slash",
-                        slash_range.clone(),
-                        source,
-                        "",
-                    )]
-                }
-            },
-
-            ErrorKind::ExpectedDigitAfterE { .. } => Vec::new(),
-            ErrorKind::ExpectedDigitAfterDot { .. } => Vec::new(),
-            ErrorKind::ExpectedPlusOrMinusOrDigitAfterE { .. } => Vec::new(),
-            ErrorKind::ExpectedEntryOrClosedDelimiter {
-                found: TokenOption(Some(_)),
-                ..
-            } => Vec::new(),
-            ErrorKind::UnexpectedCharacter(_) => Vec::new(),
-            ErrorKind::ExpectedHexDigit { .. } => Vec::new(),
-            // unreachable
-            ErrorKind::InvalidEncoding => Vec::new(),
-            ErrorKind::ExpectedOpenBrace { .. } => Vec::new(),
-            ErrorKind::ExpectedMinusOrDigit(_) => Vec::new(),
-            ErrorKind::ExpectedKey(_, _) => Vec::new(),
+                REPLACEMENT_CHARACTER_ESCAPE,
+            )]
         }
+        ErrorKind::MismatchedSurrogates {
+            first,
+            first_high: false,
+        } => error
+            .source_text
+            .get(first.clone())
+            .zip(error.source_text.get(error.range.clone()))
+            .map(|(low, high)| {
+                Patch::new(
+                    "swap the escapes so the high surrogate comes first",
+                    first.start..error.range.end,
+                    source,
+                    format!("{high}{low}"),
+                )
+            })
+            .into_iter()
+            .collect(),
+
+        ErrorKind::ExpectedDigitAfterE { .. } => Vec::new(),
+        ErrorKind::ExpectedDigitAfterDot { .. } => Vec::new(),
+        ErrorKind::ExpectedPlusOrMinusOrDigitAfterE { .. } => Vec::new(),
+        ErrorKind::UnexpectedCharacter(_) => Vec::new(),
+        ErrorKind::MisspelledKeyword { keyword, .. } => vec![Patch::new(
+            format!("replace it with `{keyword}`"),
+            error.range.clone(),
+            source,
+            *keyword,
+        )],
+        ErrorKind::ExpectedHexDigit { .. } => Vec::new(),
+        ErrorKind::ExpectedHexDigitInNumber { .. } => Vec::new(),
+        // unreachable
+        ErrorKind::InvalidEncoding => Vec::new(),
+        ErrorKind::ReadFailed(_) => Vec::new(),
+        ErrorKind::WriteFailed(_) => Vec::new(),
+        ErrorKind::ExpectedOpenBrace { .. } => Vec::new(),
+        ErrorKind::ExpectedMinusOrDigit(_) => Vec::new(),
+        ErrorKind::ExpectedKey(_, _) => Vec::new(),
     }
 }
 
@@ -398,17 +721,51 @@ impl<'a> From<&'a Error<'a>> for Vec<Context<'a>> {
             } => vec![
                 Context::new("opening quote found here", quote_range.clone(), source),
                 Context::new(
-                    "\\u escape started here",
+                    format!(
+                        "{} escape started here",
+                        &error.source_text[slash_range.start..u_range.end]
+                    ),
                     slash_range.start..u_range.end,
                     source,
                 ),
             ],
+            ErrorKind::MismatchedSurrogates { first, first_high } => vec![Context::new(
+                if *first_high {
+                    "high surrogate found here"
+                } else {
+                    "low surrogate found here"
+                },
+                first.clone(),
+                source,
+            )],
+            ErrorKind::DuplicateKey { first, .. } => {
+                vec![Context::new("first used here", first.clone(), source)]
+            }
+            ErrorKind::ExpectedHexDigitInNumber { number_range, .. } => vec![Context::new(
+                "hexadecimal number found here",
+                number_range.clone(),
+                source,
+            )],
             ErrorKind::ExpectedValue(None, _) => Vec::new(),
             ErrorKind::UnexpectedCharacter(_) => Vec::new(),
+            ErrorKind::MisspelledKeyword { .. } => Vec::new(),
             ErrorKind::UnexpectedControlCharacterInString(_) => Vec::new(),
+            ErrorKind::LoneHighSurrogate => Vec::new(),
+            ErrorKind::LoneLowSurrogate => Vec::new(),
+            ErrorKind::NumberOutOfRange => Vec::new(),
+            ErrorKind::NumberLosesPrecision { .. } => Vec::new(),
+            ErrorKind::Noncharacter(_) => Vec::new(),
             ErrorKind::TokenAfterEnd(_) => Vec::new(),
+            ErrorKind::CommentNotAllowed => Vec::new(),
+            ErrorKind::UnterminatedBlockComment => Vec::new(),
+            ErrorKind::Json5Syntax { .. } => Vec::new(),
+            ErrorKind::NonFiniteNumber(_) => Vec::new(),
+            ErrorKind::JavaScriptSyntax { .. } => Vec::new(),
+            ErrorKind::ExpectedRecordSeparator => Vec::new(),
             // unreachable
             ErrorKind::InvalidEncoding => Vec::new(),
+            ErrorKind::ReadFailed(_) => Vec::new(),
+            ErrorKind::WriteFailed(_) => Vec::new(),
             ErrorKind::ExpectedMinusOrDigit(_) => Vec::new(),
             ErrorKind::ExpectedOpenBrace { context: None, .. } => Vec::new(),
         }
@@ -417,22 +774,37 @@ impl<'a> From<&'a Error<'a>> for Vec<Context<'a>> {
 
 impl<'a> From<&'a Error<'a>> for Diagnostic<'a> {
     fn from(error: &'a Error<'a>) -> Self {
+        // errors found while reading input a piece at a time can refer to
+        // text that was already discarded
+        let in_source = |span: &Range<usize>| span.end <= error.source_text.len();
+        let mut context: Vec<Context> = error.into();
+        context.retain(|ctx| in_source(&ctx.span));
+        let mut patches: Vec<Patch> = error.into();
+        patches.retain(|patch| in_source(&patch.span));
         Diagnostic {
+            severity: Severity::Error,
+            code: Some(error.kind.code().to_string()),
             message: error.kind.to_string(),
             range: Some(error.range.clone()),
-            context: error.into(),
-            patches: error.into(),
+            context,
+            patches,
             source: error_source(error),
+            line_start: error.start.line,
+            column_start: error.start.column,
         }
     }
 }
 
 pub fn invalid_encoding<'a>(source: Source<'a>) -> Diagnostic<'a> {
     Diagnostic {
+        severity: Severity::Error,
+        code: Some(ErrorKind::InvalidEncoding.code().to_string()),
         message: ErrorKind::InvalidEncoding.to_string(),
         source,
         range: None,
         patches: vec![],
         context: vec![],
+        line_start: 1,
+        column_start: 1,
     }
 }
@@ -0,0 +1,387 @@
+//! pulling a value apart one event at a time, for callers that want to stop
+//! early or interleave parsing with other work
+//!
+//! ```
+//! # use jjpwrgem_parse::events::{Event, Scalar, events};
+//! let json = r#"{"name": "jjp", "tags": ["json", "cli"]}"#;
+//! let name = events(json)
+//!     .map(|event| event.unwrap().event)
+//!     .skip_while(|event| *event != Event::Key("name".into()))
+//!     .nth(1);
+//! assert_eq!(name, Some(Event::Scalar(Scalar::String("jjp".into()))));
+//! ```
+
+use crate::{
+    ErrorKind, Result,
+    tokens::{Dialect, Token, TokenStream, TokenWithContext, unescape},
+    traverse::{ArrayState, ObjectState, Step, Tokens},
+};
+use core::ops::Range;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, hash_map::Entry},
+};
+
+/// what happens when an object uses a key more than once. Keys are compared
+/// decoded, so `"\u0061"` is a duplicate of `"a"`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// fail with [`ErrorKind::DuplicateKey`]
+    Error,
+    /// keep every entry, reporting each duplicate as a warning on the
+    /// [`TokenStream`]
+    Warn,
+    /// keep the first entry for each key
+    KeepFirst,
+    /// keep the value of the last entry for each key, where the first entry
+    /// was. This is what most json implementations do
+    KeepLast,
+    /// keep every entry without looking for duplicates
+    #[default]
+    KeepAll,
+}
+
+impl DuplicateKeys {
+    /// whether duplicates are reported as errors or warnings while traversing
+    pub fn is_reported(self) -> bool {
+        matches!(self, DuplicateKeys::Error | DuplicateKeys::Warn)
+    }
+}
+
+/// something found while traversing a value
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Event<'a> {
+    StartObject,
+    /// the contents of an object key, still escaped. Decode them with
+    /// [`unescape`](crate::tokens::unescape)
+    Key(Cow<'a, str>),
+    EndObject,
+    StartArray,
+    EndArray,
+    Scalar(Scalar<'a>),
+}
+
+/// a value that isn't a container
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Scalar<'a> {
+    Null,
+    Boolean(bool),
+    Number(Cow<'a, str>),
+    /// the contents of a string, still escaped. Decode them with
+    /// [`unescape`](crate::tokens::unescape)
+    String(Cow<'a, str>),
+}
+
+/// an event and where it was found in the input
+///
+/// Starts cover the opening delimiter, ends cover the whole container, and
+/// keys and scalars cover their token
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EventWithRange<'a> {
+    pub event: Event<'a>,
+    pub range: Range<usize>,
+}
+
+#[derive(Debug)]
+enum Frame<'a> {
+    Array(ArrayState<'a>),
+    Object(ObjectState<'a>),
+}
+
+/// the events of a single value, followed by an error if the input is invalid.
+/// Nothing is read past the first error
+#[derive(Debug)]
+pub struct Events<'a, T = TokenStream<'a>> {
+    tokens: T,
+    /// containers being traversed, innermost last
+    stack: Vec<Frame<'a>>,
+    /// whether anything after the value is an error
+    fail_on_multiple_value: bool,
+    started: bool,
+    finished: bool,
+    /// the range of the value once it is done
+    range: Option<Range<usize>>,
+    /// the decoded keys of each object being traversed and where they were
+    /// first used, only kept when duplicates are reported
+    keys: Vec<HashMap<String, Range<usize>>>,
+}
+
+/// the events of `json`, which should hold exactly one value
+pub fn events(json: &str) -> Events<'_> {
+    events_with_dialect(json, Dialect::Json)
+}
+
+pub fn events_with_dialect(json: &str, dialect: Dialect) -> Events<'_> {
+    Events::new(TokenStream::with_dialect(json, dialect), true)
+}
+
+impl<'a, T: Tokens<'a>> Events<'a, T> {
+    pub(crate) fn new(tokens: T, fail_on_multiple_value: bool) -> Self {
+        Self {
+            tokens,
+            stack: Vec::new(),
+            fail_on_multiple_value,
+            started: false,
+            finished: false,
+            range: None,
+            keys: Vec::new(),
+        }
+    }
+
+    /// the range of the whole value, once its last event was returned
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.range.clone()
+    }
+
+    fn step(&mut self) -> Result<'a, Option<EventWithRange<'a>>> {
+        let event = self.next_event()?;
+        if let Some(event) = &event
+            && self.tokens.duplicate_keys().is_reported()
+        {
+            self.track_keys(event)?;
+        }
+        Ok(event)
+    }
+
+    /// reports keys already used in the same object
+    fn track_keys(
+        &mut self,
+        EventWithRange { event, range }: &EventWithRange<'a>,
+    ) -> Result<'a, ()> {
+        match event {
+            Event::StartObject => self.keys.push(HashMap::new()),
+            Event::EndObject => {
+                self.keys.pop();
+            }
+            Event::Key(key) => {
+                let keys = self
+                    .keys
+                    .last_mut()
+                    .expect("keys are only found in objects");
+                match keys.entry(unescape(key).into_owned()) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(range.clone());
+                    }
+                    Entry::Occupied(entry) => {
+                        let kind = ErrorKind::DuplicateKey {
+                            key: key.clone(),
+                            first: entry.get().clone(),
+                        };
+                        let error = self.tokens.error(kind, range.clone());
+                        if self.tokens.duplicate_keys() == DuplicateKeys::Error {
+                            return Err(error);
+                        }
+                        self.tokens.warn(error);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn next_event(&mut self) -> Result<'a, Option<EventWithRange<'a>>> {
+        loop {
+            let Some(frame) = self.stack.pop() else {
+                if self.started {
+                    return self.finish().map(|()| None);
+                }
+                self.started = true;
+                match self.start_value()? {
+                    Some(event) => return Ok(Some(event)),
+                    None => continue,
+                }
+            };
+
+            let step = match frame {
+                Frame::Array(state) => state.process(&mut self.tokens)?.map(Frame::Array),
+                Frame::Object(state) => state.process(&mut self.tokens)?.map(Frame::Object),
+            };
+            match step {
+                Step::Next(frame, event) => {
+                    self.stack.push(frame);
+                    if event.is_some() {
+                        return Ok(event);
+                    }
+                }
+                Step::Value(frame) => {
+                    self.stack.push(frame);
+                    if let Some(event) = self.start_value()? {
+                        return Ok(Some(event));
+                    }
+                }
+                Step::End(event) => {
+                    self.value_end(event.range.clone());
+                    return Ok(Some(event));
+                }
+            }
+        }
+    }
+
+    /// starts traversing the next value. Containers are left for their state
+    /// machines, scalars are done right away
+    fn start_value(&mut self) -> Result<'a, Option<EventWithRange<'a>>> {
+        let Some(peeked) = self.tokens.peek_token()?.cloned() else {
+            return Err(self
+                .tokens
+                .unterminated(ErrorKind::ExpectedValue(None, None.into())));
+        };
+        let scalar = match peeked.token {
+            Token::OpenCurlyBrace => {
+                self.stack.push(Frame::Object(ObjectState::Open));
+                return Ok(None);
+            }
+            Token::OpenSquareBracket => {
+                self.stack.push(Frame::Array(ArrayState::Open));
+                return Ok(None);
+            }
+            Token::Null => Scalar::Null,
+            Token::Boolean(b) => Scalar::Boolean(b),
+            Token::Number(n) => Scalar::Number(n),
+            Token::String(s) => Scalar::String(s),
+            invalid => {
+                return Err(self.tokens.error(
+                    ErrorKind::ExpectedValue(None, Some(invalid).into()),
+                    peeked.range,
+                ));
+            }
+        };
+
+        self.tokens.next_token()?;
+        self.value_end(peeked.range.clone());
+        Ok(Some(EventWithRange {
+            event: Event::Scalar(scalar),
+            range: peeked.range,
+        }))
+    }
+
+    /// moves the container the value was in past it
+    fn value_end(&mut self, range: Range<usize>) {
+        match self.stack.pop() {
+            Some(Frame::Array(state)) => self.stack.push(Frame::Array(state.value_end(range))),
+            Some(Frame::Object(state)) => self.stack.push(Frame::Object(state.value_end(range))),
+            None => self.range = Some(range),
+        }
+    }
+
+    fn finish(&mut self) -> Result<'a, ()> {
+        if self.fail_on_multiple_value
+            && let Some(TokenWithContext { token, range }) = self.tokens.peek_token()?.cloned()
+        {
+            return Err(self.tokens.error(ErrorKind::TokenAfterEnd(token), range));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: Tokens<'a>> Iterator for Events<'a, T> {
+    type Item = Result<'a, EventWithRange<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.step();
+        self.finished = !matches!(result, Ok(Some(_)));
+        result.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(json: &str) -> Vec<(Event<'_>, &str)> {
+        events(json)
+            .map(|event| {
+                let EventWithRange { event, range } = event.unwrap();
+                (event, &json[range])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scalar() {
+        assert_eq!(
+            collect(" 12.5 "),
+            [(Event::Scalar(Scalar::Number("12.5".into())), "12.5")]
+        );
+    }
+
+    #[test]
+    fn nested_containers() {
+        let json = r#"{"a": [1, {}], "b": null}"#;
+        assert_eq!(
+            collect(json),
+            [
+                (Event::StartObject, "{"),
+                (Event::Key("a".into()), r#""a""#),
+                (Event::StartArray, "["),
+                (Event::Scalar(Scalar::Number("1".into())), "1"),
+                (Event::StartObject, "{"),
+                (Event::EndObject, "{}"),
+                (Event::EndArray, "[1, {}]"),
+                (Event::Key("b".into()), r#""b""#),
+                (Event::Scalar(Scalar::Null), "null"),
+                (Event::EndObject, json),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut events = events("[true, false,]");
+        assert!(events.by_ref().take(3).all(|event| event.is_ok()));
+        assert!(events.next().unwrap().is_err());
+        assert_eq!(events.next(), None);
+        assert_eq!(events.range(), None);
+    }
+
+    #[test]
+    fn trailing_value_is_an_error() {
+        let results: Vec<_> = events("1 2").collect();
+        assert!(results[0].is_ok());
+        assert_eq!(
+            results[1].as_ref().unwrap_err().to_string(),
+            "unexpected token `2` after json finished at line 1 column 3"
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_are_errors() {
+        let json = r#"{"a": {"a": 1}, "b": 2, "\u0061": 3}"#;
+        let tokens = TokenStream::new(json).with_duplicate_keys(DuplicateKeys::Error);
+        let error = Events::new(tokens, true)
+            .find_map(|event| event.err())
+            .unwrap();
+        assert_eq!(&json[error.range()], r#""\u0061""#);
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::DuplicateKey {
+                key: r"\u0061".into(),
+                first: 1..4,
+            }
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_can_be_warnings() {
+        let json = r#"[{"a": 1, "a": 2}, {"a": 3}]"#;
+        let mut tokens = TokenStream::new(json).with_duplicate_keys(DuplicateKeys::Warn);
+        assert_eq!(Events::new(&mut tokens, true).count(), 12);
+        let warnings: Vec<_> = tokens.warnings().iter().map(|w| &json[w.range()]).collect();
+        assert_eq!(warnings, [r#""a""#]);
+        assert_eq!(tokens.warnings()[0].range(), 10..13);
+    }
+
+    #[test]
+    fn stopping_early_reads_no_further() {
+        let mut events = events_with_dialect("[1, 2, // comment\n 3,]", Dialect::Jsonc);
+        assert_eq!(
+            events.nth(1).unwrap().unwrap().event,
+            Event::Scalar(Scalar::Number("1".into()))
+        );
+        assert_eq!(events.range(), None);
+        assert_eq!(events.count(), 3);
+    }
+}
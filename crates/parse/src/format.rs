@@ -1,11 +1,21 @@
+mod convert;
 mod prettify;
 #[cfg(feature = "serde")]
 pub mod serde;
 mod uglify;
 
 use crate::tokens::{FALSE, NULL, TRUE};
-pub use prettify::{FormatOptions, format_str, format_value, prettify_str, prettify_value};
-pub use uglify::{uglify_str, uglify_value};
+pub use convert::{Change, ChangeKind, Conversion, convert_str};
+pub use prettify::{
+    FormatOptions, format_str, format_str_with_comments, format_str_with_dialect, format_to_writer,
+    format_value, prettify_str, prettify_to_writer, prettify_value,
+};
+use std::{fmt, io};
+pub use uglify::{
+    uglify_read_tokens, uglify_reader, uglify_str, uglify_str_with_dialect,
+    uglify_str_with_duplicate_keys, uglify_to_writer, uglify_to_writer_with_dialect,
+    uglify_token_stream, uglify_value,
+};
 
 /// writes formatted delimiters between formatted items
 ///
@@ -97,3 +107,78 @@ pub trait Emitter {
         self.push(':');
     }
 }
+
+/// write errors are left to the writer to keep, as [`IoWriter`] does
+impl<W: fmt::Write> Emitter for W {
+    fn push(&mut self, c: char) {
+        let _ = self.write_char(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        let _ = self.write_str(s);
+    }
+}
+
+/// formats into an [`io::Write`]. Writing stops at the first failure, which is
+/// kept to report once formatting is done
+pub(crate) struct IoWriter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// flushes the writer, returning the first failure
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        match self.error {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.writer.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    /// a writer that has run out of space
+    struct Full;
+
+    impl io::Write for Full {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::StorageFull.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writers_report_write_failures() {
+        let pretty = prettify_to_writer("[1, 2]", 80, LineEnding::Lf, Full).unwrap_err();
+        assert!(matches!(pretty.kind(), ErrorKind::WriteFailed(_)));
+
+        let ugly = uglify_to_writer("[1, 2]", Full).unwrap_err();
+        assert!(matches!(ugly.kind(), ErrorKind::WriteFailed(_)));
+    }
+}
@@ -0,0 +1,262 @@
+//! converting between dialects while recording what had to change
+
+use crate::{
+    Result,
+    check::{validate_finite_numbers, validate_token_stream},
+    format::{FormatOptions, format_str_with_comments, format_str_with_dialect},
+    tokens::{Dialect, Token, TokenWithContext, json5::NAN},
+};
+use core::ops::Range;
+use displaydoc::Display;
+use std::borrow::Cow;
+
+/// a construct that was rewritten during a conversion
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display)]
+pub enum ChangeKind {
+    /// stripped comment
+    Comment,
+    /// dropped trailing comma
+    TrailingComma,
+    /// quoted key
+    UnquotedKey,
+    /// converted single quoted string
+    SingleQuotedString,
+    /// converted string escapes
+    StringEscapes,
+    /// converted hex number
+    HexadecimalNumber,
+    /// removed plus sign
+    PlusSign,
+    /// added leading zero
+    LeadingDecimalPoint,
+    /// removed trailing decimal point
+    TrailingDecimalPoint,
+    /// removed sign from NaN
+    SignedNaN,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// where the construct was in the input
+    pub range: Range<usize>,
+    /// what it was written as, empty when removed
+    pub replacement: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Conversion {
+    /// the formatted output
+    pub json: String,
+    /// the changes in order of where they appear in the input
+    pub changes: Vec<Change>,
+}
+
+/// formats `json` written in `from` as `to`, listing every construct that
+/// had to change on the way
+///
+/// Converting to json also removes comments. `Infinity` and `NaN` are errors
+/// unless converting to json5, since nothing else can represent them
+pub fn convert_str<'a>(
+    json: &'a str,
+    from: Dialect,
+    to: Dialect,
+    options: FormatOptions,
+    preferred_width: usize,
+) -> Result<'a, Conversion> {
+    validate_token_stream(&mut options.tokens(json, from))?;
+    if from.is_json5() && !to.is_json5() {
+        validate_finite_numbers(json, from)?;
+    }
+
+    let changes = changes(json, from, to, options)?;
+    let json = if to.allows_comments() {
+        format_str_with_comments(json, from, options, preferred_width)?
+    } else {
+        format_str_with_dialect(json, from, options, preferred_width)?
+    };
+    Ok(Conversion { json, changes })
+}
+
+fn changes<'a>(
+    json: &'a str,
+    from: Dialect,
+    to: Dialect,
+    options: FormatOptions,
+) -> Result<'a, Vec<Change>> {
+    let mut stream = options.tokens(json, from);
+    let tokens = stream.by_ref().collect::<Result<Vec<_>>>()?;
+
+    let mut changes = Vec::new();
+    for (i, TokenWithContext { token, range }) in tokens.iter().enumerate() {
+        let source = &json[range.clone()];
+        let change = match token {
+            Token::Identifier(key) => Some((ChangeKind::UnquotedKey, format!("\"{key}\""))),
+            Token::String(s) if source.starts_with('\'') => {
+                Some((ChangeKind::SingleQuotedString, format!("\"{s}\"")))
+            }
+            Token::String(Cow::Owned(s)) => Some((ChangeKind::StringEscapes, format!("\"{s}\""))),
+            Token::Number(n) if n != source => {
+                changes.extend(number_changes(source, n, range.start));
+                None
+            }
+            Token::Comma
+                if tokens.get(i + 1).is_some_and(|next| {
+                    matches!(
+                        next.token,
+                        Token::ClosedCurlyBrace | Token::ClosedSquareBracket
+                    )
+                }) =>
+            {
+                Some((ChangeKind::TrailingComma, String::new()))
+            }
+            _ => None,
+        };
+        if let Some((kind, replacement)) = change {
+            changes.push(Change {
+                kind,
+                range: range.clone(),
+                replacement,
+            });
+        }
+    }
+
+    if !to.allows_comments() {
+        changes.extend(stream.comments().map(|range| Change {
+            kind: ChangeKind::Comment,
+            range,
+            replacement: String::new(),
+        }));
+    }
+    changes.sort_by_key(|change| change.range.start);
+    Ok(changes)
+}
+
+/// each rewrite that turned the number written as `source` at `start` into
+/// `normalized`, since one number can need several like `+.5`
+fn number_changes(source: &str, normalized: &str, start: usize) -> Vec<Change> {
+    let change = |kind, range: Range<usize>, replacement: &str| Change {
+        kind,
+        range: start + range.start..start + range.end,
+        replacement: replacement.into(),
+    };
+    let mut changes = Vec::new();
+    let sign = usize::from(source.starts_with(['+', '-']));
+    let unsigned = &source[sign..];
+    if source.starts_with('+') {
+        changes.push(change(ChangeKind::PlusSign, 0..1, ""));
+    }
+
+    if unsigned == NAN {
+        if source.starts_with('-') {
+            changes.push(change(ChangeKind::SignedNaN, 0..1, ""));
+        }
+    } else if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        // a minus sign is kept in the decimal number
+        let from = usize::from(source.starts_with('+'));
+        changes.push(change(
+            ChangeKind::HexadecimalNumber,
+            from..source.len(),
+            normalized,
+        ));
+    } else {
+        let mantissa = unsigned
+            .find(['e', 'E'])
+            .map_or(unsigned, |i| &unsigned[..i]);
+        if mantissa.starts_with('.') {
+            changes.push(change(
+                ChangeKind::LeadingDecimalPoint,
+                sign..sign + 1,
+                "0.",
+            ));
+        }
+        if mantissa.ends_with('.') {
+            let dot = sign + mantissa.len() - 1;
+            changes.push(change(ChangeKind::TrailingDecimalPoint, dot..dot + 1, ""));
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::LineEnding;
+
+    fn convert(json: &str, from: Dialect, to: Dialect) -> Conversion {
+        convert_str(json, from, to, FormatOptions::prettify(LineEnding::Lf), 80).unwrap()
+    }
+
+    #[test]
+    fn lists_json5_changes_in_order() {
+        let json5 = "{ // settings\n  a: 'x', \"b\": [0x10, +1, .5, 5., \"\\x41\",], }";
+        let conversion = convert(json5, Dialect::Json5, Dialect::Json);
+
+        assert_eq!(
+            conversion.json,
+            "{\n  \"a\": \"x\",\n  \"b\": [16, 1, 0.5, 5, \"A\"]\n}"
+        );
+        let kinds: Vec<_> = conversion
+            .changes
+            .iter()
+            .map(|c| (c.kind, &json5[c.range.clone()], c.replacement.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (ChangeKind::Comment, "// settings", ""),
+                (ChangeKind::UnquotedKey, "a", "\"a\""),
+                (ChangeKind::SingleQuotedString, "'x'", "\"x\""),
+                (ChangeKind::HexadecimalNumber, "0x10", "16"),
+                (ChangeKind::PlusSign, "+", ""),
+                (ChangeKind::LeadingDecimalPoint, ".", "0."),
+                (ChangeKind::TrailingDecimalPoint, ".", ""),
+                (ChangeKind::StringEscapes, "\"\\x41\"", "\"A\""),
+                (ChangeKind::TrailingComma, ",", ""),
+                (ChangeKind::TrailingComma, ",", ""),
+            ]
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("+.5", &[(ChangeKind::PlusSign, "+", ""), (ChangeKind::LeadingDecimalPoint, ".", "0.")])]
+    #[case("+5.e3", &[(ChangeKind::PlusSign, "+", ""), (ChangeKind::TrailingDecimalPoint, ".", "")])]
+    #[case("+0x10", &[(ChangeKind::PlusSign, "+", ""), (ChangeKind::HexadecimalNumber, "0x10", "16")])]
+    #[case("-0x10", &[(ChangeKind::HexadecimalNumber, "-0x10", "-16")])]
+    #[case("-NaN", &[(ChangeKind::SignedNaN, "-", "")])]
+    #[case("+NaN", &[(ChangeKind::PlusSign, "+", "")])]
+    #[case("+Infinity", &[(ChangeKind::PlusSign, "+", "")])]
+    #[case("-Infinity", &[])]
+    fn reports_each_number_rewrite(
+        #[case] json5: &str,
+        #[case] expected: &[(ChangeKind, &str, &str)],
+    ) {
+        let conversion = convert(json5, Dialect::Json5, Dialect::Json5);
+        let changes: Vec<_> = conversion
+            .changes
+            .iter()
+            .map(|c| (c.kind, &json5[c.range.clone()], c.replacement.as_str()))
+            .collect();
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn keeps_comments_for_jsonc() {
+        let conversion = convert("[1, /* two */ 2,]", Dialect::Jsonc, Dialect::Jsonc);
+        assert_eq!(conversion.json, "[\n  1, /* two */\n  2\n]");
+        assert_eq!(
+            conversion.changes,
+            [Change {
+                kind: ChangeKind::TrailingComma,
+                range: 15..16,
+                replacement: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn strict_json_has_no_changes() {
+        let conversion = convert(r#"{"a": [1, "b"]}"#, Dialect::Json, Dialect::Json);
+        assert_eq!(conversion.changes, []);
+    }
+}
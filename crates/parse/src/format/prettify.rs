@@ -1,17 +1,23 @@
-use core::iter;
+mod jsonc;
+
+use core::{fmt, iter};
+use std::io;
 
 use crate::{
-    Result,
-    ast::{Value, parse_str},
-    format::LineEnding,
-    tokens::{FALSE, NULL, TRUE},
+    DuplicateKeys, Error, ErrorKind, Result,
+    ast::{Value, parse_token_stream},
+    format::{IoWriter, LineEnding},
+    tokens::{Dialect, FALSE, NULL, TRUE, TokenStream},
 };
+pub use jsonc::format_str_with_comments;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct FormatOptions {
     key_val_delimiter: Option<(char, usize)>,
     indent: Option<(char, usize)>,
     line_ending: LineEnding,
+    duplicate_keys: DuplicateKeys,
+    allow_lone_surrogates: bool,
 }
 
 impl FormatOptions {
@@ -24,6 +30,8 @@ impl FormatOptions {
             key_val_delimiter,
             indent,
             line_ending,
+            duplicate_keys: DuplicateKeys::default(),
+            allow_lone_surrogates: false,
         }
     }
 
@@ -32,32 +40,70 @@ impl FormatOptions {
             key_val_delimiter: Some((' ', 1)),
             indent: Some((' ', 2)),
             line_ending,
+            duplicate_keys: DuplicateKeys::default(),
+            allow_lone_surrogates: false,
         }
     }
+
+    /// how objects that use a key more than once are formatted, keeping
+    /// every entry by default. [`DuplicateKeys::Warn`] keeps every entry and
+    /// drops the warnings, so check for them separately
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub fn duplicate_keys(&self) -> DuplicateKeys {
+        self.duplicate_keys
+    }
+
+    /// whether the input may hold `\u` escapes of unpaired surrogates, see
+    /// [`TokenStream::allow_lone_surrogates`]
+    pub fn allow_lone_surrogates(mut self, allow: bool) -> Self {
+        self.allow_lone_surrogates = allow;
+        self
+    }
+
+    /// lexes `json` following these options
+    pub fn tokens<'a>(&self, json: &'a str, dialect: Dialect) -> TokenStream<'a> {
+        TokenStream::with_dialect(json, dialect)
+            .with_duplicate_keys(self.duplicate_keys)
+            .allow_lone_surrogates(self.allow_lone_surrogates)
+    }
+
+    /// parses `json` following these options
+    fn parse<'a>(&self, json: &'a str, dialect: Dialect) -> Result<'a, Value<'a>> {
+        parse_token_stream(&mut self.tokens(json, dialect))
+    }
 }
 
-struct FormatBuf {
+/// write errors are ignored here, since only [`IoWriter`] can fail and it
+/// keeps the first one
+struct FormatBuf<W = String> {
     opts: FormatOptions,
-    buf: String,
-    line_start: usize,
+    buf: W,
+    /// bytes written since the last line ending
+    column: usize,
     preferred_width: usize,
 }
 
-impl FormatBuf {
-    fn new(buf: String, opts: FormatOptions, preferred_width: usize) -> Self {
+impl<W: fmt::Write> FormatBuf<W> {
+    fn new(buf: W, opts: FormatOptions, preferred_width: usize) -> Self {
         Self {
             opts,
             buf,
-            line_start: 0,
+            column: 0,
             preferred_width,
         }
     }
 
     fn push(&mut self, value: char) {
-        self.buf.push(value);
+        let _ = self.buf.write_char(value);
+        self.column += value.len_utf8();
     }
     fn push_str(&mut self, value: &str) {
-        self.buf.push_str(value);
+        let _ = self.buf.write_str(value);
+        self.column += value.len();
     }
 
     #[inline]
@@ -69,7 +115,9 @@ impl FormatBuf {
 
     #[inline]
     fn push_repeat(&mut self, c: char, count: usize) {
-        self.buf.extend(iter::repeat_n(c, count));
+        for c in iter::repeat_n(c, count) {
+            self.push(c);
+        }
     }
 
     #[inline]
@@ -85,19 +133,19 @@ impl FormatBuf {
 
     pub fn write_eol(&mut self) {
         self.push_str(self.opts.line_ending.as_str());
-        self.line_start = self.buf.len();
+        self.column = 0;
     }
 
     pub fn write_indent(&mut self, level: usize) {
         self.write_spec(self.opts.indent.map(|(c, size)| (c, size * level)));
     }
 
-    fn into_inner(self) -> String {
+    fn into_inner(self) -> W {
         self.buf
     }
 
     pub fn column(&self) -> usize {
-        self.buf.len() - self.line_start
+        self.column
     }
 
     fn available_bytes(&self) -> usize {
@@ -109,12 +157,39 @@ pub fn format_str<'a>(
     json: &'a str,
     options: FormatOptions,
     preferred_width: usize,
+) -> Result<'a, String> {
+    format_str_with_dialect(json, Dialect::Json, options, preferred_width)
+}
+
+/// formats as strict json, so comments are removed
+pub fn format_str_with_dialect<'a>(
+    json: &'a str,
+    dialect: Dialect,
+    options: FormatOptions,
+    preferred_width: usize,
 ) -> Result<'a, String> {
     let mut buf = FormatBuf::new(String::with_capacity(json.len()), options, preferred_width);
-    format_value_into(&mut buf, &parse_str(json)?, 0);
+    format_value_into(&mut buf, &options.parse(json, dialect)?, 0);
     Ok(buf.into_inner())
 }
 
+/// like [`format_str_with_dialect`], but writes to `writer` instead of
+/// building a `String`. Nothing is written unless the input is valid
+pub fn format_to_writer<'a>(
+    json: &'a str,
+    dialect: Dialect,
+    options: FormatOptions,
+    preferred_width: usize,
+    writer: impl io::Write,
+) -> Result<'a, ()> {
+    let value = options.parse(json, dialect)?;
+    let mut buf = FormatBuf::new(IoWriter::new(writer), options, preferred_width);
+    format_value_into(&mut buf, &value, 0);
+    buf.into_inner()
+        .finish()
+        .map_err(|error| Error::from_unterminated(ErrorKind::WriteFailed(error.to_string()), json))
+}
+
 /// writes formatted delimiters between formatted items
 ///
 /// avoids allocating intermediate `String`s declaratively
@@ -146,7 +221,7 @@ pub fn join_into<T, B>(
     }
 }
 
-fn format_value_into(buf: &mut FormatBuf, val: &Value, depth: usize) {
+fn format_value_into<W: fmt::Write>(buf: &mut FormatBuf<W>, val: &Value, depth: usize) {
     match val {
         Value::Null => buf.push_str(NULL),
         Value::String(s) => buf.push_quoted(s),
@@ -186,7 +261,7 @@ fn format_value_into(buf: &mut FormatBuf, val: &Value, depth: usize) {
     }
 }
 
-fn expanded_format_arr_into(buf: &mut FormatBuf, items: &[Value], depth: usize) {
+fn expanded_format_arr_into<W: fmt::Write>(buf: &mut FormatBuf<W>, items: &[Value], depth: usize) {
     buf.push('[');
     buf.write_eol();
     join_into(
@@ -206,7 +281,7 @@ fn expanded_format_arr_into(buf: &mut FormatBuf, items: &[Value], depth: usize)
     buf.push(']');
 }
 
-fn compact_format_arr_into(buf: &mut FormatBuf, items: &[Value], depth: usize) {
+fn compact_format_arr_into<W: fmt::Write>(buf: &mut FormatBuf<W>, items: &[Value], depth: usize) {
     buf.push('[');
     join_into(
         buf,
@@ -234,6 +309,21 @@ pub fn prettify_str(
     format_str(json, FormatOptions::prettify(line_ending), preferred_width)
 }
 
+pub fn prettify_to_writer(
+    json: &str,
+    preferred_width: usize,
+    line_ending: LineEnding,
+    writer: impl io::Write,
+) -> Result<'_, ()> {
+    format_to_writer(
+        json,
+        Dialect::Json,
+        FormatOptions::prettify(line_ending),
+        preferred_width,
+        writer,
+    )
+}
+
 pub fn prettify_value(val: &Value, preferred_width: usize, line_ending: LineEnding) -> String {
     format_value(val, &FormatOptions::prettify(line_ending), preferred_width)
 }
@@ -0,0 +1,497 @@
+//! formatting for json with comments
+//!
+//! [`Value`] has nowhere to store comments, so comments are attached to the
+//! object entries and array items around them. Containers without comments
+//! fall back to [`Value`] so they are laid out exactly like strict json
+
+use super::{FormatBuf, FormatOptions, format_value_into};
+use crate::{
+    DuplicateKeys, Result,
+    ast::{
+        ObjectEntries, Value,
+        spanned::{self, NodeValue},
+    },
+    tokens::{Dialect, unescape},
+};
+use core::{iter::Peekable, ops::Range};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, hash_map::Entry},
+    vec,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Comment<'a> {
+    text: &'a str,
+    /// whether the comment starts on a new line rather than following code
+    own_line: bool,
+}
+
+impl Comment<'_> {
+    fn is_line(&self) -> bool {
+        self.text.starts_with("//")
+    }
+}
+
+/// an object entry or array item with the comments attached to it
+#[derive(Debug)]
+struct Member<'a> {
+    /// comments on the lines before the member
+    leading: Vec<Comment<'a>>,
+    /// `None` for array items
+    key: Option<Cow<'a, str>>,
+    /// block comments between the key and value
+    inner: Vec<Comment<'a>>,
+    value: Node<'a>,
+    /// comments on the same line after the member
+    trailing: Vec<Comment<'a>>,
+}
+
+impl Member<'_> {
+    fn has_comments(&self) -> bool {
+        !(self.leading.is_empty() && self.inner.is_empty() && self.trailing.is_empty())
+    }
+}
+
+#[derive(Debug)]
+enum Node<'a> {
+    /// a value without any comments inside
+    Plain(Value<'a>),
+    Container {
+        open: char,
+        close: char,
+        members: Vec<Member<'a>>,
+        /// comments after the last member
+        dangling: Vec<Comment<'a>>,
+    },
+}
+
+/// builds [`Node`]s from a [`spanned::Node`] and the comments around it
+struct Parser<'a> {
+    text: &'a str,
+    comments: Peekable<vec::IntoIter<Range<usize>>>,
+    /// end of the last value or comment consumed
+    last_end: usize,
+    duplicate_keys: DuplicateKeys,
+}
+
+/// an entry's key and where it is, `None` for array items
+type Key<'a> = Option<(Cow<'a, str>, Range<usize>)>;
+
+impl<'a> Parser<'a> {
+    /// comments starting before `end`
+    fn comments_before(&mut self, end: usize) -> Vec<Comment<'a>> {
+        let mut comments = Vec::new();
+        while let Some(range) = self.comments.next_if(|r| r.start < end) {
+            let own_line = self.text[self.last_end..range.start].contains(['\n', '\r']);
+            self.last_end = range.end;
+            comments.push(Comment {
+                text: &self.text[range],
+                own_line,
+            });
+        }
+        comments
+    }
+
+    /// splits comments into those on the line of the previous code and those
+    /// on lines after it
+    fn split_trailing(comments: Vec<Comment<'a>>) -> (Vec<Comment<'a>>, Vec<Comment<'a>>) {
+        let split = comments
+            .iter()
+            .position(|c| c.own_line)
+            .unwrap_or(comments.len());
+        let mut trailing = comments;
+        let leading = trailing.split_off(split);
+        (trailing, leading)
+    }
+
+    fn value(&mut self, node: spanned::Node<'a>) -> Node<'a> {
+        let range = node.range;
+        let value = match node.value {
+            NodeValue::Object(members) => {
+                let members = members
+                    .into_iter()
+                    .map(|m| (Some((m.key, m.key_range)), m.value));
+                return self.container('{', '}', range, members);
+            }
+            NodeValue::Array(items) => {
+                return self.container('[', ']', range, items.into_iter().map(|i| (None, i)));
+            }
+            NodeValue::String(s) => Value::String(s),
+            NodeValue::Number(n) => Value::Number(n),
+            NodeValue::Null => Value::Null,
+            NodeValue::Boolean(b) => Value::Boolean(b),
+        };
+        self.last_end = range.end;
+        Node::Plain(value)
+    }
+
+    /// attaches the comments before `end`, those on the line of `last` to it
+    /// and the rest to whatever comes next
+    fn attach_comments(
+        &mut self,
+        end: usize,
+        last: &mut Option<Member<'a>>,
+        leading: &mut Vec<Comment<'a>>,
+    ) {
+        let (trailing, rest) = Self::split_trailing(self.comments_before(end));
+        match last.as_mut() {
+            Some(last) => last.trailing.extend(trailing),
+            // comments right after the open brace read like leading comments
+            None => leading.extend(trailing),
+        }
+        leading.extend(rest);
+    }
+
+    fn container(
+        &mut self,
+        open: char,
+        close: char,
+        range: Range<usize>,
+        entries: impl Iterator<Item = (Key<'a>, spanned::Node<'a>)>,
+    ) -> Node<'a> {
+        self.last_end = range.start + open.len_utf8();
+        let mut members: Vec<Member<'a>> = Vec::new();
+        let mut leading = Vec::new();
+        // where each decoded key is in `members`, for dropping duplicates
+        let mut positions = HashMap::new();
+        // the member parsed last, held back until its trailing comments are
+        // known so they are dropped or moved along with it
+        let mut last: Option<Member<'a>> = None;
+
+        for (key, value) in entries {
+            let start = key.as_ref().map_or(value.range.start, |(_, r)| r.start);
+            self.attach_comments(start, &mut last, &mut leading);
+            if let Some(last) = last.take() {
+                self.push_member(&mut members, &mut positions, last);
+            }
+
+            let (key, inner) = match key {
+                Some((key, key_range)) => {
+                    self.last_end = key_range.end;
+                    (Some(key), self.comments_before(value.range.start))
+                }
+                None => (None, Vec::new()),
+            };
+            // line comments can't be inlined, so they are moved above the entry
+            let (line, block): (Vec<_>, Vec<_>) = inner.into_iter().partition(Comment::is_line);
+            leading.extend(line);
+
+            last = Some(Member {
+                leading: std::mem::take(&mut leading),
+                key,
+                inner: block,
+                value: self.value(value),
+                trailing: Vec::new(),
+            });
+        }
+        self.attach_comments(range.end - close.len_utf8(), &mut last, &mut leading);
+        if let Some(last) = last.take() {
+            self.push_member(&mut members, &mut positions, last);
+        }
+        self.last_end = range.end;
+
+        let node = Node::Container {
+            open,
+            close,
+            members,
+            dangling: leading,
+        };
+        node.into_plain()
+    }
+
+    /// adds `member`, unless [`DuplicateKeys`] says to drop it or to replace
+    /// an earlier entry with it. Comments go with the entry they belong to
+    fn push_member(
+        &self,
+        members: &mut Vec<Member<'a>>,
+        positions: &mut HashMap<String, usize>,
+        member: Member<'a>,
+    ) {
+        let Some(key) = member.key.as_ref().filter(|_| {
+            matches!(
+                self.duplicate_keys,
+                DuplicateKeys::KeepFirst | DuplicateKeys::KeepLast
+            )
+        }) else {
+            members.push(member);
+            return;
+        };
+        match positions.entry(unescape(key).into_owned()) {
+            Entry::Vacant(entry) => {
+                entry.insert(members.len());
+                members.push(member);
+            }
+            Entry::Occupied(entry) => {
+                if self.duplicate_keys == DuplicateKeys::KeepLast {
+                    members[*entry.get()] = member;
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    /// converts containers without any comments back into a [`Value`]
+    fn into_plain(self) -> Self {
+        let Node::Container {
+            open,
+            close,
+            members,
+            dangling,
+        } = self
+        else {
+            return self;
+        };
+        let has_comments = !dangling.is_empty()
+            || members
+                .iter()
+                .any(|m| m.has_comments() || !matches!(m.value, Node::Plain(_)));
+        if has_comments {
+            return Node::Container {
+                open,
+                close,
+                members,
+                dangling,
+            };
+        }
+
+        let members = members.into_iter().map(|m| match m.value {
+            Node::Plain(value) => (m.key, value),
+            Node::Container { .. } => unreachable!("checked above"),
+        });
+        let value = if open == '{' {
+            Value::Object(ObjectEntries(
+                members
+                    .map(|(key, value)| (key.expect("object entries have keys"), value))
+                    .collect(),
+            ))
+        } else {
+            Value::Array(members.map(|(_, value)| value).collect())
+        };
+        Node::Plain(value)
+    }
+}
+
+fn write_comment(buf: &mut FormatBuf, comment: &Comment, depth: usize) {
+    let mut lines = comment.text.trim_end().lines();
+    if let Some(first) = lines.next() {
+        buf.push_str(first);
+    }
+    // continuation lines of block comments follow the surrounding indentation
+    for line in lines {
+        buf.write_eol();
+        buf.write_indent(depth);
+        let line = line.trim_start();
+        if line.starts_with('*') {
+            buf.push(' ');
+        }
+        buf.push_str(line);
+    }
+}
+
+fn write_own_line_comments(buf: &mut FormatBuf, comments: &[Comment], depth: usize) {
+    for comment in comments {
+        buf.write_indent(depth);
+        write_comment(buf, comment, depth);
+        buf.write_eol();
+    }
+}
+
+fn write_trailing_comments(buf: &mut FormatBuf, comments: &[Comment], depth: usize) {
+    for comment in comments {
+        buf.push(' ');
+        write_comment(buf, comment, depth);
+    }
+}
+
+fn write_node(buf: &mut FormatBuf, node: &Node, depth: usize) {
+    let (open, close, members, dangling) = match node {
+        Node::Plain(value) => return format_value_into(buf, value, depth),
+        Node::Container {
+            open,
+            close,
+            members,
+            dangling,
+        } => (open, close, members, dangling),
+    };
+
+    buf.push(*open);
+    buf.write_eol();
+    for (i, member) in members.iter().enumerate() {
+        write_own_line_comments(buf, &member.leading, depth + 1);
+        buf.write_indent(depth + 1);
+        if let Some(key) = &member.key {
+            buf.push_quoted(key);
+            buf.push(':');
+            buf.write_key_val_delimiter();
+        }
+        for comment in &member.inner {
+            write_comment(buf, comment, depth + 1);
+            buf.push(' ');
+        }
+        write_node(buf, &member.value, depth + 1);
+        if i + 1 < members.len() {
+            buf.push(',');
+        }
+        write_trailing_comments(buf, &member.trailing, depth + 1);
+        buf.write_eol();
+    }
+    write_own_line_comments(buf, dangling, depth + 1);
+    buf.write_indent(depth);
+    buf.push(*close);
+}
+
+/// formats json with comments, keeping comments next to the entries and items
+/// they describe. Trailing commas are removed
+pub fn format_str_with_comments<'a>(
+    json: &'a str,
+    dialect: Dialect,
+    options: FormatOptions,
+    preferred_width: usize,
+) -> Result<'a, String> {
+    let mut stream = options.tokens(json, dialect);
+    let node = spanned::Node::from_token_stream(&mut stream)?;
+    let mut parser = Parser {
+        text: json,
+        comments: stream.comments().collect::<Vec<_>>().into_iter().peekable(),
+        last_end: 0,
+        duplicate_keys: options.duplicate_keys,
+    };
+
+    let mut buf = FormatBuf::new(String::with_capacity(json.len()), options, preferred_width);
+    let leading = parser.comments_before(node.range.start);
+    write_own_line_comments(&mut buf, &leading, 0);
+    let value = parser.value(node);
+    write_node(&mut buf, &value, 0);
+
+    let (trailing, rest) = Parser::split_trailing(parser.comments_before(usize::MAX));
+    write_trailing_comments(&mut buf, &trailing, 0);
+    for comment in &rest {
+        buf.write_eol();
+        write_comment(&mut buf, comment, 0);
+    }
+
+    Ok(buf.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::LineEnding;
+
+    fn format(json: &str) -> String {
+        format_str_with_comments(
+            json,
+            Dialect::Jsonc,
+            FormatOptions::prettify(LineEnding::Lf),
+            80,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn without_comments_matches_strict_formatting() {
+        let json = r#"{"a": [1, 2, 3], "b": {"c": null,},}"#;
+        assert_eq!(
+            format(json),
+            crate::format::format_str(
+                r#"{"a": [1, 2, 3], "b": {"c": null}}"#,
+                FormatOptions::prettify(LineEnding::Lf),
+                80
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_leading_and_trailing_comments() {
+        let json = r#"
+// file header
+{
+    // the compiler
+  "compilerOptions": { "strict": true, /* why not */ "target": "es2022" // modern
+  },
+  "files": [
+      "a.ts", // entry
+      /* generated */ "b.ts",
+      // more to come
+  ],
+} // end
+// bye"#;
+        assert_eq!(
+            format(json),
+            r#"// file header
+{
+  // the compiler
+  "compilerOptions": {
+    "strict": true, /* why not */
+    "target": "es2022" // modern
+  },
+  "files": [
+    "a.ts", // entry
+    /* generated */
+    "b.ts"
+    // more to come
+  ]
+} // end
+// bye"#
+        );
+    }
+
+    #[test]
+    fn reindents_block_comments() {
+        let json = "[\n        /**\n         * docs\n         */\n        1\n]";
+        assert_eq!(format(json), "[\n  /**\n   * docs\n   */\n  1\n]");
+    }
+
+    #[test]
+    fn comments_in_empty_containers_and_between_key_and_value() {
+        assert_eq!(format("{ /* nothing */ }"), "{\n  /* nothing */\n}");
+        assert_eq!(
+            format(
+                r#"{"a": /* one */ 1, "b" // two
+            : 2}"#
+            ),
+            "{\n  \"a\": /* one */ 1,\n  // two\n  \"b\": 2\n}"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(DuplicateKeys::KeepFirst, "{\n  // first\n  \"a\": 1,\n  \"b\": 2\n}")]
+    #[case(DuplicateKeys::KeepLast, "{\n  // last\n  \"a\": 3,\n  \"b\": 2\n}")]
+    fn duplicate_keys_take_their_comments(
+        #[case] duplicate_keys: DuplicateKeys,
+        #[case] expected: &str,
+    ) {
+        let json = "{\n  // first\n  \"a\": 1,\n  \"b\": 2,\n  // last\n  \"a\": 3\n}";
+        let options = FormatOptions::prettify(LineEnding::Lf).with_duplicate_keys(duplicate_keys);
+        assert_eq!(
+            format_str_with_comments(json, Dialect::Jsonc, options, 80).unwrap(),
+            expected
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(
+        DuplicateKeys::KeepFirst,
+        "{\"a\":1, // one\n\"a\":3, // three\n\"b\":2 // two\n}",
+        "{\n  \"a\": 1, // one\n  \"b\": 2 // two\n}"
+    )]
+    #[case(
+        DuplicateKeys::KeepLast,
+        "{\"a\":1, // one\n\"b\":2, // two\n\"a\":3 // three\n}",
+        "{\n  \"a\": 3, // three\n  \"b\": 2 // two\n}"
+    )]
+    fn duplicate_keys_take_their_trailing_comments(
+        #[case] duplicate_keys: DuplicateKeys,
+        #[case] json: &str,
+        #[case] expected: &str,
+    ) {
+        let options = FormatOptions::prettify(LineEnding::Lf).with_duplicate_keys(duplicate_keys);
+        assert_eq!(
+            format_str_with_comments(json, Dialect::Jsonc, options, 80).unwrap(),
+            expected
+        );
+    }
+}
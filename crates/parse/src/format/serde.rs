@@ -9,17 +9,14 @@ impl From<serde_json::Value> for Value<'_> {
             serde_json::Value::Null => Value::Null,
             serde_json::Value::Bool(b) => Value::Boolean(b),
             serde_json::Value::Number(number) => Value::Number(number.to_string().into()),
-            serde_json::Value::String(s) => {
-                let s_static: &'static str = Box::leak(s.into_boxed_str());
-                Value::String(s_static)
-            }
+            serde_json::Value::String(s) => Value::String(s.into()),
             serde_json::Value::Array(values) => {
                 Value::Array(values.into_iter().map(Value::from).collect())
             }
             serde_json::Value::Object(map) => {
                 let entries = map
                     .into_iter()
-                    .map(|(k, v)| (&*k.leak(), v.into()))
+                    .map(|(k, v)| (k.into(), v.into()))
                     .collect::<Vec<_>>()
                     .into();
                 Value::Object(entries)
@@ -1,24 +1,105 @@
 use crate::{
-    Result,
-    ast::Value,
-    format::Emitter,
-    tokens::TokenStream,
-    traverse::{Visitor, parse_tokens, parse_value},
+    DuplicateKeys, Error, ErrorKind, Result,
+    ast::{Value, parse_token_stream},
+    format::{Emitter, IoWriter, join_into},
+    tokens::{Dialect, ReadTokens, TokenStream},
+    traverse::{Tokens, Visitor, parse_tokens},
+};
+use core::{
+    convert::Infallible,
+    ops::{ControlFlow, Range},
+};
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
 };
-use std::borrow::Cow;
 
 pub fn uglify_str(json: &str) -> Result<'_, String> {
+    uglify_str_with_dialect(json, Dialect::Json)
+}
+
+/// comments are removed along with whitespace
+pub fn uglify_str_with_dialect(json: &str, dialect: Dialect) -> Result<'_, String> {
+    uglify_str_with_duplicate_keys(json, dialect, DuplicateKeys::KeepAll)
+}
+
+/// like [`uglify_str_with_dialect`], keeping or dropping entries that use a
+/// key more than once following `duplicate_keys`
+pub fn uglify_str_with_duplicate_keys(
+    json: &str,
+    dialect: Dialect,
+    duplicate_keys: DuplicateKeys,
+) -> Result<'_, String> {
+    uglify_token_stream(
+        &mut TokenStream::with_dialect(json, dialect).with_duplicate_keys(duplicate_keys),
+    )
+}
+
+/// uglifies the value lexed by `tokens`, for lexers configured with more than
+/// a dialect such as [`TokenStream::allow_lone_surrogates`]
+pub fn uglify_token_stream<'a>(tokens: &mut TokenStream<'a>) -> Result<'a, String> {
+    if let DuplicateKeys::KeepFirst | DuplicateKeys::KeepLast = tokens.duplicate_keys() {
+        // entries can only be dropped once the whole object was read
+        return Ok(uglify_value(&parse_token_stream(tokens)?));
+    }
     let mut visitor = UglifyEmitVisitor::default();
-    parse_tokens(&mut TokenStream::new(json), json, true, &mut visitor)?;
+    parse_tokens(tokens, true, &mut visitor)?;
     Ok(visitor.buf)
 }
 
+pub fn uglify_to_writer(json: &str, writer: impl Write) -> Result<'_, ()> {
+    uglify_to_writer_with_dialect(json, Dialect::Json, writer)
+}
+
+/// like [`uglify_str_with_dialect`], but writes as the input is traversed.
+/// Output written before an error is left in `writer`
+pub fn uglify_to_writer_with_dialect(
+    json: &str,
+    dialect: Dialect,
+    writer: impl Write,
+) -> Result<'_, ()> {
+    let mut visitor = UglifyEmitVisitor {
+        buf: IoWriter::new(writer),
+    };
+    let mut tokens = TokenStream::with_dialect(json, dialect);
+    parse_tokens(&mut tokens, true, &mut visitor)?;
+    visitor
+        .buf
+        .finish()
+        .map_err(|error| Error::from_unterminated(ErrorKind::WriteFailed(error.to_string()), json))
+}
+
+/// uglifies input read a piece at a time into `writer`, so neither has to fit
+/// in memory. Output written before an error is left in `writer`
+pub fn uglify_reader(
+    reader: impl Read,
+    writer: impl Write,
+    dialect: Dialect,
+) -> Result<'static, ()> {
+    uglify_read_tokens(ReadTokens::new(reader, dialect), writer)
+}
+
+/// [`uglify_reader`] for readers configured with more than a dialect
+pub fn uglify_read_tokens(
+    mut tokens: ReadTokens<impl Read>,
+    writer: impl Write,
+) -> Result<'static, ()> {
+    let mut visitor = UglifyEmitVisitor {
+        buf: IoWriter::new(writer),
+    };
+    parse_tokens(&mut tokens, true, &mut visitor)?;
+    visitor
+        .buf
+        .finish()
+        .map_err(|error| tokens.unterminated(ErrorKind::WriteFailed(error.to_string())))
+}
+
 #[derive(Debug, Default)]
-pub struct UglifyEmitVisitor {
-    pub buf: String,
+pub struct UglifyEmitVisitor<E = String> {
+    pub buf: E,
 }
 
-impl Emitter for UglifyEmitVisitor {
+impl<E: Emitter> Emitter for UglifyEmitVisitor<E> {
     fn push(&mut self, c: char) {
         self.buf.push(c);
     }
@@ -28,54 +109,100 @@ impl Emitter for UglifyEmitVisitor {
     }
 }
 
-impl<'a> Visitor<'a> for UglifyEmitVisitor {
-    fn on_object_open(&mut self) {
+impl<'a, E: Emitter> Visitor<'a> for UglifyEmitVisitor<E> {
+    type Break = Infallible;
+
+    fn on_object_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_object_open();
+        ControlFlow::Continue(())
     }
 
-    fn on_object_key(&mut self, key: &str) {
-        self.emit_string(key);
+    fn on_object_key(&mut self, key: Cow<'_, str>, _: Range<usize>) -> ControlFlow<Infallible> {
+        self.emit_string(&key);
+        ControlFlow::Continue(())
     }
 
-    fn on_object_key_val_delim(&mut self) {
+    fn on_object_key_val_delim(&mut self) -> ControlFlow<Infallible> {
         self.emit_key_val_delim();
+        ControlFlow::Continue(())
     }
 
-    fn on_object_close(&mut self) {
+    fn on_object_close(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_object_close();
+        ControlFlow::Continue(())
     }
 
-    fn on_array_open(&mut self) {
+    fn on_array_open(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_array_open();
+        ControlFlow::Continue(())
     }
 
-    fn on_array_close(&mut self) {
+    fn on_array_close(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_array_close();
+        ControlFlow::Continue(())
     }
 
-    fn on_null(&mut self) {
+    fn on_null(&mut self, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_null();
+        ControlFlow::Continue(())
     }
 
-    fn on_string(&mut self, s: &str) {
-        self.emit_string(s);
+    fn on_string(&mut self, s: Cow<'_, str>, _: Range<usize>) -> ControlFlow<Infallible> {
+        self.emit_string(&s);
+        ControlFlow::Continue(())
     }
 
-    fn on_number(&mut self, n: Cow<'_, str>) {
+    fn on_number(&mut self, n: Cow<'_, str>, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_number(&n);
+        ControlFlow::Continue(())
     }
 
-    fn on_boolean(&mut self, b: bool) {
+    fn on_boolean(&mut self, b: bool, _: Range<usize>) -> ControlFlow<Infallible> {
         self.emit_boolean(b);
+        ControlFlow::Continue(())
     }
 
-    fn on_item_delim(&mut self) {
+    fn on_item_delim(&mut self) -> ControlFlow<Infallible> {
         self.emit_item_delim();
+        ControlFlow::Continue(())
     }
 }
 
 pub fn uglify_value(val: &Value) -> String {
-    let mut visitor = UglifyEmitVisitor::default();
-    parse_value(val, &mut visitor);
-    visitor.buf
+    let mut buf = String::new();
+    emit_value(&mut buf, val);
+    buf
+}
+
+fn emit_value(buf: &mut String, val: &Value) {
+    match val {
+        Value::Null => buf.emit_null(),
+        Value::String(s) => buf.emit_string(s),
+        Value::Number(n) => buf.emit_number(n),
+        Value::Boolean(b) => buf.emit_boolean(*b),
+        Value::Object(entries) => {
+            buf.emit_object_open();
+            join_into(
+                buf,
+                &entries.0,
+                |buf, (key, val)| {
+                    buf.emit_string(key);
+                    buf.emit_key_val_delim();
+                    emit_value(buf, val);
+                },
+                |buf, _| buf.emit_item_delim(),
+            );
+            buf.emit_object_close();
+        }
+        Value::Array(items) => {
+            buf.emit_array_open();
+            join_into(
+                buf,
+                items,
+                |buf, val| emit_value(buf, val),
+                |buf, _| buf.emit_item_delim(),
+            );
+            buf.emit_array_close();
+        }
+    }
 }
@@ -0,0 +1,199 @@
+//! checks for [I-JSON (RFC 7493)](https://datatracker.ietf.org/doc/html/rfc7493),
+//! the subset of json that every implementation reads the same way
+//!
+//! Lone surrogates are already rejected while lexing, so this only adds the
+//! checks that need the whole value
+
+use crate::{
+    DuplicateKeys, Error, ErrorKind,
+    error::Position,
+    tokens::{DecodedChars, Dialect, DoubleFit, TokenStream, fit_in_double},
+    traverse::{Tokens, Visitor, parse_tokens},
+};
+use core::{convert::Infallible, ops::ControlFlow, ops::Range};
+use std::borrow::Cow;
+
+/// every way `json` is not I-JSON, or its syntax error
+pub fn validate_ijson<'a>(json: &'a str, dialect: Dialect) -> Result<(), Vec<Error<'a>>> {
+    validate_ijson_at(json, Position::START, dialect)
+}
+
+/// [`validate_ijson`] for `document`, which is at `start` in a larger input
+pub fn validate_ijson_at<'a>(
+    document: &'a str,
+    start: Position,
+    dialect: Dialect,
+) -> Result<(), Vec<Error<'a>>> {
+    let mut visitor = IJsonVisitor {
+        text: document,
+        offset: start.offset,
+        errors: Vec::new(),
+    };
+    let mut tokens = TokenStream::with_dialect_at(document, dialect, start)
+        .with_duplicate_keys(DuplicateKeys::Warn);
+    parse_tokens(&mut tokens, true, &mut visitor).map_err(|error| vec![error])?;
+
+    let mut errors: Vec<_> = visitor
+        .errors
+        .into_iter()
+        .map(|(kind, range)| tokens.error(kind, range))
+        .collect();
+    errors.extend_from_slice(tokens.warnings());
+    errors.sort_by_key(|error| error.range().start);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+struct IJsonVisitor<'a> {
+    text: &'a str,
+    /// where `text` starts in the input
+    offset: usize,
+    /// turned into errors by the lexer once parsing is done, so they refer to
+    /// the same text as its errors
+    errors: Vec<(ErrorKind<'a>, Range<usize>)>,
+}
+
+impl<'a> IJsonVisitor<'a> {
+    fn error(&mut self, kind: ErrorKind<'a>, range: Range<usize>) {
+        self.errors.push((kind, range));
+    }
+
+    /// reports noncharacters in the string token at `range`
+    fn check_chars(&mut self, contents: &str, range: Range<usize>) {
+        // json5 strings are converted, so their contents aren't in the input
+        let inner = range.start - self.offset + 1..range.end - self.offset - 1;
+        let written = self.text.get(inner) == Some(contents);
+        for (char_range, c) in DecodedChars::new(contents) {
+            if is_noncharacter(c) {
+                let range = if written {
+                    range.start + 1 + char_range.start..range.start + 1 + char_range.end
+                } else {
+                    range.clone()
+                };
+                self.error(ErrorKind::Noncharacter(c.into()), range);
+            }
+        }
+    }
+}
+
+/// See [Unicode, Section 23.7](https://www.unicode.org/versions/latest/core-spec/chapter-23/#G12612)
+fn is_noncharacter(c: char) -> bool {
+    matches!(c, '\u{FDD0}'..='\u{FDEF}') || u32::from(c) & 0xFFFE == 0xFFFE
+}
+
+/// how a double is usually written back out
+fn display_double(double: f64) -> String {
+    if (1e-6..1e21).contains(&double.abs()) {
+        double.to_string()
+    } else {
+        format!("{double:e}")
+    }
+}
+
+impl<'a> Visitor<'a> for IJsonVisitor<'a> {
+    type Break = Infallible;
+
+    fn on_object_key(&mut self, key: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Infallible> {
+        self.check_chars(&key, range);
+        ControlFlow::Continue(())
+    }
+
+    fn on_string(&mut self, value: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Infallible> {
+        self.check_chars(&value, range);
+        ControlFlow::Continue(())
+    }
+
+    fn on_number(&mut self, value: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Infallible> {
+        match fit_in_double(&value) {
+            DoubleFit::Exact => {}
+            DoubleFit::Rounded(double) => self.error(
+                ErrorKind::NumberLosesPrecision {
+                    read_as: display_double(double),
+                },
+                range,
+            ),
+            DoubleFit::OutOfRange => self.error(ErrorKind::NumberOutOfRange, range),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violations(json: &str) -> Vec<(String, &str)> {
+        match validate_ijson(json, Dialect::Json) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .iter()
+                .map(|error| (error.kind().to_string(), &json[error.range()]))
+                .collect(),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case(r#"{"a": 1, "b": [1.5, -0, "text"], "c": {"a": null}}"#)]
+    #[case(r#"["🦀", "\ud83e\udd80", "\uFFFD", 9007199254740991]"#)]
+    fn accepts_ijson(#[case] json: &str) {
+        assert_eq!(violations(json), []);
+    }
+
+    #[test]
+    fn duplicate_keys_are_compared_decoded() {
+        assert_eq!(
+            violations(r#"{"café": 1, "caf\u00e9": 2, "other": {"café": 3}}"#),
+            [(
+                r"duplicate key `caf\u00e9`, keys have to be unique".to_owned(),
+                r#""caf\u00e9""#
+            )]
+        );
+    }
+
+    #[test]
+    fn numbers_have_to_fit_doubles() {
+        assert_eq!(
+            violations("[1e400, 9007199254740993, 0.1]"),
+            [
+                (
+                    "number is outside the range of an IEEE 754 double, so it is read differently everywhere".to_owned(),
+                    "1e400"
+                ),
+                (
+                    "number is read as 9007199254740992 by implementations that use IEEE 754 doubles".to_owned(),
+                    "9007199254740993"
+                ),
+            ]
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("\"a\u{FFFF}\"", "\u{FFFF}", "U+FFFF")]
+    #[case(r#""a\uFDD0""#, r"\uFDD0", "U+FDD0")]
+    #[case(r#""\ud83f\udffe""#, r"\ud83f\udffe", "U+1FFFE")]
+    fn noncharacters(#[case] json: &str, #[case] found: &str, #[case] code: &str) {
+        assert_eq!(
+            violations(json),
+            [(
+                format!(
+                    "string contains the noncharacter {code}, which is reserved for use inside programs"
+                ),
+                found
+            )]
+        );
+    }
+
+    #[test]
+    fn syntax_errors_come_first() {
+        assert_eq!(
+            violations(r#"{"a": 1, "a": 2"#),
+            [(
+                "expected comma or closed curly brace, found no significant characters".to_owned(),
+                "2"
+            )]
+        );
+    }
+}
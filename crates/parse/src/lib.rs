@@ -1,9 +1,22 @@
 pub mod ast;
 mod check;
 pub mod error;
+pub mod events;
 pub mod format;
+mod ijson;
+pub mod lines;
+pub mod lint;
+pub mod sequence;
 pub mod tokens;
 mod traverse;
 
-pub use crate::error::{Error, ErrorKind, Result};
-pub use check::validate_str;
+pub use crate::error::{Error, ErrorCode, ErrorKind, Result};
+pub use check::{
+    validate_at, validate_document, validate_finite_numbers, validate_finite_numbers_at,
+    validate_read_tokens, validate_reader, validate_str, validate_str_with_dialect,
+    validate_token_stream,
+};
+pub use events::DuplicateKeys;
+pub use ijson::{validate_ijson, validate_ijson_at};
+pub use tokens::Dialect;
+pub use traverse::{Visitor, visit_str, visit_str_with_dialect};
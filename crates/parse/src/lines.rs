@@ -0,0 +1,103 @@
+//! [json lines](https://jsonlines.org), also known as ndjson, where every line
+//! holds its own document
+//!
+//! Documents are validated in place, so errors point into the whole input and
+//! report the line the document is on
+
+use crate::{Error, error::Positions, tokens::Dialect, validate_at};
+use core::ops::Range;
+
+/// a non-blank line of json lines input
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Line<'a> {
+    /// the line without its line ending
+    pub text: &'a str,
+    /// byte offset of the line in the input
+    pub start: usize,
+}
+
+impl Line<'_> {
+    /// where the line is in the input, without its line ending
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.text.len()
+    }
+}
+
+/// the lines of `input` holding a document. Blank lines are skipped
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .split('\n')
+        .scan(0, |start, line| {
+            let current = *start;
+            *start += line.len() + 1;
+            Some(Line {
+                text: line.strip_suffix('\r').unwrap_or(line),
+                start: current,
+            })
+        })
+        .filter(|line| !line.text.trim().is_empty())
+}
+
+/// validates every line of `input`, returning an error for each invalid one
+pub fn validate_lines(input: &str, dialect: Dialect) -> Vec<Error<'_>> {
+    let mut positions = Positions::new(input);
+    lines(input)
+        .filter_map(|line| validate_at(line.text, positions.at(line.start), dialect).err())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::diagnostics::{Diagnostic, Source};
+
+    #[test]
+    fn skips_blank_lines_and_line_endings() {
+        let input = "{}\r\n\n  \n[1]\n";
+        assert_eq!(
+            lines(input).collect::<Vec<_>>(),
+            [
+                Line {
+                    text: "{}",
+                    start: 0
+                },
+                Line {
+                    text: "[1]",
+                    start: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_point_into_the_input() {
+        let input = "{\"a\": 1}\n[1, 2\n\"ok\"\n1 2\n";
+        let errors = validate_lines(input, Dialect::Json);
+        let lines: Vec<_> = errors
+            .iter()
+            .map(|error| error.to_string().rsplit_once(" at ").unwrap().1.to_owned())
+            .collect();
+        assert_eq!(lines, ["line 2 column 5", "line 4 column 3"]);
+    }
+
+    #[test]
+    fn errors_only_keep_their_line() {
+        let input = "1\n".repeat(1000) + "[1 2]\n";
+        let errors = validate_lines(&input, Dialect::Json);
+        let [error] = errors.as_slice() else {
+            panic!("expected one error, got {errors:?}")
+        };
+        assert_eq!((error.line(), error.column()), (1001, 4));
+        assert_eq!(&input[error.range()], "2");
+        let Source::Stdin(source) = Diagnostic::from(error).source else {
+            unreachable!("errors are named stdin by default")
+        };
+        assert_eq!(source, "[1 2]");
+    }
+
+    #[test]
+    fn each_line_is_one_document() {
+        assert!(validate_lines("1\n2\n", Dialect::Json).is_empty());
+        assert_eq!(validate_lines("[\n1]\n", Dialect::Json).len(), 2);
+    }
+}
@@ -0,0 +1,290 @@
+//! style and correctness rules for valid json, beyond what the syntax allows
+//!
+//! Rules look at a [`Node`] tree, which is like [`Value`](crate::ast::Value)
+//! but keeps where each part was found. Each rule has an id used to
+//! configure it, a default severity, and may suggest a [`Fix`]
+//!
+//! ```
+//! # use jjpwrgem_parse::{Dialect, lint::{Linter, Node}};
+//! let json = r#"{"first_name": "Ferris", "first_name": "Corro"}"#;
+//! let node = Node::parse(json, Dialect::Json).unwrap();
+//! let findings = Linter::default().lint(&node);
+//! let rules: Vec<_> = findings.iter().map(|finding| finding.rule).collect();
+//! assert_eq!(rules, ["camel-case-keys", "duplicate-keys", "camel-case-keys"]);
+//! ```
+
+mod rules;
+
+pub use crate::ast::spanned::{Member, Node, NodeValue};
+use crate::error::diagnostics::{Context, Diagnostic, Patch, Severity, Source};
+use core::ops::Range;
+pub use rules::{
+    CamelCaseKeys, DeepNesting, ExponentNumbers, LongStrings, MixedTypeArrays, NonEmptyKeys,
+    UniqueKeys,
+};
+
+/// a rule over a whole value
+///
+/// [`Linter`] calls [`Rule::check`] for every node, containers before what
+/// they hold
+pub trait Rule: Send + Sync {
+    /// kebab-case name used to turn the rule on or off
+    fn id(&self) -> &'static str;
+    /// a sentence on what the rule looks for
+    fn description(&self) -> &'static str;
+    fn default_severity(&self) -> Severity;
+    /// adds a finding for each problem with `node`, which is inside `depth`
+    /// containers
+    fn check(&self, node: &Node<'_>, depth: usize, findings: &mut Vec<Finding>);
+}
+
+/// a replacement that resolves a [`Finding`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fix {
+    pub message: String,
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// something a rule found
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Finding {
+    /// the [`Rule::id`] of the rule that found it
+    pub rule: &'static str,
+    /// set by [`Linter::lint`] from the configured severity
+    pub severity: Severity,
+    pub message: String,
+    pub range: Range<usize>,
+    /// related places, such as where a duplicate key was first used
+    pub context: Vec<(String, Range<usize>)>,
+    pub fix: Option<Fix>,
+}
+
+impl Finding {
+    pub fn new(rule: &'static str, message: impl Into<String>, range: Range<usize>) -> Self {
+        Self {
+            rule,
+            severity: Severity::default(),
+            message: message.into(),
+            range,
+            context: Vec::new(),
+            fix: None,
+        }
+    }
+
+    pub fn with_context(mut self, message: impl Into<String>, range: Range<usize>) -> Self {
+        self.context.push((message.into(), range));
+        self
+    }
+
+    pub fn with_fix(
+        mut self,
+        message: impl Into<String>,
+        range: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.fix = Some(Fix {
+            message: message.into(),
+            range,
+            replacement: replacement.into(),
+        });
+        self
+    }
+
+    /// `source` is the text the finding's ranges point into
+    pub fn to_diagnostic<'a>(&'a self, source: Source<'a>) -> Diagnostic<'a> {
+        let context = self
+            .context
+            .iter()
+            .map(|(message, range)| Context::new(message.as_str(), range.clone(), source))
+            .collect();
+        let patches = self
+            .fix
+            .iter()
+            .map(|fix| {
+                Patch::new(
+                    fix.message.as_str(),
+                    fix.range.clone(),
+                    source,
+                    fix.replacement.as_str(),
+                )
+            })
+            .collect();
+        Diagnostic::new(
+            format!("{} ({})", self.message, self.rule),
+            context,
+            patches,
+            source,
+            Some(self.range.clone()),
+        )
+        .with_severity(self.severity)
+    }
+}
+
+/// the rules to run and how serious their findings are. Rules without a
+/// severity are turned off
+pub struct Linter {
+    rules: Vec<(Box<dyn Rule>, Option<Severity>)>,
+}
+
+impl Default for Linter {
+    /// every built-in rule at its default severity
+    fn default() -> Self {
+        Self {
+            rules: builtin_rules()
+                .into_iter()
+                .map(|rule| {
+                    let severity = rule.default_severity();
+                    (rule, Some(severity))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Linter {
+    /// no rules at all
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// adds `rule` at its default severity, replacing a rule with the same id
+    pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Self {
+        self.rules.retain(|(r, _)| r.id() != rule.id());
+        let severity = rule.default_severity();
+        self.rules.push((rule, Some(severity)));
+        self
+    }
+
+    /// changes the severity of the rule with `id`, turning it off for `None`.
+    /// Returns whether there is such a rule
+    pub fn configure(&mut self, id: &str, severity: Option<Severity>) -> bool {
+        match self.rules.iter_mut().find(|(rule, _)| rule.id() == id) {
+            Some((_, configured)) => {
+                *configured = severity;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// every rule and its severity, `None` when it is off
+    pub fn rules(&self) -> impl Iterator<Item = (&dyn Rule, Option<Severity>)> {
+        self.rules
+            .iter()
+            .map(|(rule, severity)| (&**rule, *severity))
+    }
+
+    /// every finding of the configured rules, in input order
+    pub fn lint(&self, node: &Node<'_>) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.lint_node(node, 0, &mut findings);
+        findings.sort_by_key(|finding| finding.range.start);
+        findings
+    }
+
+    fn lint_node(&self, node: &Node<'_>, depth: usize, findings: &mut Vec<Finding>) {
+        for (rule, severity) in &self.rules {
+            let Some(severity) = severity else {
+                continue;
+            };
+            let found = findings.len();
+            rule.check(node, depth, findings);
+            for finding in &mut findings[found..] {
+                finding.severity = *severity;
+            }
+        }
+        match &node.value {
+            NodeValue::Array(items) => {
+                for item in items {
+                    self.lint_node(item, depth + 1, findings);
+                }
+            }
+            NodeValue::Object(members) => {
+                for member in members {
+                    self.lint_node(&member.value, depth + 1, findings);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// every rule that comes with the linter
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(UniqueKeys),
+        Box::new(NonEmptyKeys),
+        Box::new(CamelCaseKeys),
+        Box::new(MixedTypeArrays),
+        Box::new(DeepNesting::default()),
+        Box::new(LongStrings::default()),
+        Box::new(ExponentNumbers),
+    ]
+}
+
+/// `json` with every fix applied. Fixes overlapping an earlier fix are skipped
+pub fn apply_fixes<'a>(json: &str, findings: impl IntoIterator<Item = &'a Finding>) -> String {
+    let mut fixes: Vec<_> = findings
+        .into_iter()
+        .filter_map(|finding| finding.fix.as_ref())
+        .collect();
+    fixes.sort_by_key(|fix| fix.range.start);
+
+    let mut fixed = String::with_capacity(json.len());
+    let mut end = 0;
+    for fix in fixes {
+        if fix.range.start < end {
+            continue;
+        }
+        fixed.push_str(&json[end..fix.range.start]);
+        fixed.push_str(&fix.replacement);
+        end = fix.range.end;
+    }
+    fixed.push_str(&json[end..]);
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::Dialect;
+
+    #[test]
+    fn configured_severities_and_removed_rules() {
+        let node = Node::parse(r#"{"": 1e3}"#, Dialect::Json).unwrap();
+        let mut linter = Linter::default();
+        assert!(linter.configure("empty-keys", Some(Severity::Error)));
+        assert!(linter.configure("exponent-numbers", None));
+        assert!(linter.configure("camel-case-keys", None));
+        assert!(linter.configure("camel-case-keys", Some(Severity::Warning)));
+        assert!(!linter.configure("no-such-rule", None));
+        let findings: Vec<_> = linter
+            .lint(&node)
+            .into_iter()
+            .map(|finding| (finding.rule, finding.severity))
+            .collect();
+        assert_eq!(findings, [("empty-keys", Severity::Error)]);
+
+        let linter = Linter::empty().with_rule(Box::new(ExponentNumbers));
+        let rules: Vec<_> = linter.lint(&node).iter().map(|f| f.rule).collect();
+        assert_eq!(rules, ["exponent-numbers"]);
+    }
+
+    #[test]
+    fn fixes_apply_in_order_skipping_overlaps() {
+        let json = r#"{"snake_case": [1e2, 2.5E-1]}"#;
+        let node = Node::parse(json, Dialect::Json).unwrap();
+        let findings = Linter::default().lint(&node);
+        assert_eq!(
+            apply_fixes(json, &findings),
+            r#"{"snakeCase": [100, 0.25]}"#
+        );
+
+        let overlapping = [
+            Finding::new("a", "", 0..3).with_fix("", 0..3, "x"),
+            Finding::new("b", "", 1..2).with_fix("", 1..2, "y"),
+        ];
+        assert_eq!(apply_fixes("abcd", &overlapping), "xd");
+    }
+}
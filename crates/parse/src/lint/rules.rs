@@ -0,0 +1,503 @@
+//! the rules that come with the linter
+
+use super::{Finding, Node, NodeValue, Rule};
+use crate::{error::diagnostics::Severity, tokens::unescape};
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+/// objects using a key more than once, which implementations read differently
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UniqueKeys;
+
+impl Rule for UniqueKeys {
+    fn id(&self) -> &'static str {
+        "duplicate-keys"
+    }
+
+    fn description(&self) -> &'static str {
+        "objects should not use a key more than once"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::Object(members) = &node.value else {
+            return;
+        };
+        let mut first_used = HashMap::new();
+        for member in members {
+            match first_used.entry(unescape(&member.key)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(member.key_range.clone());
+                }
+                Entry::Occupied(entry) => findings.push(
+                    Finding::new(
+                        self.id(),
+                        format!("duplicate key `{}`", member.key),
+                        member.key_range.clone(),
+                    )
+                    .with_context("first used here", entry.get().clone()),
+                ),
+            }
+        }
+    }
+}
+
+/// keys that are empty strings
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NonEmptyKeys;
+
+impl Rule for NonEmptyKeys {
+    fn id(&self) -> &'static str {
+        "empty-keys"
+    }
+
+    fn description(&self) -> &'static str {
+        "keys should not be empty"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::Object(members) = &node.value else {
+            return;
+        };
+        for member in members.iter().filter(|member| member.key.is_empty()) {
+            findings.push(Finding::new(
+                self.id(),
+                "empty key",
+                member.key_range.clone(),
+            ));
+        }
+    }
+}
+
+/// keys that aren't camelCase, such as `snake_case` or `PascalCase` keys
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CamelCaseKeys;
+
+impl Rule for CamelCaseKeys {
+    fn id(&self) -> &'static str {
+        "camel-case-keys"
+    }
+
+    fn description(&self) -> &'static str {
+        "keys should be camelCase"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::Object(members) = &node.value else {
+            return;
+        };
+        let keys: HashSet<_> = members.iter().map(|m| unescape(&m.key)).collect();
+        for member in members {
+            let key = unescape(&member.key);
+            // empty keys have their own rule
+            if key.is_empty() || is_camel_case(&key) {
+                continue;
+            }
+            let finding = Finding::new(
+                self.id(),
+                format!("key `{}` is not camelCase", member.key),
+                member.key_range.clone(),
+            );
+            // renaming onto another key would make a duplicate
+            let camel = to_camel_case(&key).filter(|camel| !keys.contains(camel.as_str()));
+            findings.push(match camel {
+                Some(camel) => finding.with_fix(
+                    format!("rename it to `{camel}`"),
+                    member.key_range.clone(),
+                    format!("\"{camel}\""),
+                ),
+                None => finding,
+            });
+        }
+    }
+}
+
+fn is_camel_case(key: &str) -> bool {
+    key.starts_with(char::is_lowercase) && key.chars().all(char::is_alphanumeric)
+}
+
+/// joins the words of `key`, `None` if that doesn't make a camelCase key
+fn to_camel_case(key: &str) -> Option<String> {
+    let mut camel = String::with_capacity(key.len());
+    for word in key
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let mut chars = word.chars();
+        let first = chars.next().expect("empty words are skipped");
+        let rest = chars.as_str();
+        if camel.is_empty() {
+            // an acronym such as `ID` is lowercased whole
+            if word.chars().all(|c| !c.is_lowercase()) {
+                camel.extend(word.chars().flat_map(char::to_lowercase));
+            } else {
+                camel.extend(first.to_lowercase());
+                camel.push_str(rest);
+            }
+        } else {
+            camel.extend(first.to_uppercase());
+            camel.push_str(rest);
+        }
+    }
+    is_camel_case(&camel).then_some(camel)
+}
+
+/// arrays whose items aren't all the same type
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MixedTypeArrays;
+
+impl Rule for MixedTypeArrays {
+    fn id(&self) -> &'static str {
+        "mixed-type-arrays"
+    }
+
+    fn description(&self) -> &'static str {
+        "arrays should hold items of a single type"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::Array(items) = &node.value else {
+            return;
+        };
+        let Some((first, rest)) = items.split_first() else {
+            return;
+        };
+        let expected = type_name(first);
+        if let Some(other) = rest.iter().find(|item| type_name(item) != expected) {
+            findings.push(
+                Finding::new(
+                    self.id(),
+                    format!("array mixes {expected}s and {}s", type_name(other)),
+                    other.range.clone(),
+                )
+                .with_context(
+                    format!("the first item is a {expected}"),
+                    first.range.clone(),
+                ),
+            );
+        }
+    }
+}
+
+fn type_name(node: &Node<'_>) -> &'static str {
+    match node.value {
+        NodeValue::Null => "null",
+        NodeValue::Boolean(_) => "boolean",
+        NodeValue::Number(_) => "number",
+        NodeValue::String(_) => "string",
+        NodeValue::Array(_) => "array",
+        NodeValue::Object(_) => "object",
+    }
+}
+
+/// containers nested deeper than `max_depth`, reported once at the first
+/// container past the limit
+#[derive(Debug, Clone, Copy)]
+pub struct DeepNesting {
+    pub max_depth: usize,
+}
+
+impl Default for DeepNesting {
+    fn default() -> Self {
+        Self { max_depth: 10 }
+    }
+}
+
+impl Rule for DeepNesting {
+    fn id(&self) -> &'static str {
+        "deep-nesting"
+    }
+
+    fn description(&self) -> &'static str {
+        "containers should not be nested too deeply"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, depth: usize, findings: &mut Vec<Finding>) {
+        if depth == self.max_depth
+            && matches!(node.value, NodeValue::Array(_) | NodeValue::Object(_))
+        {
+            findings.push(Finding::new(
+                self.id(),
+                format!("nested more than {} levels deep", self.max_depth),
+                // the whole container would hide the message
+                node.range.start..node.range.start + 1,
+            ));
+        }
+    }
+}
+
+/// strings longer than `max_chars` decoded characters
+#[derive(Debug, Clone, Copy)]
+pub struct LongStrings {
+    pub max_chars: usize,
+}
+
+impl Default for LongStrings {
+    fn default() -> Self {
+        Self { max_chars: 1000 }
+    }
+}
+
+impl Rule for LongStrings {
+    fn id(&self) -> &'static str {
+        "long-strings"
+    }
+
+    fn description(&self) -> &'static str {
+        "strings should not be very long"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::String(s) = &node.value else {
+            return;
+        };
+        let chars = unescape(s).chars().count();
+        if chars > self.max_chars {
+            findings.push(Finding::new(
+                self.id(),
+                format!(
+                    "string is {chars} characters long, more than {}",
+                    self.max_chars
+                ),
+                node.range.clone(),
+            ));
+        }
+    }
+}
+
+/// numbers written with an exponent, such as `1e3`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ExponentNumbers;
+
+/// exponents up to this size are written out in fixes
+const MAX_FIXED_EXPONENT: i32 = 20;
+
+impl Rule for ExponentNumbers {
+    fn id(&self) -> &'static str {
+        "exponent-numbers"
+    }
+
+    fn description(&self) -> &'static str {
+        "numbers should be written without an exponent"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, node: &Node<'_>, _: usize, findings: &mut Vec<Finding>) {
+        let NodeValue::Number(n) = &node.value else {
+            return;
+        };
+        if !n.contains(['e', 'E']) || n.starts_with("0x") || n.starts_with("-0x") {
+            return;
+        }
+        let finding = Finding::new(
+            self.id(),
+            format!("number `{n}` uses an exponent"),
+            node.range.clone(),
+        );
+        findings.push(match without_exponent(n) {
+            Some(plain) => {
+                finding.with_fix(format!("write it as `{plain}`"), node.range.clone(), plain)
+            }
+            None => finding,
+        });
+    }
+}
+
+/// `number` with its exponent applied to the decimal point, keeping every
+/// digit written. `None` for exponents too large to write out
+fn without_exponent(number: &str) -> Option<String> {
+    let (mantissa, exponent) = number.split_once(['e', 'E'])?;
+    let exponent: i32 = exponent.parse().ok()?;
+    if exponent.abs() > MAX_FIXED_EXPONENT {
+        return None;
+    }
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", mantissa),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    let point = i32::try_from(int.len()).ok()? + exponent;
+
+    let (int, frac) = if point <= 0 {
+        let zeros = "0".repeat(point.unsigned_abs() as usize);
+        ("0".to_owned(), format!("{zeros}{digits}"))
+    } else {
+        let point = point as usize;
+        if point >= digits.len() {
+            (
+                format!("{digits}{}", "0".repeat(point - digits.len())),
+                String::new(),
+            )
+        } else {
+            (digits[..point].to_owned(), digits[point..].to_owned())
+        }
+    };
+    let int = match int.trim_start_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    Some(if frac.is_empty() {
+        format!("{sign}{int}")
+    } else {
+        format!("{sign}{int}.{frac}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::Dialect;
+
+    fn check(rule: &dyn Rule, json: &str) -> Vec<(String, String)> {
+        let mut findings = Vec::new();
+        fn walk(rule: &dyn Rule, node: &Node<'_>, depth: usize, findings: &mut Vec<Finding>) {
+            rule.check(node, depth, findings);
+            match &node.value {
+                NodeValue::Array(items) => items
+                    .iter()
+                    .for_each(|n| walk(rule, n, depth + 1, findings)),
+                NodeValue::Object(members) => members
+                    .iter()
+                    .for_each(|m| walk(rule, &m.value, depth + 1, findings)),
+                _ => {}
+            }
+        }
+        walk(
+            rule,
+            &Node::parse(json, Dialect::Json).unwrap(),
+            0,
+            &mut findings,
+        );
+        findings
+            .into_iter()
+            .map(|finding| (finding.message, json[finding.range].to_owned()))
+            .collect()
+    }
+
+    fn strings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| ((*a).to_owned(), (*b).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_keys_are_compared_decoded() {
+        assert_eq!(
+            check(&UniqueKeys, r#"{"a": 1, "a": 2, "b": {"a": 3}}"#),
+            strings(&[(r"duplicate key `a`", r#""a""#)])
+        );
+    }
+
+    #[test]
+    fn empty_keys() {
+        assert_eq!(
+            check(&NonEmptyKeys, r#"{"": 1, " ": 2}"#),
+            strings(&[("empty key", r#""""#)])
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("first_name", Some("firstName"))]
+    #[case("kebab-case-key", Some("kebabCaseKey"))]
+    #[case("PascalCase", Some("pascalCase"))]
+    #[case("ID", Some("id"))]
+    #[case("user ID", Some("userID"))]
+    #[case("$schema", Some("schema"))]
+    #[case("2fa", None)]
+    #[case("_", None)]
+    fn camel_case_fixes(#[case] key: &str, #[case] expected: Option<&str>) {
+        assert!(!is_camel_case(key));
+        assert_eq!(to_camel_case(key).as_deref(), expected);
+    }
+
+    #[test]
+    fn camel_case_keys() {
+        assert_eq!(
+            check(
+                &CamelCaseKeys,
+                r#"{"camelCase": 1, "ünïcode": 2, "snake_case": 3}"#
+            ),
+            strings(&[("key `snake_case` is not camelCase", r#""snake_case""#)])
+        );
+    }
+
+    #[test]
+    fn camel_case_fixes_avoid_existing_keys() {
+        let node = Node::parse(r#"{"a_b": 1, "aB": 2, "c_d": 3}"#, Dialect::Json).unwrap();
+        let mut findings = Vec::new();
+        CamelCaseKeys.check(&node, 0, &mut findings);
+        let replacements: Vec<_> = findings
+            .iter()
+            .map(|f| f.fix.as_ref().map(|fix| fix.replacement.as_str()))
+            .collect();
+        assert_eq!(replacements, [None, Some(r#""cD""#)]);
+    }
+
+    #[rstest::rstest]
+    #[case("[1, 2, 3]", &[])]
+    #[case("[[1], [\"a\"]]", &[])]
+    #[case(r#"[1, 2, "three", null]"#, &[("array mixes numbers and strings", r#""three""#)])]
+    fn mixed_type_arrays(#[case] json: &str, #[case] expected: &[(&str, &str)]) {
+        assert_eq!(check(&MixedTypeArrays, json), strings(expected));
+    }
+
+    #[test]
+    fn deep_nesting_is_reported_once() {
+        let rule = DeepNesting { max_depth: 2 };
+        assert_eq!(
+            check(&rule, "[[[1]], [[[2]]]]"),
+            [
+                ("nested more than 2 levels deep".to_owned(), "[".to_owned()),
+                ("nested more than 2 levels deep".to_owned(), "[".to_owned()),
+            ]
+        );
+        assert_eq!(check(&rule, "[[1], {\"a\": 2}]"), []);
+    }
+
+    #[test]
+    fn long_strings_count_decoded_characters() {
+        let rule = LongStrings { max_chars: 3 };
+        assert_eq!(
+            check(&rule, r#"["abc", "ééé", "abcd"]"#),
+            strings(&[("string is 4 characters long, more than 3", r#""abcd""#)])
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("1e3", Some("1000"))]
+    #[case("-2.5E-1", Some("-0.25"))]
+    #[case("1.50e1", Some("15.0"))]
+    #[case("0.5e+1", Some("5"))]
+    #[case("123e-5", Some("0.00123"))]
+    #[case("1e400", None)]
+    fn exponents_written_out(#[case] number: &str, #[case] expected: Option<&str>) {
+        assert_eq!(without_exponent(number).as_deref(), expected);
+    }
+}
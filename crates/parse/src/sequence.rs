@@ -0,0 +1,168 @@
+//! inputs holding several top-level values, either written back to back as
+//! concatenated json or as an [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464)
+//! json text sequence
+//!
+//! Ranges and errors point into the whole input
+
+use crate::{
+    Error, ErrorKind, Result,
+    check::NoopVisitor,
+    error::Positions,
+    tokens::{Dialect, TokenStream},
+    traverse::parse_tokens,
+    validate_at,
+};
+use core::ops::Range;
+
+/// starts every json text in a json text sequence
+pub const RECORD_SEPARATOR: char = '\u{1E}';
+
+/// the ranges of successive top-level values in a token stream, with any
+/// whitespace between them. Stops after the first error since there is no
+/// telling where the next value would start
+#[derive(Debug, Clone)]
+pub struct Values<'a> {
+    tokens: TokenStream<'a>,
+    failed: bool,
+}
+
+impl<'a> Values<'a> {
+    pub fn new(tokens: TokenStream<'a>) -> Self {
+        Self {
+            tokens,
+            failed: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = Result<'a, Range<usize>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = match self.tokens.peek_token() {
+            Ok(None) => return None,
+            Ok(Some(_)) => parse_tokens(&mut self.tokens, false, &mut NoopVisitor),
+            Err(error) => Err(error),
+        }
+        // the input holds the other values too
+        .map_err(Error::partial);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// the values of concatenated json, such as `{"a": 1}{"a": 2} [3]`
+pub fn concatenated(input: &str, dialect: Dialect) -> Values<'_> {
+    Values::new(TokenStream::with_dialect(input, dialect))
+}
+
+/// the values of a json text sequence, where each value follows a record
+/// separator. Blank records are skipped, and an invalid record doesn't stop
+/// the ones after it
+pub fn json_text_sequence(
+    input: &str,
+    dialect: Dialect,
+) -> impl Iterator<Item = Result<'_, Range<usize>>> {
+    let mut positions = Positions::new(input);
+    records(input).map(move |record| {
+        record.and_then(|range| {
+            validate_at(&input[range.clone()], positions.at(range.start), dialect)
+        })
+    })
+}
+
+/// the ranges of the non-blank records of a json text sequence, without
+/// validating them. Content before the first record separator is an error
+pub fn records(input: &str) -> impl Iterator<Item = Result<'_, Range<usize>>> {
+    input
+        .split(RECORD_SEPARATOR)
+        .scan(0, |start, record| {
+            let range = *start..*start + record.len();
+            *start = range.end + RECORD_SEPARATOR.len_utf8();
+            Some(range)
+        })
+        .enumerate()
+        .filter_map(move |(i, range)| {
+            let record = &input[range.clone()];
+            let content = record.trim_start();
+            if content.is_empty() {
+                return None;
+            }
+            if i > 0 {
+                return Some(Ok(range));
+            }
+
+            // anything before the first separator is missing one
+            let at = range.start + record.len() - content.len();
+            let len = content.chars().next().map_or(0, char::len_utf8);
+            Some(Err(Error::new(
+                ErrorKind::ExpectedRecordSeparator,
+                at..at + len,
+                input,
+            )))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slices<'a>(
+        input: &'a str,
+        values: impl Iterator<Item = Result<'a, Range<usize>>>,
+    ) -> Vec<&'a str> {
+        values.map(|range| &input[range.unwrap()]).collect()
+    }
+
+    #[test]
+    fn concatenated_values() {
+        let input = "{\"a\": 1}{\"a\": 2}\n [3]\"x\"4 null";
+        assert_eq!(
+            slices(input, concatenated(input, Dialect::Json)),
+            ["{\"a\": 1}", "{\"a\": 2}", "[3]", "\"x\"", "4", "null"]
+        );
+    }
+
+    #[test]
+    fn concatenated_stops_at_first_error() {
+        let values: Vec<_> = concatenated("[1] [2,] [3]", Dialect::Json).collect();
+        assert_eq!(values.len(), 2);
+        assert!(values[0].is_ok());
+        assert!(values[1].is_err());
+    }
+
+    #[test]
+    fn empty_input_has_no_values() {
+        assert_eq!(concatenated(" \n", Dialect::Json).count(), 0);
+        assert_eq!(json_text_sequence("\n", Dialect::Json).count(), 0);
+    }
+
+    #[test]
+    fn sequence_values() {
+        let input = "\u{1E}{\"a\": 1}\n\u{1E}\n\u{1E}[2]\n";
+        assert_eq!(
+            slices(input, json_text_sequence(input, Dialect::Json)),
+            ["{\"a\": 1}", "[2]"]
+        );
+    }
+
+    #[test]
+    fn sequence_reports_each_invalid_record() {
+        let input = "1\n\u{1E}[\n\u{1E}2\n\u{1E}3 4\n";
+        let errors: Vec<_> = json_text_sequence(input, Dialect::Json)
+            .filter_map(|value| value.err())
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "expected record separator before json text at line 1 column 1",
+                "expected entry or closed delimiter `]`, found no significant characters at line 2 column 2",
+                "unexpected token `4` after json finished at line 4 column 4",
+            ]
+        );
+    }
+}
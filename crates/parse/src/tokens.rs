@@ -1,12 +1,48 @@
+pub mod javascript;
+pub mod json5;
+mod keyword;
 pub mod lexical;
 mod number;
+mod read;
 mod stream;
 mod string;
 
 use crate::tokens::lexical::JsonChar;
 use core::{fmt::Display, ops::Range};
+pub use number::{DoubleFit, fit_in_double};
+pub use read::ReadTokens;
 use std::borrow::Cow;
 pub use stream::TokenStream;
+pub use string::{DecodedChars, unescape};
+
+/// the flavor of json to accept
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Dialect {
+    /// strict json as described by [RFC 8259](https://datatracker.ietf.org/doc/html/rfc8259)
+    #[default]
+    Json,
+    /// json with `//` and `/* */` comments and trailing commas, as used by
+    /// tsconfig.json and VS Code settings
+    Jsonc,
+    /// [JSON5](https://spec.json5.org), which adds comments, trailing commas,
+    /// unquoted keys, single quoted strings, hexadecimal numbers, `Infinity`
+    /// and `NaN` among others
+    Json5,
+}
+
+impl Dialect {
+    pub fn allows_comments(self) -> bool {
+        matches!(self, Self::Jsonc | Self::Json5)
+    }
+
+    pub fn allows_trailing_commas(self) -> bool {
+        matches!(self, Self::Jsonc | Self::Json5)
+    }
+
+    pub fn is_json5(self) -> bool {
+        matches!(self, Self::Json5)
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Token<'a> {
@@ -16,7 +52,10 @@ pub enum Token<'a> {
     Comma,
     OpenSquareBracket,
     ClosedSquareBracket,
-    String(&'a str),
+    /// the escaped contents of a string, converted to json escapes in json5
+    String(Cow<'a, str>),
+    /// an unquoted object key, only produced in json5
+    Identifier(Cow<'a, str>),
     Number(Cow<'a, str>),
     Null,
     Boolean(bool),
@@ -35,6 +74,23 @@ impl<'a> Token<'a> {
         )
     }
 
+    /// copies any borrowed text so the token can outlive its input
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::OpenCurlyBrace => Token::OpenCurlyBrace,
+            Token::ClosedCurlyBrace => Token::ClosedCurlyBrace,
+            Token::Colon => Token::Colon,
+            Token::Comma => Token::Comma,
+            Token::OpenSquareBracket => Token::OpenSquareBracket,
+            Token::ClosedSquareBracket => Token::ClosedSquareBracket,
+            Token::String(s) => Token::String(Cow::Owned(s.into_owned())),
+            Token::Identifier(s) => Token::Identifier(Cow::Owned(s.into_owned())),
+            Token::Number(n) => Token::Number(Cow::Owned(n.into_owned())),
+            Token::Null => Token::Null,
+            Token::Boolean(b) => Token::Boolean(b),
+        }
+    }
+
     pub fn is_scalar(&self) -> bool {
         matches!(
             self,
@@ -53,6 +109,7 @@ impl Display for Token<'_> {
             Token::OpenSquareBracket => "[",
             Token::ClosedSquareBracket => "]",
             Token::String(x) => &format!("{x:?}"),
+            Token::Identifier(x) => x.as_ref(),
             Token::Number(x) => x.as_ref(),
             Token::Boolean(x) => &format!("{x:?}"),
             Token::Null => NULL,
@@ -75,6 +132,12 @@ impl Display for TokenOption<'_> {
     }
 }
 
+impl TokenOption<'_> {
+    pub fn into_owned(self) -> TokenOption<'static> {
+        TokenOption(self.0.map(Token::into_owned))
+    }
+}
+
 impl<'a> From<Option<Token<'a>>> for TokenOption<'a> {
     fn from(value: Option<Token<'a>>) -> Self {
         Self(value)
@@ -187,7 +250,7 @@ mod tests {
                     range: 0..1
                 },
                 TokenWithContext {
-                    token: Token::String("rust"),
+                    token: Token::String("rust".into()),
                     range: 1..7
                 },
                 TokenWithContext {
@@ -195,7 +258,7 @@ mod tests {
                     range: 7..8
                 },
                 TokenWithContext {
-                    token: Token::String("is a must"),
+                    token: Token::String("is a must".into()),
                     range: 9..20
                 },
                 TokenWithContext {
@@ -211,8 +274,8 @@ mod tests {
     #[case("null", Token::Null)]
     #[case("true", Token::Boolean(true))]
     #[case("false", Token::Boolean(false))]
-    #[case("\"burger\"", Token::String("burger"))]
-    #[case(r#""\"burger\"""#, Token::String(r#"\"burger\""#))]
+    #[case("\"burger\"", Token::String("burger".into()))]
+    #[case(r#""\"burger\"""#, Token::String(r#"\"burger\""#.into()))]
     #[case(r#"0"#, 0.into())]
     #[case(r#"12389"#, 12389.into())]
     #[case(r#"-12389"#, (-12389).into())]
@@ -248,7 +311,7 @@ mod tests {
                     range: 0..1
                 },
                 TokenWithContext {
-                    token: Token::String("rust"),
+                    token: Token::String("rust".into()),
                     range: 18..24
                 },
                 TokenWithContext {
@@ -290,6 +353,43 @@ mod tests {
         ErrorKind::UnexpectedCharacter('n'.into()),
         Some(0..1)
     ))]
+    #[case(json_to_json_and_error(
+        "[Nul]",
+        ErrorKind::MisspelledKeyword {
+            found: "Nul".into(),
+            keyword: NULL
+        },
+        Some(1..4)
+    ))]
+    #[case(json_to_json_and_error(
+        "[nulll]",
+        ErrorKind::MisspelledKeyword {
+            found: "nulll".into(),
+            keyword: NULL
+        },
+        Some(1..6)
+    ))]
+    #[case(json_to_json_and_error(
+        "[truee]",
+        ErrorKind::MisspelledKeyword {
+            found: "truee".into(),
+            keyword: TRUE
+        },
+        Some(1..6)
+    ))]
+    #[case(json_to_json_and_error(
+        "[falsey]",
+        ErrorKind::MisspelledKeyword {
+            found: "falsey".into(),
+            keyword: FALSE
+        },
+        Some(1..7)
+    ))]
+    #[case(json_to_json_and_error(
+        "[nullable]",
+        ErrorKind::UnexpectedCharacter('n'.into()),
+        Some(1..9)
+    ))]
     #[case(json_to_json_and_error(
         r#""
     
@@ -317,7 +417,7 @@ mod tests {
                     range: 0..1
                 },
                 TokenWithContext {
-                    token: Token::String("rust"),
+                    token: Token::String("rust".into()),
                     range: 18..24
                 },
                 TokenWithContext {
@@ -325,7 +425,7 @@ mod tests {
                     range: 24..25
                 },
                 TokenWithContext {
-                    token: Token::String("is a must"),
+                    token: Token::String("is a must".into()),
                     range: 26..37
                 },
                 TokenWithContext {
@@ -333,7 +433,7 @@ mod tests {
                     range: 37..38
                 },
                 TokenWithContext {
-                    token: Token::String("name"),
+                    token: Token::String("name".into()),
                     range: 55..61
                 },
                 TokenWithContext {
@@ -341,7 +441,7 @@ mod tests {
                     range: 61..62
                 },
                 TokenWithContext {
-                    token: Token::String("ferris"),
+                    token: Token::String("ferris".into()),
                     range: 63..71
                 },
                 TokenWithContext {
@@ -368,4 +468,91 @@ mod tests {
             ]
         )
     }
+
+    #[rstest::rstest]
+    #[case("// line\n[]")]
+    #[case("[/* block */]")]
+    #[case("[] // no trailing newline")]
+    #[case("/**/[]/* a\n * multi line\n */")]
+    #[case("[]//\r\n")]
+    fn jsonc_skips_comments(#[case] json: &str) {
+        let tokens = stream::TokenStream::with_dialect(json, Dialect::Jsonc)
+            .map(|t| t.map(|t| t.token))
+            .collect::<Result<Vec<_>>>();
+        assert_eq!(
+            tokens,
+            Ok(vec![Token::OpenSquareBracket, Token::ClosedSquareBracket])
+        );
+    }
+
+    #[rstest::rstest]
+    #[case("[] // comment\n", 3..13)]
+    #[case("[/* block */]", 1..12)]
+    #[case("[/* unterminated", 1..16)]
+    fn json_rejects_whole_comment(#[case] json: &'static str, #[case] range: Range<usize>) {
+        assert_eq!(
+            str_to_tokens(json),
+            Err(Error::new(ErrorKind::CommentNotAllowed, range, json))
+        );
+    }
+
+    #[test]
+    fn jsonc_unterminated_block_comment() {
+        let json = "[] /*/";
+        assert_eq!(
+            stream::TokenStream::with_dialect(json, Dialect::Jsonc).collect::<Result<Vec<_>>>(),
+            Err(Error::new(ErrorKind::UnterminatedBlockComment, 3..5, json))
+        );
+    }
+
+    #[test]
+    fn lone_slash_is_unexpected() {
+        let json = "/";
+        assert_eq!(
+            stream::TokenStream::with_dialect(json, Dialect::Jsonc).collect::<Result<Vec<_>>>(),
+            Err(Error::new(
+                ErrorKind::UnexpectedCharacter('/'.into()),
+                0..1,
+                json
+            ))
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(r#""plain""#, "plain")]
+    #[case(r#"'single "quoted"'"#, r#"single \"quoted\""#)]
+    #[case(r#"'it\'s'"#, "it's")]
+    #[case("'line \\\ncontinued'", "line continued")]
+    #[case(r"'\x41\v\0\q'", r"A\u000B\u0000q")]
+    #[case("'tab\there'", r"tab\there")]
+    #[case(r#""🦀""#, r"🦀")]
+    fn json5_strings_become_json_strings(#[case] json5: &str, #[case] expected: &str) {
+        let tokens = stream::TokenStream::with_dialect(json5, Dialect::Json5)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(tokens[0].token, Token::String(expected.into()));
+    }
+
+    #[test]
+    fn json5_identifiers() {
+        let json5 = "{key: null, $_ünï: Infinity}";
+        let tokens = stream::TokenStream::with_dialect(json5, Dialect::Json5)
+            .map(|t| t.map(|t| t.token))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            [
+                Token::OpenCurlyBrace,
+                Token::Identifier("key".into()),
+                Token::Colon,
+                Token::Null,
+                Token::Comma,
+                Token::Identifier("$_ünï".into()),
+                Token::Colon,
+                Token::Number("Infinity".into()),
+                Token::ClosedCurlyBrace,
+            ]
+        );
+    }
 }
@@ -0,0 +1,174 @@
+//! javascript syntax that neither json nor json5 allow, usually left over
+//! from pasting an object literal
+//!
+//! Both lexers use these to explain the error and suggest the json
+//! equivalent instead of pointing at an unexpected character
+
+use crate::{
+    Error, ErrorKind,
+    tokens::{NULL, json5, lexical::JsonChar, string::push_json_char},
+};
+use displaydoc::Display;
+
+pub const UNDEFINED: &str = "undefined";
+
+/// syntax that is only allowed in javascript
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display)]
+pub enum JavaScriptSyntax {
+    /// a template literal
+    TemplateLiteral,
+    /// `undefined`
+    Undefined,
+    /// an octal number
+    OctalNumber,
+    /// a binary number
+    BinaryNumber,
+}
+
+/// the length of the template literal at the start of `s` and the json
+/// string it is equivalent to, if json can represent it. `None` if it is
+/// unterminated
+fn template_literal(s: &str) -> Option<(usize, Option<String>)> {
+    let mut contents = String::from('"');
+    let mut representable = true;
+    let mut chars = s.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '`' => {
+                contents.push('"');
+                return Some((i + 1, representable.then_some(contents)));
+            }
+            // substitutions need a javascript runtime
+            '$' if chars.next_if(|(_, c)| *c == '{').is_some() => representable = false,
+            '\\' => match chars.next()? {
+                (j, 'u')
+                    if s.get(j + 1..j + 5).is_some_and(|digits| {
+                        digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_hexdigit())
+                    }) =>
+                {
+                    contents.push_str("\\u");
+                }
+                (_, c @ ('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't')) => {
+                    contents.push('\\');
+                    contents.push(c);
+                }
+                (_, c @ ('`' | '$' | '\'')) => push_json_char(&mut contents, c),
+                // line continuations
+                (_, '\n' | '\u{2028}' | '\u{2029}') => {}
+                (_, '\r') => {
+                    chars.next_if(|(_, c)| *c == '\n');
+                }
+                _ => representable = false,
+            },
+            c => push_json_char(&mut contents, c),
+        }
+    }
+    None
+}
+
+/// the syntax, radix and digits of an octal or binary number at the start of
+/// `s`. `None` if it has no digits
+fn radix_number(s: &str) -> Option<(JavaScriptSyntax, u32, &str)> {
+    let rest = s.strip_prefix('0')?;
+    let (syntax, radix) = match rest.chars().next()? {
+        'o' | 'O' => (JavaScriptSyntax::OctalNumber, 8),
+        'b' | 'B' => (JavaScriptSyntax::BinaryNumber, 2),
+        _ => return None,
+    };
+    let rest = &rest[1..];
+    let end = rest
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| (syntax, radix, &rest[..end]))
+}
+
+/// an error for javascript syntax at `start`, for either lexer
+pub fn syntax_error<'a>(input: &'a str, start: usize) -> Option<Error<'a>> {
+    let rest = &input[start..];
+    let (syntax, range, replacement) = match rest.chars().next()? {
+        '`' => match template_literal(rest) {
+            Some((len, replacement)) => (
+                JavaScriptSyntax::TemplateLiteral,
+                start..start + len,
+                replacement,
+            ),
+            None => (JavaScriptSyntax::TemplateLiteral, start..start + 1, None),
+        },
+        c @ ('-' | '0') => {
+            let sign = usize::from(c == '-');
+            let (syntax, radix, digits) = radix_number(&rest[sign..])?;
+            let end = start + sign + "0o".len() + digits.len();
+            let replacement = format!("{}{}", &rest[..sign], json5::to_decimal(digits, radix));
+            (syntax, start..end, Some(replacement))
+        }
+        c if json5::is_identifier_start(c) => {
+            let word = json5::identifier(rest);
+            // json5 allows `undefined` as an unquoted key
+            let after = rest[word.len()..]
+                .trim_start_matches(|c| JsonChar(c).is_whitespace() || json5::is_whitespace(c));
+            if word != UNDEFINED || after.starts_with([':', '/']) {
+                return None;
+            }
+            (
+                JavaScriptSyntax::Undefined,
+                start..start + word.len(),
+                Some(NULL.into()),
+            )
+        }
+        _ => return None,
+    };
+
+    Some(Error::new(
+        ErrorKind::JavaScriptSyntax {
+            syntax,
+            replacement,
+        },
+        range,
+        input,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ops::Range;
+
+    #[rstest::rstest]
+    #[case("`hi`", JavaScriptSyntax::TemplateLiteral, 0..4, Some(r#""hi""#))]
+    #[case(
+        "`say \"hi\"\n\\`ok\\``",
+        JavaScriptSyntax::TemplateLiteral,
+        0..17,
+        Some(r#""say \"hi\"\n`ok`""#)
+    )]
+    #[case(r"`é\t`", JavaScriptSyntax::TemplateLiteral, 0..6, Some(r#""é\t""#))]
+    #[case("`hi ${name}`", JavaScriptSyntax::TemplateLiteral, 0..12, None)]
+    #[case(r"`\x41`", JavaScriptSyntax::TemplateLiteral, 0..6, None)]
+    #[case("`hi", JavaScriptSyntax::TemplateLiteral, 0..1, None)]
+    #[case("undefined", JavaScriptSyntax::Undefined, 0..9, Some("null"))]
+    #[case("0o17", JavaScriptSyntax::OctalNumber, 0..4, Some("15"))]
+    #[case("-0B101", JavaScriptSyntax::BinaryNumber, 0..6, Some("-5"))]
+    fn detects_javascript_syntax(
+        #[case] input: &str,
+        #[case] expected: JavaScriptSyntax,
+        #[case] range: Range<usize>,
+        #[case] replacement: Option<&str>,
+    ) {
+        let kind = ErrorKind::JavaScriptSyntax {
+            syntax: expected,
+            replacement: replacement.map(Into::into),
+        };
+        assert_eq!(syntax_error(input, 0), Some(Error::new(kind, range, input)));
+    }
+
+    #[rstest::rstest]
+    #[case("undefined: 1")]
+    #[case("undefinedness")]
+    #[case("0o")]
+    #[case("0b2")]
+    #[case("-1")]
+    #[case("0x10")]
+    fn ignores_other_syntax(#[case] input: &str) {
+        assert_eq!(syntax_error(input, 0), None);
+    }
+}
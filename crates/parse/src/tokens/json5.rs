@@ -0,0 +1,285 @@
+//! syntax json5 adds on top of json
+//!
+//! The json5 lexer uses these to accept the extra syntax, and the strict
+//! lexer uses them to point out json5 written where json was expected
+
+use crate::{
+    Error, ErrorKind,
+    tokens::{CharWithContext, Token, lexical::JsonChar, string::parse_json5_string},
+};
+use core::ops::Range;
+use displaydoc::Display;
+
+pub const INFINITY: &str = "Infinity";
+pub const NAN: &str = "NaN";
+
+/// syntax that is only allowed in json5
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display)]
+pub enum Json5Syntax {
+    /// a single quoted string
+    SingleQuotedString,
+    /// an unquoted key
+    UnquotedKey,
+    /// a hexadecimal number
+    HexadecimalNumber,
+    /// `Infinity`
+    Infinity,
+    /// `NaN`
+    NaN,
+    /// a leading decimal point
+    LeadingDecimalPoint,
+    /// a trailing decimal point
+    TrailingDecimalPoint,
+    /// a plus sign
+    PlusSign,
+}
+
+/// `Infinity` or `NaN` at the start of `s`, unless it is only the start of a
+/// longer identifier like `NaNa`
+pub fn non_finite_number(s: &str) -> Option<&'static str> {
+    let word = identifier(s);
+    [INFINITY, NAN].into_iter().find(|name| word == *name)
+}
+
+/// the digits of a hexadecimal number at the start of `s`, which may be empty
+pub fn hex_digits(s: &str) -> Option<&str> {
+    let rest = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    let end = rest
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// converts digits in `radix` of any length to decimal digits
+pub fn to_decimal(radix_digits: &str, radix: u32) -> String {
+    // little endian decimal digits
+    let mut digits = vec![0u32];
+    for h in radix_digits.chars() {
+        let mut carry = h.to_digit(radix).expect("only called with digits in radix");
+        for digit in &mut digits {
+            let value = *digit * radix + carry;
+            *digit = value % 10;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            digits.push(carry % 10);
+            carry /= 10;
+        }
+    }
+    while digits.len() > 1 && digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+        .iter()
+        .rev()
+        .map(|d| char::from_digit(*d, 10).expect("digits are below 10"))
+        .collect()
+}
+
+/// where the decimal point is in a number at the start of `s` that ends with
+/// one, like `5.`. `None` unless the digits before it are valid json
+fn trailing_decimal_point(s: &str) -> Option<usize> {
+    let sign = usize::from(s.starts_with('-'));
+    let rest = &s[sign..];
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let integer = &rest[..digits];
+    let valid = integer == "0" || (!integer.is_empty() && !integer.starts_with('0'));
+    let after = &rest[digits..];
+    let trailing = after.starts_with('.') && !after[1..].starts_with(|c: char| c.is_ascii_digit());
+    (valid && trailing).then_some(sign + digits)
+}
+
+/// See [ECMAScript IdentifierName](https://262.ecma-international.org/5.1/#sec-7.6).
+/// Unicode escapes in identifiers are not supported
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || matches!(c, '$' | '_')
+}
+
+/// see [is_identifier_start]
+pub fn is_identifier_part(c: char) -> bool {
+    is_identifier_start(c) || c.is_alphanumeric() || matches!(c, '\u{200C}' | '\u{200D}')
+}
+
+/// the identifier at the start of `s`, which may be empty
+pub fn identifier(s: &str) -> &str {
+    let mut chars = s.char_indices();
+    let end = match chars.next() {
+        Some((_, c)) if is_identifier_start(c) => chars
+            .find(|(_, c)| !is_identifier_part(*c))
+            .map_or(s.len(), |(i, _)| i),
+        _ => 0,
+    };
+    &s[..end]
+}
+
+/// the token an identifier stands for. Anything other than a literal is an
+/// unquoted key
+pub fn identifier_token(identifier: &str) -> Token<'_> {
+    match identifier {
+        "null" => Token::Null,
+        "true" => Token::Boolean(true),
+        "false" => Token::Boolean(false),
+        INFINITY | NAN => Token::Number(identifier.into()),
+        _ => Token::Identifier(identifier.into()),
+    }
+}
+
+/// See [ECMAScript WhiteSpace](https://262.ecma-international.org/5.1/#sec-7.2)
+/// and [LineTerminator](https://262.ecma-international.org/5.1/#sec-7.3)
+pub fn is_whitespace(c: char) -> bool {
+    c.is_whitespace() || c == '\u{FEFF}'
+}
+
+/// json5 syntax found by [strict_error], the range it covers and the json it
+/// can be replaced with
+type Found = (Json5Syntax, Range<usize>, Option<String>);
+
+/// json5 syntax in a number at the start of `rest`, found at `start`
+fn number_syntax(rest: &str, start: usize) -> Option<Found> {
+    let sign = usize::from(rest.starts_with('-'));
+    let body = &rest[sign..];
+    if let Some(name) = non_finite_number(body) {
+        let syntax = match name {
+            INFINITY => Json5Syntax::Infinity,
+            _ => Json5Syntax::NaN,
+        };
+        Some((syntax, start..start + sign + name.len(), None))
+    } else if let Some(digits) = hex_digits(body).filter(|d| !d.is_empty()) {
+        let decimal = to_decimal(digits, 16);
+        let end = start + sign + "0x".len() + digits.len();
+        let replacement = format!("{}{decimal}", &rest[..sign]);
+        Some((
+            Json5Syntax::HexadecimalNumber,
+            start..end,
+            Some(replacement),
+        ))
+    } else {
+        let dot = trailing_decimal_point(rest)?;
+        Some((
+            Json5Syntax::TrailingDecimalPoint,
+            start + dot..start + dot + 1,
+            Some(String::new()),
+        ))
+    }
+}
+
+/// an unquoted key at the start of `rest`, found at `start`
+fn unquoted_key(rest: &str, start: usize) -> Option<Found> {
+    let key = identifier(rest);
+    let after = rest[key.len()..].trim_start_matches(|c| JsonChar(c).is_whitespace());
+    if key.is_empty() || !after.starts_with(':') {
+        return None;
+    }
+    Some((
+        Json5Syntax::UnquotedKey,
+        start..start + key.len(),
+        Some(format!("\"{key}\"")),
+    ))
+}
+
+/// an error for json5 syntax at `start`, for lexers that only accept json
+pub fn strict_error<'a>(input: &'a str, start: usize) -> Option<Error<'a>> {
+    let rest = &input[start..];
+    let after_first = rest.get(1..).unwrap_or_default();
+    let (syntax, range, replacement) = match rest.chars().next()? {
+        '\'' => {
+            let mut chars = rest
+                .char_indices()
+                .map(|(i, c)| CharWithContext::from((start + i, c)))
+                .peekable();
+            match parse_json5_string(input, &mut chars, true) {
+                Ok(string) => {
+                    let Token::String(s) = string.token else {
+                        unreachable!("string parsing only produces strings")
+                    };
+                    (
+                        Json5Syntax::SingleQuotedString,
+                        string.range,
+                        Some(format!("\"{s}\"")),
+                    )
+                }
+                Err(_) => (Json5Syntax::SingleQuotedString, start..start + 1, None),
+            }
+        }
+        '+' if after_first
+            .starts_with(|c: char| c.is_ascii_digit() || matches!(c, '.' | 'I' | 'N')) =>
+        {
+            (Json5Syntax::PlusSign, start..start + 1, Some(String::new()))
+        }
+        '.' if after_first.starts_with(|c: char| c.is_ascii_digit()) => (
+            Json5Syntax::LeadingDecimalPoint,
+            start..start + 1,
+            Some("0.".into()),
+        ),
+        // keys like `Name` start the same way as `NaN`
+        '-' | '0'..='9' | 'I' | 'N' => {
+            number_syntax(rest, start).or_else(|| unquoted_key(rest, start))?
+        }
+        c if is_identifier_start(c) => unquoted_key(rest, start)?,
+        _ => return None,
+    };
+
+    Some(Error::new(
+        ErrorKind::Json5Syntax {
+            syntax,
+            replacement,
+        },
+        range,
+        input,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case("0", "0")]
+    #[case("ff", "255")]
+    #[case("00010", "16")]
+    #[case("123456789abcdef", "81985529216486895")]
+    fn converts_hex_to_decimal(#[case] hex: &str, #[case] expected: &str) {
+        assert_eq!(to_decimal(hex, 16), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("'hi'", Json5Syntax::SingleQuotedString, 0..4, Some(r#""hi""#))]
+    #[case("+1", Json5Syntax::PlusSign, 0..1, Some(""))]
+    #[case(".5", Json5Syntax::LeadingDecimalPoint, 0..1, Some("0."))]
+    #[case("5.", Json5Syntax::TrailingDecimalPoint, 1..2, Some(""))]
+    #[case("-0.e3", Json5Syntax::TrailingDecimalPoint, 2..3, Some(""))]
+    #[case("-0x10", Json5Syntax::HexadecimalNumber, 0..5, Some("-16"))]
+    #[case("-Infinity", Json5Syntax::Infinity, 0..9, None)]
+    #[case("NaN", Json5Syntax::NaN, 0..3, None)]
+    #[case("key : 1", Json5Syntax::UnquotedKey, 0..3, Some(r#""key""#))]
+    #[case("Name: 1", Json5Syntax::UnquotedKey, 0..4, Some(r#""Name""#))]
+    #[case("Id: 1", Json5Syntax::UnquotedKey, 0..2, Some(r#""Id""#))]
+    #[case("NaNa: 1", Json5Syntax::UnquotedKey, 0..4, Some(r#""NaNa""#))]
+    fn detects_json5_syntax(
+        #[case] input: &str,
+        #[case] expected: Json5Syntax,
+        #[case] range: Range<usize>,
+        #[case] replacement: Option<&str>,
+    ) {
+        let kind = ErrorKind::Json5Syntax {
+            syntax: expected,
+            replacement: replacement.map(Into::into),
+        };
+        assert_eq!(strict_error(input, 0), Some(Error::new(kind, range, input)));
+    }
+
+    #[rstest::rstest]
+    #[case("-1")]
+    #[case("0.5")]
+    #[case("05.")]
+    #[case("5")]
+    #[case("null, 1")]
+    #[case("x")]
+    #[case("NaNa")]
+    #[case("Infinityx")]
+    fn ignores_other_syntax(#[case] input: &str) {
+        assert_eq!(strict_error(input, 0), None);
+    }
+}
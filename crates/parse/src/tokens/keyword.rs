@@ -0,0 +1,106 @@
+//! recovery for misspelled `true`, `false` and `null`
+
+use crate::tokens::{FALSE, NULL, TRUE, json5};
+use crate::{Error, ErrorKind};
+
+const KEYWORDS: [&str; 3] = [TRUE, FALSE, NULL];
+
+/// the keyword `word` was most likely meant to be, ignoring case and allowing
+/// one typo, where swapping two neighboring letters counts as one
+pub fn closest_keyword(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    KEYWORDS
+        .into_iter()
+        .map(|keyword| (edit_distance(&word, keyword), keyword))
+        .filter(|(distance, _)| *distance <= 1)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, keyword)| keyword)
+}
+
+/// an error spanning the word at `start` of `input`, `None` unless it looks
+/// like a misspelled keyword
+pub fn misspelled_keyword(input: &str, start: usize) -> Option<Error<'_>> {
+    let word = json5::identifier(&input[start..]);
+    let keyword = closest_keyword(word)?;
+    Some(Error::new(
+        ErrorKind::MisspelledKeyword {
+            found: word.into(),
+            keyword,
+        },
+        start..start + word.len(),
+        input,
+    ))
+}
+
+/// the optimal string alignment distance, counting insertions, deletions,
+/// substitutions and transpositions of chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    // rows for the previous two prefixes of `a`
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = previous[j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("True", Some(TRUE))]
+    #[case("NULL", Some(NULL))]
+    #[case("trbe", Some(TRUE))]
+    #[case("tru", Some(TRUE))]
+    #[case("flase", Some(FALSE))]
+    #[case("nul", Some(NULL))]
+    #[case("nulll", Some(NULL))]
+    #[case("truee", Some(TRUE))]
+    #[case("falsey", Some(FALSE))]
+    #[case("Nil", None)]
+    #[case("n", None)]
+    #[case("yes", None)]
+    #[case("", None)]
+    fn finds_closest_keyword(#[case] word: &str, #[case] expected: Option<&str>) {
+        assert_eq!(closest_keyword(word), expected);
+    }
+
+    #[rstest]
+    #[case("", "null", 4)]
+    #[case("null", "null", 0)]
+    #[case("nul", "null", 1)]
+    #[case("flase", "false", 1)]
+    #[case("ture", "true", 1)]
+    #[case("trbe", "true", 1)]
+    #[case("abc", "ca", 3)]
+    fn counts_edits(#[case] a: &str, #[case] b: &str, #[case] expected: usize) {
+        assert_eq!(edit_distance(a, b), expected);
+    }
+
+    #[test]
+    fn spans_the_whole_word() {
+        let input = "[trbe]";
+        assert_eq!(
+            misspelled_keyword(input, 1),
+            Some(Error::new(
+                ErrorKind::MisspelledKeyword {
+                    found: "trbe".into(),
+                    keyword: TRUE,
+                },
+                1..5,
+                input,
+            ))
+        );
+    }
+}
@@ -4,8 +4,9 @@ use itertools::Itertools;
 
 use crate::{
     Error, ErrorKind, Result,
-    tokens::{CharWithContext, Token, TokenWithContext, lexical::JsonChar},
+    tokens::{CharWithContext, Token, TokenWithContext, json5, lexical::JsonChar},
 };
+use std::borrow::Cow;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum NumberState<'a> {
@@ -39,14 +40,21 @@ impl<'a> NumberState<'a> {
             range,
         })
     }
+    /// `json5` also accepts a leading `+` and leading or trailing decimal points
     fn process(
         self,
         chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
         input: &'a str,
+        json5: bool,
     ) -> Result<'a, Self> {
         let res = match self {
             NumberState::MinusOrInteger => match chars.next() {
                 Some(CharWithContext(range, JsonChar('-'))) => NumberState::Leading(range),
+                Some(CharWithContext(range, JsonChar('+'))) if json5 => NumberState::Leading(range),
+                Some(dot @ CharWithContext(_, JsonChar('.'))) if json5 => NumberState::Fraction {
+                    number_range: dot.0.clone(),
+                    dot_range: dot,
+                },
                 Some(leading @ CharWithContext(_, JsonChar('0'..='9'))) => {
                     NumberState::IntegerOrDecimalOrExponentOrEnd {
                         leading: Some(leading.clone()),
@@ -68,6 +76,10 @@ impl<'a> NumberState<'a> {
                         number_range: number_range.start..leading.0.end,
                     }
                 }
+                Some(dot @ CharWithContext(_, JsonChar('.'))) if json5 => NumberState::Fraction {
+                    number_range: number_range.start..dot.0.end,
+                    dot_range: dot,
+                },
                 maybe_char @ (Some(_) | None) => {
                     return Err(Error::new(
                         ErrorKind::ExpectedDigitFollowingMinus(
@@ -138,6 +150,19 @@ impl<'a> NumberState<'a> {
                     chars.next();
                     NumberState::FractionOrExponentOrEnd(number_range.start..range.end)
                 }
+                // a trailing decimal point still needs digits before it
+                Some(CharWithContext(ref range, JsonChar('e' | 'E')))
+                    if json5 && has_integer_digits(&input[number_range.clone()]) =>
+                {
+                    let exponent = chars.next().expect("peeked");
+                    NumberState::MinusOrPlusOrDigit {
+                        number_range: number_range.start..range.end,
+                        e_range: exponent,
+                    }
+                }
+                _ if json5 && has_integer_digits(&input[number_range.clone()]) => {
+                    Self::make_end(input, number_range)
+                }
                 maybe_c => {
                     return Err(Error::from_maybe_json_char_with_context(
                         |c| ErrorKind::ExpectedDigitAfterDot {
@@ -241,13 +266,244 @@ impl<'a> NumberState<'a> {
 pub fn parse_num<'a>(
     input: &'a str,
     chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+) -> Result<'a, TokenWithContext<'a>> {
+    parse_decimal(input, chars, false)
+}
+
+fn parse_decimal<'a>(
+    input: &'a str,
+    chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+    json5: bool,
 ) -> Result<'a, TokenWithContext<'a>> {
     let mut state = NumberState::MinusOrInteger;
 
     loop {
-        state = state.process(chars, input)?;
+        state = state.process(chars, input, json5)?;
         if let NumberState::End(tok) = state {
             break Ok(tok);
         }
     }
 }
+
+fn has_integer_digits(number: &str) -> bool {
+    number
+        .trim_start_matches(['+', '-'])
+        .starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// json5 numbers also allow a leading `+`, leading or trailing decimal
+/// points, hexadecimal integers, `Infinity` and `NaN`. Each is converted to
+/// the equivalent json number, except `Infinity` and `NaN` which json can't
+/// represent and are kept as is
+///
+/// See [the JSON5 spec](https://spec.json5.org/#numbers)
+pub fn parse_json5_num<'a>(
+    input: &'a str,
+    chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+) -> Result<'a, TokenWithContext<'a>> {
+    let start = chars
+        .peek()
+        .map_or(input.len(), |CharWithContext(r, _)| r.start);
+    let negative = input[start..].starts_with('-');
+    let body_start = start + usize::from(input[start..].starts_with(['+', '-']));
+    let body = &input[body_start..];
+
+    if let Some(name) = json5::non_finite_number(body) {
+        let range = start..body_start + name.len();
+        skip_to(chars, range.end);
+        let token = match (negative, name) {
+            (true, json5::INFINITY) => Cow::Borrowed("-Infinity"),
+            _ => Cow::Borrowed(name),
+        };
+        return Ok(TokenWithContext {
+            token: Token::Number(token),
+            range,
+        });
+    }
+
+    if let Some(digits) = json5::hex_digits(body) {
+        let digits_start = body_start + "0x".len();
+        skip_to(chars, digits_start);
+        if digits.is_empty() {
+            return Err(Error::from_maybe_json_char_with_context(
+                |c| ErrorKind::ExpectedHexDigitInNumber {
+                    number_range: start..digits_start,
+                    maybe_c: c,
+                },
+                chars.next(),
+                input,
+            ));
+        }
+        let range = start..digits_start + digits.len();
+        skip_to(chars, range.end);
+        let decimal = json5::to_decimal(digits, 16);
+        let token = if negative && decimal != "0" {
+            format!("-{decimal}")
+        } else {
+            decimal
+        };
+        return Ok(TokenWithContext {
+            token: Token::Number(token.into()),
+            range,
+        });
+    }
+
+    let TokenWithContext { token, range } = parse_decimal(input, chars, true)?;
+    let Token::Number(number) = token else {
+        unreachable!("number parsing only produces numbers")
+    };
+    Ok(TokenWithContext {
+        token: Token::Number(normalize_json5_decimal(number)),
+        range,
+    })
+}
+
+/// drops a leading `+`, adds a zero before a leading decimal point and removes
+/// a trailing decimal point
+fn normalize_json5_decimal(number: Cow<'_, str>) -> Cow<'_, str> {
+    let Cow::Borrowed(number) = number else {
+        return number;
+    };
+    let number = number.strip_prefix('+').unwrap_or(number);
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+    let (mantissa, exponent) = unsigned
+        .find(['e', 'E'])
+        .map_or((unsigned, ""), |i| unsigned.split_at(i));
+
+    let leading_dot = mantissa.starts_with('.');
+    let trailing_dot = mantissa.ends_with('.');
+    if !leading_dot && !trailing_dot {
+        return Cow::Borrowed(number);
+    }
+    let zero = if leading_dot { "0" } else { "" };
+    let mantissa = mantissa.strip_suffix('.').unwrap_or(mantissa);
+    Cow::Owned(format!("{sign}{zero}{mantissa}{exponent}"))
+}
+
+/// how the contents of a number token are read as an IEEE 754 double
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DoubleFit {
+    Exact,
+    /// read as the nearest double, which is a different number
+    Rounded(f64),
+    /// too large, or too small to be told apart from zero
+    OutOfRange,
+}
+
+/// whether `number` is read as a double without changing its value. A number
+/// counts as exact when it is the shortest way to write the double nearest to
+/// it, so `0.1` is exact but `0.10000000000000001` is not
+pub fn fit_in_double(number: &str) -> DoubleFit {
+    let Ok(double) = number.parse::<f64>() else {
+        return DoubleFit::OutOfRange;
+    };
+    let Some(written) = significand(number) else {
+        // every digit is zero
+        return DoubleFit::Exact;
+    };
+    if !double.is_finite() || double == 0.0 {
+        return DoubleFit::OutOfRange;
+    }
+    match significand(&format!("{double:e}")) {
+        Some(read) if read == written => DoubleFit::Exact,
+        _ => DoubleFit::Rounded(double),
+    }
+}
+
+/// the sign, the digits without leading or trailing zeros and the power of
+/// ten of the last digit, `None` for zero or an exponent too large to hold
+fn significand(number: &str) -> Option<(bool, String, i64)> {
+    let (negative, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, number),
+    };
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(i) => (&unsigned[..i], unsigned[i + 1..].parse::<i64>().ok()?),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{integer}{fraction}");
+    let trimmed = digits.trim_start_matches('0');
+    let significant = trimmed.trim_end_matches('0');
+    if significant.is_empty() {
+        return None;
+    }
+    let trailing_zeros = (trimmed.len() - significant.len()) as i64;
+    let power = exponent.checked_sub(fraction.len() as i64)? + trailing_zeros;
+    Some((negative, significant.to_owned(), power))
+}
+
+fn skip_to(chars: &mut Peekable<impl Iterator<Item = CharWithContext>>, end: usize) {
+    while chars
+        .next_if(|CharWithContext(r, _)| r.start < end)
+        .is_some()
+    {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::{Dialect, TokenStream};
+
+    #[rstest::rstest]
+    #[case("0", DoubleFit::Exact)]
+    #[case("-0.000e99999999999999999999", DoubleFit::Exact)]
+    #[case("0.1", DoubleFit::Exact)]
+    #[case("1.50E+3", DoubleFit::Exact)]
+    #[case("-123.456e-7", DoubleFit::Exact)]
+    #[case("9007199254740992", DoubleFit::Exact)]
+    #[case("1.7976931348623157e308", DoubleFit::Exact)]
+    #[case("9007199254740993", DoubleFit::Rounded(9007199254740992.0))]
+    #[case("0.10000000000000001", DoubleFit::Rounded(0.1))]
+    #[case("1e400", DoubleFit::OutOfRange)]
+    #[case("-1e400", DoubleFit::OutOfRange)]
+    #[case("1e-400", DoubleFit::OutOfRange)]
+    #[case("-Infinity", DoubleFit::OutOfRange)]
+    #[case("NaN", DoubleFit::OutOfRange)]
+    fn doubles(#[case] number: &str, #[case] expected: DoubleFit) {
+        assert_eq!(fit_in_double(number), expected);
+    }
+
+    #[rstest::rstest]
+    #[case("+1", "1")]
+    #[case("-.5", "-0.5")]
+    #[case(".5e3", "0.5e3")]
+    #[case("5.", "5")]
+    #[case("5.E3", "5E3")]
+    #[case("0x1F", "31")]
+    #[case("-0XfF", "-255")]
+    #[case(
+        "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        "87112285931760246646623899502532662132735"
+    )]
+    #[case("+Infinity", "Infinity")]
+    #[case("-Infinity", "-Infinity")]
+    #[case("-NaN", "NaN")]
+    #[case("1.5e-3", "1.5e-3")]
+    fn json5_numbers_become_json_numbers(#[case] input: &str, #[case] expected: &str) {
+        let token = TokenStream::with_dialect(input, Dialect::Json5)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(token.token, Token::Number(expected.into()));
+        assert_eq!(token.range, 0..input.len());
+    }
+
+    #[rstest::rstest]
+    #[case(".")]
+    #[case("+")]
+    #[case("0x")]
+    #[case("01")]
+    #[case("1.e")]
+    fn invalid_json5_numbers(#[case] input: &str) {
+        assert!(
+            TokenStream::with_dialect(input, Dialect::Json5)
+                .next()
+                .unwrap()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! lexing input read a piece at a time, so inputs larger than memory can be
+//! validated and uglified
+//!
+//! Only a window of the input is kept. Tokens are lexed from the window with
+//! the same lexers as [`TokenStream`], and the window grows when a token might
+//! continue past its end. Text before the previous token is discarded once
+//! enough has built up, keeping the start of its line when that is short so
+//! diagnostics can show whole lines
+
+use crate::{
+    Error, ErrorKind, Result,
+    error::Position,
+    tokens::{Dialect, TokenStream, TokenWithContext},
+    traverse::Tokens,
+};
+use core::ops::Range;
+use std::io::{ErrorKind as IoErrorKind, Read};
+
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// how far past an error the window has to reach before it is reported. Some
+/// errors depend on the text after them, such as a literal cut in half
+const ERROR_LOOKAHEAD: usize = 64;
+
+/// stands in for ranges in text that was already discarded
+const DISCARDED: Range<usize> = usize::MAX..usize::MAX;
+
+/// tokens of input read from `R`, in bounded memory apart from tokens longer
+/// than the window such as very large strings
+///
+/// Tokens own their text, and ranges and errors refer to the whole input
+#[derive(Debug)]
+pub struct ReadTokens<R> {
+    reader: R,
+    dialect: Dialect,
+    allow_lone_surrogates: bool,
+    /// decoded input that hasn't been discarded yet
+    window: String,
+    /// where `window` starts in the input
+    start: Position,
+    /// where the next token is lexed from in `window`
+    pos: usize,
+    /// where the last token returned starts in `window`
+    last_token_start: usize,
+    /// bytes read after the last complete utf8 character
+    undecoded: Vec<u8>,
+    eof: bool,
+    chunk_size: usize,
+    cached: Option<TokenWithContext<'static>>,
+}
+
+impl<R: Read> ReadTokens<R> {
+    pub fn new(reader: R, dialect: Dialect) -> Self {
+        Self::with_chunk_size(reader, dialect, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// reads at least `chunk_size` bytes at a time, keeping roughly twice that
+    /// in memory
+    pub fn with_chunk_size(reader: R, dialect: Dialect, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            dialect,
+            allow_lone_surrogates: false,
+            window: String::new(),
+            start: Position::START,
+            pos: 0,
+            last_token_start: 0,
+            undecoded: Vec::new(),
+            eof: false,
+            chunk_size: chunk_size.max(1),
+            cached: None,
+        }
+    }
+
+    /// see [`TokenStream::allow_lone_surrogates`]
+    pub fn allow_lone_surrogates(mut self, allow: bool) -> Self {
+        self.allow_lone_surrogates = allow;
+        self
+    }
+
+    fn lex(&mut self) -> Result<'static, Option<TokenWithContext<'static>>> {
+        self.discard();
+        loop {
+            let mut stream = TokenStream::with_dialect_from(&self.window, self.dialect, self.pos)
+                .allow_lone_surrogates(self.allow_lone_surrogates);
+            let lexed = match stream.next() {
+                Some(Ok(TokenWithContext { token, range })) => Some(Ok(TokenWithContext {
+                    token: token.into_owned(),
+                    range,
+                })),
+                Some(Err(error)) => Some(Err(error.into_owned())),
+                None => None,
+            };
+            // a token reaching the end of the window could continue past it
+            let complete = self.eof
+                || match &lexed {
+                    Some(Ok(token)) => token.range.end < self.window.len(),
+                    Some(Err(error)) => error.range().end + ERROR_LOOKAHEAD <= self.window.len(),
+                    None => false,
+                };
+            if !complete {
+                self.fill()?;
+                continue;
+            }
+
+            return match lexed {
+                Some(Ok(TokenWithContext { token, range })) => {
+                    self.pos = range.end;
+                    self.last_token_start = range.start;
+                    Ok(Some(TokenWithContext {
+                        token,
+                        range: self.absolute(range),
+                    }))
+                }
+                Some(Err(error)) => Err(self.moved(error)),
+                None => {
+                    self.pos = self.window.len();
+                    Ok(None)
+                }
+            };
+        }
+    }
+
+    /// reads more of the input into the window, at least doubling it so long
+    /// tokens aren't lexed over and over
+    fn fill(&mut self) -> Result<'static, ()> {
+        let mut buf = std::mem::take(&mut self.undecoded);
+        let decoded = buf.len();
+        buf.resize(decoded + self.chunk_size.max(self.window.len()), 0);
+        let read = loop {
+            match self.reader.read(&mut buf[decoded..]) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == IoErrorKind::Interrupted => {}
+                Err(error) => {
+                    return Err(self.unterminated(ErrorKind::ReadFailed(error.to_string())));
+                }
+            }
+        };
+        buf.truncate(decoded + read);
+        self.eof = read == 0;
+
+        match std::str::from_utf8(&buf) {
+            Ok(text) => self.window.push_str(text),
+            Err(error) => {
+                let (valid, rest) = buf.split_at(error.valid_up_to());
+                self.window
+                    .push_str(std::str::from_utf8(valid).expect("checked up to here"));
+                // an incomplete character at the end of the chunk is finished
+                // by the next read
+                if error.error_len().is_some() || self.eof {
+                    return Err(self.unterminated(ErrorKind::InvalidEncoding));
+                }
+                self.undecoded = rest.to_vec();
+            }
+        }
+        Ok(())
+    }
+
+    /// drops text before the previous token once enough has built up
+    fn discard(&mut self) {
+        let keep = self.last_token_start;
+        let line_start = self.window[..keep].rfind('\n').map_or(0, |i| i + 1);
+        let cut = if keep - line_start <= self.chunk_size {
+            line_start
+        } else {
+            keep
+        };
+        if cut < self.chunk_size {
+            return;
+        }
+
+        self.start = self.start.after(&self.window[..cut]);
+        self.window.drain(..cut);
+        self.pos -= cut;
+        self.last_token_start -= cut;
+    }
+
+    /// moves an error created against the window to where it starts. Once
+    /// text has been discarded, the window is only part of the document
+    fn moved(&self, error: Error<'static>) -> Error<'static> {
+        let error = error.starting_at(self.start);
+        if self.start.offset == 0 {
+            error
+        } else {
+            error.partial()
+        }
+    }
+
+    fn absolute(&self, range: Range<usize>) -> Range<usize> {
+        self.start.offset + range.start..self.start.offset + range.end
+    }
+
+    fn relative(&self, range: Range<usize>) -> Range<usize> {
+        match range.start.checked_sub(self.start.offset) {
+            Some(start) => start..range.end - self.start.offset,
+            None => DISCARDED,
+        }
+    }
+}
+
+impl<R: Read> Tokens<'static> for ReadTokens<R> {
+    fn peek_token(&mut self) -> Result<'static, Option<&TokenWithContext<'static>>> {
+        if self.cached.is_none() {
+            self.cached = self.lex()?;
+        }
+        Ok(self.cached.as_ref())
+    }
+
+    fn next_token(&mut self) -> Result<'static, Option<TokenWithContext<'static>>> {
+        match self.cached.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.lex(),
+        }
+    }
+
+    fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    fn error(&self, kind: ErrorKind<'static>, range: Range<usize>) -> Error<'static> {
+        let kind = kind.map_ranges(|range| self.relative(range));
+        self.moved(Error::new(kind, self.relative(range), &self.window))
+    }
+
+    fn unterminated(&self, kind: ErrorKind<'static>) -> Error<'static> {
+        let kind = kind.map_ranges(|range| self.relative(range));
+        self.moved(Error::from_unterminated(kind, &self.window))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        check::NoopVisitor,
+        error::diagnostics::{Diagnostic, Source},
+        traverse::parse_tokens,
+    };
+
+    fn tokens(input: &str, chunk_size: usize) -> Vec<TokenWithContext<'static>> {
+        let mut tokens = ReadTokens::with_chunk_size(input.as_bytes(), Dialect::Json, chunk_size);
+        std::iter::from_fn(|| tokens.next_token().unwrap()).collect()
+    }
+
+    fn validate(input: &str, chunk_size: usize) -> Result<'static, Range<usize>> {
+        let mut tokens = ReadTokens::with_chunk_size(input.as_bytes(), Dialect::Json, chunk_size);
+        parse_tokens(&mut tokens, true, &mut NoopVisitor)
+    }
+
+    #[rstest::rstest]
+    fn matches_in_memory_lexing(#[values(1, 2, 3, 7, 64)] chunk_size: usize) {
+        let input = r#"{"key": ["a\u00e9b", 12.5e-3, true, null, false], "ünï": -0}"#;
+        let expected: Vec<_> = TokenStream::new(input)
+            .map(|token| {
+                let TokenWithContext { token, range } = token.unwrap();
+                TokenWithContext {
+                    token: token.into_owned(),
+                    range,
+                }
+            })
+            .collect();
+        assert_eq!(tokens(input, chunk_size), expected);
+    }
+
+    #[rstest::rstest]
+    fn errors_match_in_memory_errors(
+        #[values("[1, 2,", "{\"a\" 1}", "[1, tru]", "[\"\\x\"]", "1 2")] input: &str,
+        #[values(1, 4, 64)] chunk_size: usize,
+    ) {
+        let expected = crate::validate_str(input).unwrap_err();
+        let error = validate(input, chunk_size).unwrap_err();
+        assert_eq!(error.to_string(), expected.to_string());
+        assert_eq!(error.range(), expected.range());
+    }
+
+    #[rstest::rstest]
+    #[case::multiline("\n", 102, 4)]
+    #[case::single_line(" ", 1, 1306)]
+    fn errors_point_into_the_whole_input(
+        #[case] separator: &str,
+        #[case] line: usize,
+        #[case] column: usize,
+    ) {
+        let value = format!("[\"padding\"],{separator}").repeat(100);
+        let input = format!("[{separator}{value}[1 2]]");
+        let error = validate(&input, 16).unwrap_err();
+
+        let expected = crate::validate_str(&input).unwrap_err();
+        assert_eq!(error.to_string(), expected.to_string());
+        assert_eq!(error.range(), expected.range());
+        assert_eq!((error.line(), error.column()), (line, column));
+        let diagnostic = Diagnostic::from(&error);
+        let Source::Stdin(window) = diagnostic.source else {
+            unreachable!("errors are named stdin by default")
+        };
+        assert!(window.len() < input.len() / 4, "only a window is kept");
+        // a window starting partway through a line is shown from the column
+        // it starts at
+        let start = diagnostic.range.unwrap().start;
+        let shown = match window[..start].rfind('\n') {
+            Some(i) => window[i + 1..start].chars().count() + 1,
+            None => diagnostic.column_start + window[..start].chars().count(),
+        };
+        assert_eq!(shown, column);
+    }
+
+    #[test]
+    fn splits_multibyte_characters_across_reads() {
+        let input = "[\"ünïcödé 🦀\"]";
+        assert_eq!(validate(input, 1), Ok(0..input.len()));
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let mut tokens = ReadTokens::with_chunk_size(&b"[\"\xff\"]"[..], Dialect::Json, 2);
+        let error = parse_tokens(&mut tokens, true, &mut NoopVisitor).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidEncoding);
+    }
+}
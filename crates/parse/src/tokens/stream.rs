@@ -1,21 +1,26 @@
-use core::{iter::Peekable, str::CharIndices};
+use core::{iter::Peekable, ops::Range, str::CharIndices};
 
 use crate::tokens::{
-    CharWithContext, Token, TokenWithContext, lexical::JsonChar, number::parse_num,
-    string::parse_string,
+    CharWithContext, Dialect, Token, TokenWithContext, javascript, json5, keyword,
+    lexical::JsonChar,
+    number::{parse_json5_num, parse_num},
+    string::{parse_json5_string, parse_string},
 };
 use crate::tokens::{FALSE, NULL, TRUE};
-use crate::{Error, ErrorKind, Result};
+use crate::{Error, ErrorKind, Result, error::Position, events::DuplicateKeys, traverse::Tokens};
 
 #[derive(Debug, Clone)]
 struct CharsWithContext<'a> {
     iter: CharIndices<'a>,
+    /// byte offset of `iter` in the input
+    offset: usize,
 }
 
 impl<'a> CharsWithContext<'a> {
-    fn new(s: &'a str) -> Self {
+    fn new(s: &'a str, offset: usize) -> Self {
         Self {
-            iter: s.char_indices(),
+            iter: s[offset..].char_indices(),
+            offset,
         }
     }
 }
@@ -24,7 +29,7 @@ impl<'a> Iterator for CharsWithContext<'a> {
     type Item = CharWithContext;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(Into::into)
+        self.iter.next().map(|(i, c)| (self.offset + i, c).into())
     }
 }
 
@@ -32,30 +37,158 @@ impl<'a> Iterator for CharsWithContext<'a> {
 struct TokenStreamInner<'a> {
     chars: Peekable<CharsWithContext<'a>>,
     input: &'a str,
+    dialect: Dialect,
+    comments: Vec<Range<usize>>,
+    allow_lone_surrogates: bool,
 }
 
 impl<'a> TokenStreamInner<'a> {
-    fn new(s: &'a str) -> Self {
+    fn new(s: &'a str, dialect: Dialect, start: usize) -> Self {
         Self {
-            chars: CharsWithContext::new(s).peekable(),
+            chars: CharsWithContext::new(s, start).peekable(),
             input: s,
+            dialect,
+            comments: Vec::new(),
+            allow_lone_surrogates: false,
         }
     }
 
     fn consume_whitespace(&mut self) {
+        let json5 = self.dialect.is_json5();
         while self
             .chars
-            .next_if(|CharWithContext(_, x)| x.is_whitespace())
+            .next_if(|CharWithContext(_, x)| {
+                x.is_whitespace() || (json5 && json5::is_whitespace(x.0))
+            })
             .is_some()
         {}
     }
+
+    /// skips whitespace and comments, erroring on comments if the dialect
+    /// does not allow them
+    fn consume_trivia(&mut self) -> Result<'a, ()> {
+        loop {
+            self.consume_whitespace();
+            let Some(CharWithContext(r, _)) = self.chars.peek() else {
+                return Ok(());
+            };
+            let start = r.start;
+            let rest = &self.input[start..];
+
+            let terminated = if rest.starts_with("//") {
+                self.consume_line_comment();
+                true
+            } else if rest.starts_with("/*") {
+                self.consume_block_comment()
+            } else {
+                return Ok(());
+            };
+            let end = self.position();
+
+            if !self.dialect.allows_comments() {
+                return Err(Error::new(
+                    ErrorKind::CommentNotAllowed,
+                    start..end,
+                    self.input,
+                ));
+            }
+            if !terminated {
+                return Err(Error::new(
+                    ErrorKind::UnterminatedBlockComment,
+                    start..start + "/*".len(),
+                    self.input,
+                ));
+            }
+            self.comments.push(start..end);
+        }
+    }
+
+    /// consumes up to but not including the line break
+    fn consume_line_comment(&mut self) {
+        while self
+            .chars
+            .next_if(|CharWithContext(_, JsonChar(c))| !matches!(c, '\n' | '\r'))
+            .is_some()
+        {}
+    }
+
+    /// returns whether the closing `*/` was found
+    fn consume_block_comment(&mut self) -> bool {
+        // skip the opening `/*` so `/*/` is not treated as closed
+        self.chars.nth(1);
+        let mut last = None;
+        for CharWithContext(_, JsonChar(c)) in self.chars.by_ref() {
+            if last == Some('*') && c == '/' {
+                return true;
+            }
+            last = Some(c);
+        }
+        false
+    }
+
+    fn next_json5(&mut self, start: usize, c: char) -> Result<'a, TokenWithContext<'a>> {
+        if let Some(error) = javascript::syntax_error(self.input, start) {
+            return Err(error);
+        }
+        match c {
+            '"' | '\'' => {
+                parse_json5_string(self.input, &mut self.chars, self.allow_lone_surrogates)
+            }
+            '0'..='9' | '-' | '+' | '.' => parse_json5_num(self.input, &mut self.chars),
+            c if json5::is_identifier_start(c) => {
+                let identifier = json5::identifier(&self.input[start..]);
+                let end = start + identifier.len();
+                while self
+                    .chars
+                    .next_if(|CharWithContext(r, _)| r.start < end)
+                    .is_some()
+                {}
+                let token = json5::identifier_token(identifier);
+                // other identifiers can only be keys, so one that isn't
+                // followed by a colon or comment may be a misspelled keyword
+                let after = self.input[end..]
+                    .trim_start_matches(|c| JsonChar(c).is_whitespace() || json5::is_whitespace(c));
+                if matches!(token, Token::Identifier(_))
+                    && !after.starts_with([':', '/'])
+                    && let Some(error) = keyword::misspelled_keyword(self.input, start)
+                {
+                    return Err(error);
+                }
+                Ok(TokenWithContext {
+                    token,
+                    range: start..end,
+                })
+            }
+            _ => Err(Error::new(
+                ErrorKind::UnexpectedCharacter(c.into()),
+                start..start + c.len_utf8(),
+                self.input,
+            )),
+        }
+    }
+
+    /// an error for `c` at `range`, spanning the whole word if it looks like
+    /// a misspelled keyword
+    fn unexpected_character(&self, c: char, range: Range<usize>) -> Error<'a> {
+        keyword::misspelled_keyword(self.input, range.start).unwrap_or_else(|| {
+            Error::new(ErrorKind::UnexpectedCharacter(c.into()), range, self.input)
+        })
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.input.len(), |CharWithContext(r, _)| r.start)
+    }
 }
 
 impl<'a> Iterator for TokenStreamInner<'a> {
     type Item = Result<'a, TokenWithContext<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.consume_whitespace();
+        if let Err(e) = self.consume_trivia() {
+            return Some(Err(e));
+        }
         let ctx = self.chars.peek()?;
 
         let CharWithContext(r, JsonChar(c)) = ctx.clone();
@@ -63,8 +196,17 @@ impl<'a> Iterator for TokenStreamInner<'a> {
             self.chars.next();
             return Some(Ok(tok));
         }
+        if self.dialect.is_json5() {
+            return Some(self.next_json5(r.start, c));
+        }
+        if c != '"'
+            && let Some(err) = json5::strict_error(self.input, r.start)
+                .or_else(|| javascript::syntax_error(self.input, r.start))
+        {
+            return Some(Err(err));
+        }
         let token = match c {
-            '"' => parse_string(self.input, &mut self.chars),
+            '"' => parse_string(self.input, &mut self.chars, self.allow_lone_surrogates),
             '0'..='9' | '-' => parse_num(self.input, &mut self.chars),
             'n' | 't' | 'f' => {
                 let expected = match c {
@@ -73,13 +215,19 @@ impl<'a> Iterator for TokenStreamInner<'a> {
                     'f' => FALSE,
                     _ => unreachable!("{c} is not able to be reached"),
                 };
-                let actual = self
+                let matches = self
                     .chars
                     .by_ref()
                     .take(expected.len())
-                    .map(|c| c.as_char());
+                    .map(|c| c.as_char())
+                    .eq(expected.chars());
+                // a keyword followed by more of a word, like `nulll`, is
+                // reported as the whole word
+                let word = json5::identifier(&self.input[r.start..]);
 
-                if actual.eq(expected.chars()) {
+                if matches && word.len() > expected.len() {
+                    Err(self.unexpected_character(c, r.start..r.start + word.len()))
+                } else if matches {
                     let token = match c {
                         'n' => Token::Null,
                         't' => true.into(),
@@ -96,18 +244,10 @@ impl<'a> Iterator for TokenStreamInner<'a> {
                         range: r.start..end,
                     })
                 } else {
-                    Err(Error::new(
-                        ErrorKind::UnexpectedCharacter(c.into()),
-                        r.clone(),
-                        self.input,
-                    ))
+                    Err(self.unexpected_character(c, r))
                 }
             }
-            _ => Err(Error::new(
-                ErrorKind::UnexpectedCharacter(c.into()),
-                r.clone(),
-                self.input,
-            )),
+            _ => Err(self.unexpected_character(c, r)),
         };
 
         Some(token)
@@ -117,20 +257,86 @@ impl<'a> Iterator for TokenStreamInner<'a> {
 #[derive(Debug, Clone)]
 pub struct TokenStream<'a> {
     inner: TokenStreamInner<'a>,
+    /// where the text being lexed starts in the input, which ranges are
+    /// offset by and errors are moved to
+    start: Position,
     cached: Option<TokenWithContext<'a>>,
+    duplicate_keys: DuplicateKeys,
+    warnings: Vec<Error<'a>>,
 }
 
 impl<'a> TokenStream<'a> {
     pub fn new(s: &'a str) -> Self {
+        Self::with_dialect(s, Dialect::Json)
+    }
+
+    pub fn with_dialect(s: &'a str, dialect: Dialect) -> Self {
+        Self::with_dialect_from(s, dialect, 0)
+    }
+
+    /// starts lexing at byte `start` of `s`. Ranges and errors still refer to
+    /// all of `s`, so line numbers match the input
+    pub fn with_dialect_from(s: &'a str, dialect: Dialect, start: usize) -> Self {
         Self {
-            inner: TokenStreamInner::new(s),
+            inner: TokenStreamInner::new(s, dialect, start),
+            start: Position::START,
             cached: None,
+            duplicate_keys: DuplicateKeys::default(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// lexes `document`, which is at `start` in a larger input. Ranges and
+    /// line numbers refer to the whole input, but errors only keep the text
+    /// of the document, so checking many documents doesn't copy the input
+    /// over and over. [`Positions`](crate::error::Positions) finds where
+    /// each document starts
+    pub fn with_dialect_at(document: &'a str, dialect: Dialect, start: Position) -> Self {
+        Self {
+            start,
+            ..Self::with_dialect(document, dialect)
         }
     }
 
+    /// whether to accept `\u` escapes of unpaired surrogates, which RFC 8259
+    /// allows but which can't be decoded to utf8. They are rejected by default
+    pub fn allow_lone_surrogates(mut self, allow: bool) -> Self {
+        self.inner.allow_lone_surrogates = allow;
+        self
+    }
+
+    /// how values traversed from this stream treat keys used more than once
+    /// in an object
+    pub fn with_duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.inner.dialect
+    }
+
+    pub fn duplicate_keys(&self) -> DuplicateKeys {
+        self.duplicate_keys
+    }
+
+    /// problems found while traversing that didn't stop it, such as duplicate
+    /// keys under [`DuplicateKeys::Warn`]
+    pub fn warnings(&self) -> &[Error<'a>] {
+        &self.warnings
+    }
+
+    /// ranges of the comments skipped so far, including the delimiters
+    pub fn comments(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.inner
+            .comments
+            .iter()
+            .map(|range| self.absolute(range.clone()))
+    }
+
     pub fn peek_token(&mut self) -> Result<'a, Option<&TokenWithContext<'a>>> {
         if self.cached.is_none() {
-            match self.inner.next() {
+            match self.next() {
                 Some(Ok(token)) => self.cached = Some(token),
                 Some(Err(err)) => return Err(err),
                 None => return Ok(None),
@@ -147,12 +353,75 @@ impl<'a> TokenStream<'a> {
             self.next().transpose()
         }
     }
+
+    fn absolute(&self, range: Range<usize>) -> Range<usize> {
+        self.start.offset + range.start..self.start.offset + range.end
+    }
+
+    fn relative(&self, range: Range<usize>) -> Range<usize> {
+        range.start - self.start.offset..range.end - self.start.offset
+    }
+
+    /// moves an error created against the lexed text to where that text is
+    /// in the input
+    fn moved(&self, error: Error<'a>) -> Error<'a> {
+        if self.start == Position::START {
+            return error;
+        }
+        error.starting_at(self.start)
+    }
+}
+
+impl<'a> Tokens<'a> for TokenStream<'a> {
+    fn peek_token(&mut self) -> Result<'a, Option<&TokenWithContext<'a>>> {
+        TokenStream::peek_token(self)
+    }
+
+    fn next_token(&mut self) -> Result<'a, Option<TokenWithContext<'a>>> {
+        TokenStream::next_token(self)
+    }
+
+    fn dialect(&self) -> Dialect {
+        TokenStream::dialect(self)
+    }
+
+    fn duplicate_keys(&self) -> DuplicateKeys {
+        TokenStream::duplicate_keys(self)
+    }
+
+    fn warn(&mut self, warning: Error<'a>) {
+        self.warnings.push(warning);
+    }
+
+    fn error(&self, kind: ErrorKind<'a>, range: Range<usize>) -> Error<'a> {
+        if self.start == Position::START {
+            return Error::new(kind, range, self.inner.input);
+        }
+        let kind = kind.map_ranges(|range| self.relative(range));
+        self.moved(Error::new(kind, self.relative(range), self.inner.input))
+    }
+
+    fn unterminated(&self, kind: ErrorKind<'a>) -> Error<'a> {
+        if self.start == Position::START {
+            return Error::from_unterminated(kind, self.inner.input);
+        }
+        let kind = kind.map_ranges(|range| self.relative(range));
+        self.moved(Error::from_unterminated(kind, self.inner.input))
+    }
 }
 
 impl<'a> Iterator for TokenStream<'a> {
     type Item = Result<'a, TokenWithContext<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
+        let token = self.inner.next()?;
+        Some(
+            token
+                .map(|TokenWithContext { token, range }| TokenWithContext {
+                    token,
+                    range: self.absolute(range),
+                })
+                .map_err(|error| self.moved(error)),
+        )
     }
 }
@@ -3,11 +3,10 @@ use crate::{
     tokens::{CharWithContext, JsonChar, Token, TokenWithContext},
 };
 use core::ops::Range;
-use std::iter::Peekable;
+use std::{borrow::Cow, iter::Peekable};
 
 enum StringState<'a> {
     Open,
-    // TODO track last escaped u escapes
     CharOrEscapeOrEnd {
         string_range: Range<usize>,
         quote_range: Range<usize>,
@@ -23,15 +22,98 @@ enum StringState<'a> {
         u_range: Range<usize>,
         slash_range: Range<usize>,
         digits_seen: usize,
+        code: u32,
     },
     End(TokenWithContext<'a>),
 }
 
+/// the half of a surrogate pair escaped by the previous `\u` escape
+#[derive(Debug, Clone)]
+enum PendingSurrogate {
+    High(Range<usize>),
+    Low(Range<usize>),
+}
+
+/// checks that `\u` escapes of surrogates come in high then low pairs, since
+/// anything else can't be decoded to utf8
+#[derive(Debug, Default)]
+struct Surrogates {
+    allow_lone: bool,
+    pending: Option<PendingSurrogate>,
+}
+
+impl Surrogates {
+    fn new(allow_lone: bool) -> Self {
+        Self {
+            allow_lone,
+            pending: None,
+        }
+    }
+
+    /// a `\u` escape of `code` spanning `range`
+    fn unicode_escape<'a>(
+        &mut self,
+        code: u32,
+        range: Range<usize>,
+        input: &'a str,
+    ) -> Result<'a, ()> {
+        if self.allow_lone {
+            return Ok(());
+        }
+        let is_high = (0xD800..0xDC00).contains(&code);
+        let is_low = (0xDC00..0xE000).contains(&code);
+        match self.pending.take() {
+            Some(PendingSurrogate::High(_)) if is_low => Ok(()),
+            Some(PendingSurrogate::High(first)) => Err(Error::new(
+                ErrorKind::MismatchedSurrogates {
+                    first,
+                    first_high: true,
+                },
+                range,
+                input,
+            )),
+            Some(PendingSurrogate::Low(first)) if is_high => Err(Error::new(
+                ErrorKind::MismatchedSurrogates {
+                    first,
+                    first_high: false,
+                },
+                range,
+                input,
+            )),
+            Some(PendingSurrogate::Low(low)) => {
+                Err(Error::new(ErrorKind::LoneLowSurrogate, low, input))
+            }
+            None => {
+                if is_high {
+                    self.pending = Some(PendingSurrogate::High(range));
+                } else if is_low {
+                    self.pending = Some(PendingSurrogate::Low(range));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// anything other than a `\u` escape, including the end of the string
+    fn other<'a>(&mut self, input: &'a str) -> Result<'a, ()> {
+        match self.pending.take() {
+            None => Ok(()),
+            Some(PendingSurrogate::High(high)) => {
+                Err(Error::new(ErrorKind::LoneHighSurrogate, high, input))
+            }
+            Some(PendingSurrogate::Low(low)) => {
+                Err(Error::new(ErrorKind::LoneLowSurrogate, low, input))
+            }
+        }
+    }
+}
+
 impl<'a> StringState<'a> {
     fn process(
         self,
         chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
         input: &'a str,
+        surrogates: &mut Surrogates,
     ) -> Result<'a, Self> {
         let res = match self {
             StringState::Open => {
@@ -53,10 +135,13 @@ impl<'a> StringState<'a> {
                     quote_range,
                     slash_range: r.clone(),
                 },
-                Some(CharWithContext(r, JsonChar('"'))) => StringState::End(TokenWithContext {
-                    token: Token::String(input[quote_range.end..r.start].into()),
-                    range: string_range.start..r.end,
-                }),
+                Some(CharWithContext(r, JsonChar('"'))) => {
+                    surrogates.other(input)?;
+                    StringState::End(TokenWithContext {
+                        token: Token::String(input[quote_range.end..r.start].into()),
+                        range: string_range.start..r.end,
+                    })
+                }
                 Some(CharWithContext(r, c)) if c.is_control() => {
                     return Err(Error::new(
                         ErrorKind::UnexpectedControlCharacterInString(c),
@@ -64,10 +149,13 @@ impl<'a> StringState<'a> {
                         input,
                     ));
                 }
-                Some(CharWithContext(r, _)) => StringState::CharOrEscapeOrEnd {
-                    string_range: string_range.start..r.end,
-                    quote_range,
-                },
+                Some(CharWithContext(r, _)) => {
+                    surrogates.other(input)?;
+                    StringState::CharOrEscapeOrEnd {
+                        string_range: string_range.start..r.end,
+                        quote_range,
+                    }
+                }
                 None => {
                     return Err(Error::from_unterminated(
                         ErrorKind::ExpectedQuote {
@@ -84,6 +172,7 @@ impl<'a> StringState<'a> {
                 slash_range,
             } => match chars.next() {
                 Some(CharWithContext(r, c)) if c.can_be_escaped_directly() => {
+                    surrogates.other(input)?;
                     StringState::CharOrEscapeOrEnd {
                         string_range: string_range.start..r.end,
                         quote_range,
@@ -95,6 +184,7 @@ impl<'a> StringState<'a> {
                     u_range: r,
                     slash_range,
                     digits_seen: 0,
+                    code: 0,
                 },
                 maybe_c => {
                     return Err(Error::from_maybe_json_char_with_context(
@@ -115,11 +205,14 @@ impl<'a> StringState<'a> {
                 u_range,
                 slash_range,
                 digits_seen,
+                code,
             } => match chars.next() {
                 Some(CharWithContext(r, c)) if c.is_hexdigit() => {
                     let string_range = string_range.start..r.end;
                     let next_digits = digits_seen + 1;
+                    let code = code * 16 + c.0.to_digit(16).expect("checked hex digit");
                     if next_digits == 4 {
+                        surrogates.unicode_escape(code, slash_range.start..r.end, input)?;
                         StringState::CharOrEscapeOrEnd {
                             string_range,
                             quote_range,
@@ -131,6 +224,7 @@ impl<'a> StringState<'a> {
                             u_range,
                             slash_range,
                             digits_seen: next_digits,
+                            code,
                         }
                     }
                 }
@@ -142,6 +236,7 @@ impl<'a> StringState<'a> {
                             u_range: u_range.clone(),
                             maybe_c: c,
                             digit_idx: digits_seen + 1,
+                            digit_count: 4,
                         },
                         maybe_c,
                         input,
@@ -155,16 +250,379 @@ impl<'a> StringState<'a> {
     }
 }
 
+/// lexes a json string. Unless `allow_lone_surrogates`, `\u` escapes of
+/// surrogates have to come in pairs
 pub fn parse_string<'a>(
     input: &'a str,
     chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+    allow_lone_surrogates: bool,
 ) -> Result<'a, TokenWithContext<'a>> {
     let mut state = StringState::Open;
+    let mut surrogates = Surrogates::new(allow_lone_surrogates);
 
     loop {
-        state = state.process(chars, input)?;
+        state = state.process(chars, input, &mut surrogates)?;
         if let StringState::End(tok) = state {
             break Ok(tok);
         }
     }
 }
+
+/// json5 strings can also be single quoted, continue across lines with a
+/// trailing `\\`, contain unescaped control characters other than line breaks
+/// and use `\\v`, `\\0`, `\\xXX` or an escaped character that stands for
+/// itself. The contents are converted to the equivalent json escapes
+///
+/// See [the JSON5 spec](https://spec.json5.org/#strings)
+pub fn parse_json5_string<'a>(
+    input: &'a str,
+    chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+    allow_lone_surrogates: bool,
+) -> Result<'a, TokenWithContext<'a>> {
+    let Some(CharWithContext(quote_range, JsonChar(quote @ ('"' | '\'')))) = chars.next() else {
+        unreachable!("must start with a quote");
+    };
+    let mut contents = String::new();
+    let mut surrogates = Surrogates::new(allow_lone_surrogates);
+
+    loop {
+        let Some(CharWithContext(r, JsonChar(c))) = chars.next() else {
+            return Err(Error::from_unterminated(
+                ErrorKind::ExpectedQuote {
+                    open_range: quote_range.clone(),
+                    string_range: quote_range.start..input.len(),
+                },
+                input,
+            ));
+        };
+        match c {
+            c if c == quote => {
+                surrogates.other(input)?;
+                let raw = &input[quote_range.end..r.start];
+                let contents = if contents == raw {
+                    Cow::Borrowed(raw)
+                } else {
+                    Cow::Owned(contents)
+                };
+                return Ok(TokenWithContext {
+                    token: Token::String(contents),
+                    range: quote_range.start..r.end,
+                });
+            }
+            '\n' | '\r' => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedControlCharacterInString(c.into()),
+                    r,
+                    input,
+                ));
+            }
+            '\\' => push_json5_escape(
+                &mut contents,
+                input,
+                chars,
+                &quote_range,
+                r,
+                &mut surrogates,
+            )?,
+            c => {
+                surrogates.other(input)?;
+                push_json_char(&mut contents, c);
+            }
+        }
+    }
+}
+
+fn push_json5_escape<'a>(
+    contents: &mut String,
+    input: &'a str,
+    chars: &mut Peekable<impl Iterator<Item = CharWithContext>>,
+    quote_range: &Range<usize>,
+    slash_range: Range<usize>,
+    surrogates: &mut Surrogates,
+) -> Result<'a, ()> {
+    let escape_error = |maybe_c: Option<CharWithContext>| {
+        Error::from_maybe_json_char_with_context(
+            |c| ErrorKind::ExpectedEscape {
+                maybe_c: c,
+                slash_range: slash_range.clone(),
+                string_range: quote_range.start..slash_range.end,
+                quote_range: quote_range.clone(),
+            },
+            maybe_c,
+            input,
+        )
+    };
+
+    let escaped = chars.next();
+    let Some(CharWithContext(escape_range, JsonChar(c))) = escaped.clone() else {
+        return Err(escape_error(escaped));
+    };
+    if c != 'u' {
+        surrogates.other(input)?;
+    }
+    match c {
+        '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => {
+            contents.push('\\');
+            contents.push(c);
+        }
+        'v' => push_json_char(contents, '\u{000B}'),
+        '0' if !chars
+            .peek()
+            .is_some_and(|CharWithContext(_, JsonChar(c))| c.is_ascii_digit()) =>
+        {
+            push_json_char(contents, '\0');
+        }
+        '1'..='9' | '0' => return Err(escape_error(escaped)),
+        'x' | 'u' => {
+            let digit_count = if c == 'x' { 2 } else { 4 };
+            let mut digits = String::with_capacity(digit_count);
+            let mut end = escape_range.end;
+            for digit_idx in 1..=digit_count {
+                match chars.next() {
+                    Some(CharWithContext(r, d)) if d.is_hexdigit() => {
+                        digits.push(d.0);
+                        end = r.end;
+                    }
+                    maybe_c => {
+                        return Err(Error::from_maybe_json_char_with_context(
+                            |c| ErrorKind::ExpectedHexDigit {
+                                quote_range: quote_range.clone(),
+                                slash_range: slash_range.clone(),
+                                u_range: escape_range.clone(),
+                                maybe_c: c,
+                                digit_idx,
+                                digit_count,
+                            },
+                            maybe_c,
+                            input,
+                        ));
+                    }
+                }
+            }
+            if c == 'u' {
+                let code = u32::from_str_radix(&digits, 16).expect("validated hex digits");
+                surrogates.unicode_escape(code, slash_range.start..end, input)?;
+                // kept as is so surrogate pairs stay together
+                contents.push_str("\\u");
+                contents.push_str(&digits);
+            } else {
+                let code = u32::from_str_radix(&digits, 16).expect("validated hex digits");
+                push_json_char(contents, char::from_u32(code).expect("below 0x100"));
+            }
+        }
+        // line continuations
+        '\n' | '\u{2028}' | '\u{2029}' => {}
+        '\r' => {
+            chars.next_if(|CharWithContext(_, JsonChar(c))| *c == '\n');
+        }
+        c => push_json_char(contents, c),
+    }
+    Ok(())
+}
+
+/// pushes a character to json string contents, escaping it if needed
+pub(crate) fn push_json_char(contents: &mut String, c: char) {
+    let c = JsonChar(c);
+    if c.is_control() || matches!(c.0, '"' | '\\') {
+        contents.push_str(&c.escape());
+    } else {
+        contents.push(c.0);
+    }
+}
+
+/// decodes the escapes in the contents of a string token, borrowing when
+/// there are none. Unpaired surrogates can't be held by a `str`, so they
+/// become U+FFFD
+///
+/// ```
+/// # use jjpwrgem_parse::tokens::unescape;
+/// assert_eq!(unescape(r"café 🦀\n"), "café 🦀\n");
+/// assert_eq!(unescape(r"\ud83e"), "\u{FFFD}");
+/// ```
+pub fn unescape(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+    Cow::Owned(DecodedChars::new(raw).map(|(_, c)| c).collect())
+}
+
+/// the characters the contents of a string token stand for, each with the
+/// range of the contents it was written as
+#[derive(Debug, Clone)]
+pub struct DecodedChars<'a> {
+    raw: &'a str,
+    pos: usize,
+}
+
+impl<'a> DecodedChars<'a> {
+    pub fn new(raw: &'a str) -> Self {
+        Self { raw, pos: 0 }
+    }
+}
+
+impl Iterator for DecodedChars<'_> {
+    type Item = (Range<usize>, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.raw[self.pos..];
+        let mut chars = rest.chars();
+        let c = match chars.next()? {
+            '\\' => match chars.next() {
+                Some('b') => '\u{0008}',
+                Some('f') => '\u{000C}',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('u') => {
+                    let (c, after) = unescape_unicode(chars.as_str());
+                    chars = after.chars();
+                    c
+                }
+                // `"`, `\\` and `/` stand for themselves
+                Some(c) => c,
+                None => '\\',
+            },
+            c => c,
+        };
+        let start = self.pos;
+        self.pos += rest.len() - chars.as_str().len();
+        Some((start..self.pos, c))
+    }
+}
+
+/// decodes what follows a `\u`, joining surrogate pairs
+fn unescape_unicode(after_u: &str) -> (char, &str) {
+    let hex = |s: &str| s.get(..4).and_then(|d| u32::from_str_radix(d, 16).ok());
+    let Some(code) = hex(after_u) else {
+        return (char::REPLACEMENT_CHARACTER, after_u);
+    };
+    let rest = &after_u[4..];
+    if (0xD800..0xDC00).contains(&code)
+        && let Some(low_digits) = rest.strip_prefix("\\u")
+        && let Some(low @ 0xDC00..0xE000) = hex(low_digits)
+    {
+        let c = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        return (
+            char::from_u32(c).expect("surrogate pairs are valid scalar values"),
+            &low_digits[4..],
+        );
+    }
+    (
+        char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER),
+        rest,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokens::{Dialect, TokenStream};
+
+    #[rstest::rstest]
+    #[case(r"plain", "plain")]
+    #[case(r#"\"\\\/\b\f\n\r\t"#, "\"\\/\u{8}\u{c}\n\r\t")]
+    #[case(r"caf\u00e9", "café")]
+    #[case(r"caf\u00E9!", "café!")]
+    #[case(r"\ud83e\udd80", "🦀")]
+    #[case(r"🦀", "🦀")]
+    #[case(r"\ud83e", "\u{FFFD}")]
+    #[case(r"\udd80\ud83e", "\u{FFFD}\u{FFFD}")]
+    #[case(r"\ud83e\n", "\u{FFFD}\n")]
+    #[case(r"\ud83eA", "\u{FFFD}A")]
+    fn unescapes(#[case] raw: &str, #[case] expected: &str) {
+        assert_eq!(unescape(raw), expected);
+    }
+
+    #[test]
+    fn decoded_chars_know_where_they_were_written() {
+        let raw = r"a\n\ud83e\udd80é";
+        assert_eq!(
+            DecodedChars::new(raw)
+                .map(|(range, c)| (&raw[range], c))
+                .collect::<Vec<_>>(),
+            [
+                ("a", 'a'),
+                (r"\n", '\n'),
+                (r"\ud83e\udd80", '🦀'),
+                ("é", 'é')
+            ]
+        );
+    }
+
+    #[test]
+    fn borrows_without_escapes() {
+        assert!(matches!(unescape("café"), Cow::Borrowed("café")));
+    }
+
+    fn lex(json: &str, dialect: Dialect, allow_lone_surrogates: bool) -> Result<'_, Token<'_>> {
+        let mut tokens =
+            TokenStream::with_dialect(json, dialect).allow_lone_surrogates(allow_lone_surrogates);
+        tokens.next().expect("a token").map(|ctx| ctx.token)
+    }
+
+    #[rstest::rstest]
+    #[case(r#""\ud83e""#, ErrorKind::LoneHighSurrogate, 1..7)]
+    #[case(r#""\ud83e a""#, ErrorKind::LoneHighSurrogate, 1..7)]
+    #[case(r#""\ud83e\n""#, ErrorKind::LoneHighSurrogate, 1..7)]
+    #[case(r#""a\udd80""#, ErrorKind::LoneLowSurrogate, 2..8)]
+    #[case(r#""\udd80\u0041""#, ErrorKind::LoneLowSurrogate, 1..7)]
+    #[case(
+        r#""\ud83e\ud83e""#,
+        ErrorKind::MismatchedSurrogates { first: 1..7, first_high: true },
+        7..13
+    )]
+    #[case(
+        r#""\udd80\ud83e""#,
+        ErrorKind::MismatchedSurrogates { first: 1..7, first_high: false },
+        7..13
+    )]
+    fn rejects_unpaired_surrogates(
+        #[case] json: &str,
+        #[case] kind: ErrorKind,
+        #[case] range: Range<usize>,
+        #[values(Dialect::Json, Dialect::Json5)] dialect: Dialect,
+    ) {
+        let error = lex(json, dialect, false).unwrap_err();
+        assert_eq!((error.kind(), error.range()), (&kind, range));
+    }
+
+    #[rstest::rstest]
+    #[case(r#""\ud83e\udd80""#)]
+    #[case(r#""\uD83E\uDD80\u00e9""#)]
+    #[case(r#""\ufffd""#)]
+    fn accepts_surrogate_pairs(
+        #[values(Dialect::Json, Dialect::Json5)] dialect: Dialect,
+        #[case] json: &str,
+    ) {
+        assert!(lex(json, dialect, false).is_ok());
+    }
+
+    #[rstest::rstest]
+    #[case(r#""\ud83e""#)]
+    #[case(r#""\udd80\ud83e""#)]
+    fn lone_surrogates_can_be_allowed(#[case] json: &str) {
+        assert_eq!(
+            lex(json, Dialect::Json, true),
+            Ok(Token::String(json.trim_matches('"').into()))
+        );
+    }
+
+    #[rstest::rstest]
+    #[case(Dialect::Json, r#""café\n""#)]
+    #[case(
+        Dialect::Json5,
+        r"'caf\xe9\
+\n'"
+    )]
+    fn decodes_tokens(#[case] dialect: Dialect, #[case] json: &str) {
+        let mut tokens = TokenStream::with_dialect(json, dialect);
+        let Some(Ok(TokenWithContext {
+            token: Token::String(raw),
+            ..
+        })) = tokens.next()
+        else {
+            panic!("expected a string");
+        };
+        assert_eq!(unescape(&raw), "café\n");
+    }
+}
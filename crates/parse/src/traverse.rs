@@ -3,147 +3,369 @@ mod object;
 
 use crate::{
     Error, ErrorKind, Result,
-    ast::{ObjectEntries, Value},
-    tokens::{Token, TokenStream, TokenWithContext},
-    traverse::{array::parse_array, object::parse_object},
+    events::{DuplicateKeys, Event, EventWithRange, Events, Scalar},
+    tokens::{Dialect, TokenOption, TokenStream, TokenWithContext},
 };
-use core::ops::Range;
+pub use array::ArrayState;
+use core::{convert::Infallible, ops::ControlFlow, ops::Range};
+pub use object::ObjectState;
 use std::borrow::Cow;
 
+/// callbacks for each part of a value as it is traversed, in input order
+///
+/// Ranges are byte ranges of the input. Opening callbacks get the range of the
+/// delimiter, closing callbacks the range of the whole container. Returning
+/// [`ControlFlow::Break`] stops traversal, leaving the rest of the input
+/// unchecked. Every callback does nothing by default
+///
+/// ```
+/// # use jjpwrgem_parse::{Visitor, visit_str};
+/// # use std::{borrow::Cow, ops::{ControlFlow, Range}};
+/// /// finds where the first `version` key is
+/// struct FindVersion;
+///
+/// impl<'a> Visitor<'a> for FindVersion {
+///     type Break = Range<usize>;
+///
+///     fn on_object_key(
+///         &mut self,
+///         key: Cow<'a, str>,
+///         range: Range<usize>,
+///     ) -> ControlFlow<Self::Break> {
+///         if key == "version" {
+///             return ControlFlow::Break(range);
+///         }
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// let found = visit_str(r#"{"name": "jjp", "version": "1.0", oops"#, &mut FindVersion);
+/// assert_eq!(found, Ok(ControlFlow::Break(16..25)));
+/// ```
 pub trait Visitor<'a> {
-    fn on_object_open(&mut self);
-    fn on_object_key(&mut self, key: &'a str);
-    fn on_object_key_val_delim(&mut self);
-    fn on_object_close(&mut self);
-    fn on_array_open(&mut self);
-    fn on_array_close(&mut self);
-    fn on_null(&mut self);
-    fn on_string(&mut self, value: &'a str);
-    fn on_number(&mut self, value: Cow<'a, str>);
-    fn on_boolean(&mut self, value: bool);
-    fn on_item_delim(&mut self);
+    /// what a visitor stops with, [`Infallible`] for visitors that never stop
+    type Break;
+
+    fn on_object_open(&mut self, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = range;
+        ControlFlow::Continue(())
+    }
+    fn on_object_key(
+        &mut self,
+        key: Cow<'a, str>,
+        range: Range<usize>,
+    ) -> ControlFlow<Self::Break> {
+        let _ = (key, range);
+        ControlFlow::Continue(())
+    }
+    /// between a key and its value
+    fn on_object_key_val_delim(&mut self) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+    fn on_object_close(&mut self, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = range;
+        ControlFlow::Continue(())
+    }
+    fn on_array_open(&mut self, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = range;
+        ControlFlow::Continue(())
+    }
+    fn on_array_close(&mut self, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = range;
+        ControlFlow::Continue(())
+    }
+    fn on_null(&mut self, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = range;
+        ControlFlow::Continue(())
+    }
+    /// gets the escaped contents of the string
+    fn on_string(&mut self, value: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = (value, range);
+        ControlFlow::Continue(())
+    }
+    fn on_number(&mut self, value: Cow<'a, str>, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = (value, range);
+        ControlFlow::Continue(())
+    }
+    fn on_boolean(&mut self, value: bool, range: Range<usize>) -> ControlFlow<Self::Break> {
+        let _ = (value, range);
+        ControlFlow::Continue(())
+    }
+    /// between array items or object entries, never after the last one
+    fn on_item_delim(&mut self) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// visits the single value in `json`, returning what the visitor stopped with
+/// or the range of the value
+pub fn visit_str<'a, V: Visitor<'a>>(
+    json: &'a str,
+    visitor: &mut V,
+) -> Result<'a, ControlFlow<V::Break, Range<usize>>> {
+    visit_str_with_dialect(json, Dialect::Json, visitor)
+}
+
+pub fn visit_str_with_dialect<'a, V: Visitor<'a>>(
+    json: &'a str,
+    dialect: Dialect,
+    visitor: &mut V,
+) -> Result<'a, ControlFlow<V::Break, Range<usize>>> {
+    visit_tokens(&mut TokenStream::with_dialect(json, dialect), true, visitor)
+}
+
+/// where the traverser gets tokens from, and how errors are reported against
+/// the text they came from
+pub trait Tokens<'a> {
+    fn peek_token(&mut self) -> Result<'a, Option<&TokenWithContext<'a>>>;
+    fn next_token(&mut self) -> Result<'a, Option<TokenWithContext<'a>>>;
+    fn dialect(&self) -> Dialect;
+    fn duplicate_keys(&self) -> DuplicateKeys {
+        DuplicateKeys::KeepAll
+    }
+    /// keeps a problem that doesn't stop traversal, dropping it by default
+    fn warn(&mut self, warning: Error<'a>) {
+        let _ = warning;
+    }
+    /// an error at `range`
+    fn error(&self, kind: ErrorKind<'a>, range: Range<usize>) -> Error<'a>;
+    /// an error for input that ended too early
+    fn unterminated(&self, kind: ErrorKind<'a>) -> Error<'a>;
+
+    /// an error at `maybe_token`, or for input that ended too early
+    fn error_at<F>(&self, f: F, maybe_token: Option<TokenWithContext<'a>>) -> Error<'a>
+    where
+        F: Fn(TokenOption<'a>) -> ErrorKind<'a>,
+    {
+        match maybe_token {
+            Some(TokenWithContext { token, range }) => self.error(f(Some(token).into()), range),
+            None => self.unterminated(f(None.into())),
+        }
+    }
+}
+
+impl<'a, T: Tokens<'a>> Tokens<'a> for &mut T {
+    fn peek_token(&mut self) -> Result<'a, Option<&TokenWithContext<'a>>> {
+        (**self).peek_token()
+    }
+    fn next_token(&mut self) -> Result<'a, Option<TokenWithContext<'a>>> {
+        (**self).next_token()
+    }
+    fn dialect(&self) -> Dialect {
+        (**self).dialect()
+    }
+    fn duplicate_keys(&self) -> DuplicateKeys {
+        (**self).duplicate_keys()
+    }
+    fn warn(&mut self, warning: Error<'a>) {
+        (**self).warn(warning)
+    }
+    fn error(&self, kind: ErrorKind<'a>, range: Range<usize>) -> Error<'a> {
+        (**self).error(kind, range)
+    }
+    fn unterminated(&self, kind: ErrorKind<'a>) -> Error<'a> {
+        (**self).unterminated(kind)
+    }
+}
+
+/// what an array or object state machine did with the next token
+#[derive(Debug)]
+pub enum Step<'a, S> {
+    /// moved on to another state, maybe finding something on the way
+    Next(S, Option<EventWithRange<'a>>),
+    /// a value has to be traversed before continuing with `value_end`
+    Value(S),
+    /// the container is closed
+    End(EventWithRange<'a>),
+}
+
+impl<'a, S> Step<'a, S> {
+    /// closes the container opened by `open_ctx`
+    fn end(event: Event<'a>, open_ctx: TokenWithContext<'a>, closed_range: Range<usize>) -> Self {
+        Step::End(EventWithRange {
+            event,
+            range: open_ctx.range.start..closed_range.end,
+        })
+    }
+
+    pub fn map<T>(self, f: impl FnOnce(S) -> T) -> Step<'a, T> {
+        match self {
+            Step::Next(state, event) => Step::Next(f(state), event),
+            Step::Value(state) => Step::Value(f(state)),
+            Step::End(event) => Step::End(event),
+        }
+    }
 }
 
+/// visits the next value in `tokens`, returning its range
 pub fn parse_tokens<'a>(
-    tokens: &mut TokenStream<'a>,
-    text: &'a str,
+    tokens: &mut impl Tokens<'a>,
     fail_on_multiple_value: bool,
-    visitor: &mut impl Visitor<'a>,
+    visitor: &mut impl Visitor<'a, Break = Infallible>,
 ) -> Result<'a, Range<usize>> {
-    let peeked = tokens.peek_token()?.cloned();
-    let Some(peeked) = peeked else {
-        return Err(Error::from_maybe_token_with_context(
-            |tok| ErrorKind::ExpectedValue(None, tok),
-            None,
-            text,
-        ));
-    };
-    let range = match peeked.token {
-        Token::OpenCurlyBrace => parse_object(tokens, text, visitor)?,
-        Token::OpenSquareBracket => parse_array(tokens, text, visitor)?,
-        t if t.is_scalar() => {
-            let token_ctx = tokens
-                .next_token()?
-                .expect("peek guaranteed a value for scalar token");
-
-            match t {
-                Token::String(s) => visitor.on_string(s),
-                Token::Number(cow) => visitor.on_number(cow),
-                Token::Null => visitor.on_null(),
-                Token::Boolean(b) => visitor.on_boolean(b),
-                _ => unreachable!("guard prevents non scalars"),
-            };
-
-            token_ctx.range.clone()
-        }
-        invalid => {
-            return Err(Error::new(
-                ErrorKind::ExpectedValue(None, Some(invalid.clone()).into()),
-                peeked.range.clone(),
-                text,
-            ));
+    match visit_tokens(tokens, fail_on_multiple_value, visitor)? {
+        ControlFlow::Continue(range) => Ok(range),
+        ControlFlow::Break(never) => match never {},
+    }
+}
+
+/// visits the next value in `tokens`, stopping if the visitor does
+pub fn visit_tokens<'a, V: Visitor<'a>>(
+    tokens: &mut impl Tokens<'a>,
+    fail_on_multiple_value: bool,
+    visitor: &mut V,
+) -> Result<'a, ControlFlow<V::Break, Range<usize>>> {
+    let mut events = Events::new(tokens, fail_on_multiple_value);
+    let mut containers = Vec::new();
+    for event in events.by_ref() {
+        if let ControlFlow::Break(b) = visit_event(visitor, &mut containers, event?) {
+            return Ok(ControlFlow::Break(b));
         }
-    };
+    }
+
+    Ok(ControlFlow::Continue(events.range().expect(
+        "events only finish without an error after a whole value",
+    )))
+}
+
+/// the container a visited value is in, to know when delimiters go between
+enum Container {
+    Array { has_items: bool },
+    Object { has_entries: bool },
+}
 
-    if fail_on_multiple_value
-        && let Some(TokenWithContext { token, range }) = tokens.peek_token()?
+fn visit_event<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    containers: &mut Vec<Container>,
+    EventWithRange { event, range }: EventWithRange<'a>,
+) -> ControlFlow<V::Break> {
+    // delimiters are only visited once they are known not to trail
+    if let Some(Container::Array { has_items }) = containers.last_mut()
+        && !matches!(event, Event::EndArray)
     {
-        return Err(Error::new(
-            ErrorKind::TokenAfterEnd(token.clone()),
-            range.clone(),
-            text,
-        ));
+        if *has_items {
+            visitor.on_item_delim()?;
+        }
+        *has_items = true;
     }
 
-    Ok(range)
+    match event {
+        Event::StartObject => {
+            containers.push(Container::Object { has_entries: false });
+            visitor.on_object_open(range)
+        }
+        Event::Key(key) => {
+            if let Some(Container::Object { has_entries }) = containers.last_mut() {
+                if *has_entries {
+                    visitor.on_item_delim()?;
+                }
+                *has_entries = true;
+            }
+            visitor.on_object_key(key, range)?;
+            visitor.on_object_key_val_delim()
+        }
+        Event::EndObject => {
+            containers.pop();
+            visitor.on_object_close(range)
+        }
+        Event::StartArray => {
+            containers.push(Container::Array { has_items: false });
+            visitor.on_array_open(range)
+        }
+        Event::EndArray => {
+            containers.pop();
+            visitor.on_array_close(range)
+        }
+        Event::Scalar(Scalar::Null) => visitor.on_null(range),
+        Event::Scalar(Scalar::Boolean(b)) => visitor.on_boolean(b, range),
+        Event::Scalar(Scalar::Number(n)) => visitor.on_number(n, range),
+        Event::Scalar(Scalar::String(s)) => visitor.on_string(s, range),
+    }
 }
 
+/// errors unless the next token starts a value
 pub fn validate_start_of_value<'a>(
-    text: &'a str,
+    tokens: &mut impl Tokens<'a>,
     expect_ctx: TokenWithContext<'a>,
-    maybe_token: Option<TokenWithContext<'a>>,
 ) -> Result<'a, ()> {
+    let maybe_token = tokens.peek_token()?.cloned();
     if !maybe_token
         .as_ref()
         .is_some_and(|ctx| ctx.token.is_start_of_value())
     {
-        Err(Error::from_maybe_token_with_context(
+        Err(tokens.error_at(
             |tok| ErrorKind::ExpectedValue(Some(expect_ctx.clone()), tok),
             maybe_token,
-            text,
         ))
     } else {
         Ok(())
     }
 }
 
-fn join<V, T>(
-    visitor: &mut V,
-    items: impl IntoIterator<Item = T>,
-    mut item_fmt: impl FnMut(&mut V, &T),
-    mut delim_fmt: impl FnMut(&mut V, &T),
-) {
-    let mut iter = items.into_iter();
-    if let Some(first) = iter.next() {
-        item_fmt(visitor, &first);
-        for item in iter {
-            delim_fmt(visitor, &item);
-            item_fmt(visitor, &item);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// records what each callback saw
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        type Break = ();
+
+        fn on_object_key(&mut self, key: Cow<'a, str>, range: Range<usize>) -> ControlFlow<()> {
+            self.0.push(format!("key {key} {range:?}"));
+            ControlFlow::Continue(())
         }
-    }
-}
 
-pub fn parse_value<'a>(val: &'a Value, visitor: &mut impl Visitor<'a>) {
-    match val {
-        Value::Null => visitor.on_null(),
-        Value::String(s) => visitor.on_string(s),
-        Value::Number(n) => visitor.on_number(n.clone()),
-        Value::Boolean(b) => visitor.on_boolean(*b),
-        Value::Object(ObjectEntries(items)) => {
-            visitor.on_object_open();
-            join(
-                visitor,
-                items,
-                |visitor, (k, v)| {
-                    visitor.on_string(k);
-                    visitor.on_object_key_val_delim();
-                    parse_value(v, visitor);
-                },
-                |visitor, _| visitor.on_item_delim(),
-            );
-            visitor.on_object_close();
+        fn on_number(&mut self, value: Cow<'a, str>, range: Range<usize>) -> ControlFlow<()> {
+            self.0.push(format!("number {value} {range:?}"));
+            if value == "2" {
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
         }
-        Value::Array(items) => {
-            visitor.on_array_open();
-            join(
-                visitor,
-                items,
-                |visitor, val| {
-                    parse_value(val, visitor);
-                },
-                |visitor, _| visitor.on_item_delim(),
-            );
-            visitor.on_array_close();
+
+        fn on_array_close(&mut self, range: Range<usize>) -> ControlFlow<()> {
+            self.0.push(format!("array {range:?}"));
+            ControlFlow::Continue(())
         }
-    };
+
+        fn on_item_delim(&mut self) -> ControlFlow<()> {
+            self.0.push(",".into());
+            ControlFlow::Continue(())
+        }
+    }
+
+    #[test]
+    fn callbacks_get_ranges() {
+        let mut recorder = Recorder::default();
+        let result = visit_str(r#"{"a": [1], "b": 3}"#, &mut recorder);
+        assert_eq!(result, Ok(ControlFlow::Continue(0..18)));
+        assert_eq!(
+            recorder.0,
+            [
+                "key a 1..4",
+                "number 1 7..8",
+                "array 6..9",
+                ",",
+                "key b 11..14",
+                "number 3 16..17"
+            ]
+        );
+    }
+
+    #[test]
+    fn break_stops_before_later_errors() {
+        let mut recorder = Recorder::default();
+        let result = visit_str("[1, 2, oops", &mut recorder);
+        assert_eq!(result, Ok(ControlFlow::Break(())));
+        assert_eq!(recorder.0, ["number 1 1..2", ",", "number 2 4..5"]);
+    }
+
+    #[test]
+    fn errors_before_a_break_are_reported() {
+        let result = visit_str("[1 2]", &mut Recorder::default());
+        assert!(result.is_err());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::{
-    error::{Error, ErrorKind, Result},
-    tokens::{Token, TokenStream, TokenWithContext},
-    traverse::{Visitor, parse_tokens, validate_start_of_value},
+    error::{ErrorKind, Result},
+    events::{Event, EventWithRange},
+    tokens::{Token, TokenWithContext},
+    traverse::{Step, Tokens, validate_start_of_value},
 };
 use std::ops::Range;
 
@@ -15,20 +16,18 @@ pub enum ArrayState<'a> {
         open_ctx: TokenWithContext<'a>,
         expect_ctx: TokenWithContext<'a>,
     },
+    /// waiting for an item to be traversed
+    InValue {
+        open_ctx: TokenWithContext<'a>,
+    },
     CommaOrEnd {
         open_ctx: TokenWithContext<'a>,
         last_value_range: Range<usize>,
     },
-    End(Range<usize>),
 }
 
 impl<'a> ArrayState<'a> {
-    pub fn process(
-        self,
-        tokens: &mut TokenStream<'a>,
-        text: &'a str,
-        visitor: &mut impl Visitor<'a>,
-    ) -> Result<'a, Self> {
+    pub fn process(self, tokens: &mut impl Tokens<'a>) -> Result<'a, Step<'a, Self>> {
         let next_state = match self {
             ArrayState::Open => match tokens.next_token()? {
                 Some(
@@ -37,18 +36,20 @@ impl<'a> ArrayState<'a> {
                         ..
                     },
                 ) => {
-                    visitor.on_array_open();
-                    ArrayState::ValueOrEnd { open_ctx }
+                    let event = EventWithRange {
+                        event: Event::StartArray,
+                        range: open_ctx.range.clone(),
+                    };
+                    return Ok(Step::Next(ArrayState::ValueOrEnd { open_ctx }, Some(event)));
                 }
                 maybe_token => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok| ErrorKind::ExpectedOpenBrace {
                             expected: '['.into(),
                             context: None,
                             found: tok,
                         },
                         maybe_token,
-                        text,
                     ));
                 }
             },
@@ -60,31 +61,20 @@ impl<'a> ArrayState<'a> {
                     ..
                 }) => {
                     tokens.next_token()?;
-                    visitor.on_array_close();
-                    ArrayState::End(open_ctx.range.start..closed_range.end)
+                    return Ok(Step::end(Event::EndArray, open_ctx, closed_range));
                 }
                 Some(token_ctx) if token_ctx.token.is_start_of_value() => ArrayState::Value {
                     open_ctx: open_ctx.clone(),
                     expect_ctx: open_ctx.clone(),
                 },
-                Some(_) => {
-                    return Err(Error::from_maybe_token_with_context(
-                        |tok| {
-                            ErrorKind::expected_entry_or_closed_delimiter(open_ctx.clone(), tok)
-                                .expect("array should open with a square bracket")
-                        },
-                        tokens.next_token()?,
-                        text,
-                    ));
-                }
-                None => {
-                    return Err(Error::from_maybe_token_with_context(
+                _ => {
+                    let found = tokens.next_token()?;
+                    return Err(tokens.error_at(
                         |tok| {
                             ErrorKind::expected_entry_or_closed_delimiter(open_ctx.clone(), tok)
                                 .expect("array should open with a square bracket")
                         },
-                        None,
-                        text,
+                        found,
                     ));
                 }
             },
@@ -93,13 +83,19 @@ impl<'a> ArrayState<'a> {
                 open_ctx,
                 expect_ctx,
             } => {
-                validate_start_of_value(text, expect_ctx, tokens.peek_token()?.cloned())?;
-
-                let value_range = parse_tokens(tokens, text, false, visitor)?;
-                ArrayState::CommaOrEnd {
-                    open_ctx,
-                    last_value_range: value_range,
+                if expect_ctx.token == Token::Comma
+                    && tokens.dialect().allows_trailing_commas()
+                    && let Some(TokenWithContext {
+                        token: Token::ClosedSquareBracket,
+                        range: closed_range,
+                    }) = tokens.peek_token()?.cloned()
+                {
+                    tokens.next_token()?;
+                    return Ok(Step::end(Event::EndArray, open_ctx, closed_range));
                 }
+
+                validate_start_of_value(tokens, expect_ctx)?;
+                return Ok(Step::Value(ArrayState::InValue { open_ctx }));
             }
 
             ArrayState::CommaOrEnd { open_ctx, .. } => match tokens.peek_token()?.cloned() {
@@ -108,9 +104,7 @@ impl<'a> ArrayState<'a> {
                     range: closed_range,
                 }) => {
                     tokens.next_token()?;
-
-                    visitor.on_array_close();
-                    ArrayState::End(open_ctx.range.start..closed_range.end)
+                    return Ok(Step::end(Event::EndArray, open_ctx, closed_range));
                 }
                 Some(
                     comma_ctx @ TokenWithContext {
@@ -119,44 +113,37 @@ impl<'a> ArrayState<'a> {
                     },
                 ) => {
                     tokens.next_token()?;
-                    visitor.on_item_delim();
                     ArrayState::Value {
                         open_ctx,
                         expect_ctx: comma_ctx,
                     }
                 }
                 _ => {
-                    return Err(Error::from_maybe_token_with_context(
+                    let found = tokens.next_token()?;
+                    return Err(tokens.error_at(
                         |tok| {
                             ErrorKind::expected_entry_or_closed_delimiter(open_ctx.clone(), tok)
                                 .expect("array should open with a square bracket")
                         },
-                        tokens.next_token()?,
-                        text,
+                        found,
                     ));
                 }
             },
 
-            ArrayState::End(_) => {
-                return Ok(self);
-            }
+            ArrayState::InValue { .. } => unreachable!("items are traversed by the caller"),
         };
 
-        Ok(next_state)
+        Ok(Step::Next(next_state, None))
     }
-}
-
-pub fn parse_array<'a>(
-    tokens: &mut TokenStream<'a>,
-    text: &'a str,
-    visitor: &mut impl Visitor<'a>,
-) -> Result<'a, Range<usize>> {
-    let mut state = ArrayState::Open;
 
-    loop {
-        state = state.process(tokens, text, visitor)?;
-        if let ArrayState::End(result) = state {
-            break Ok(result);
+    /// continues after an item spanning `range`
+    pub fn value_end(self, range: Range<usize>) -> Self {
+        match self {
+            ArrayState::InValue { open_ctx } => ArrayState::CommaOrEnd {
+                open_ctx,
+                last_value_range: range,
+            },
+            state => unreachable!("no item was being traversed in {state:?}"),
         }
     }
 }
@@ -1,12 +1,22 @@
 use crate::{
-    Error, ErrorKind, Result,
-    tokens::{Token, TokenOption, TokenStream, TokenWithContext},
-    traverse::{Visitor, parse_tokens, validate_start_of_value},
+    ErrorKind, Result,
+    events::{Event, EventWithRange},
+    tokens::{Token, TokenOption, TokenWithContext},
+    traverse::{Step, Tokens, validate_start_of_value},
 };
 use core::ops::Range;
+use std::borrow::Cow;
+
+/// the key an entry starts with. Only json5 produces unquoted keys
+fn object_key<'a>(token: &Token<'a>) -> Option<Cow<'a, str>> {
+    match token {
+        Token::String(key) | Token::Identifier(key) => Some(key.clone()),
+        _ => None,
+    }
+}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-enum ObjectState<'a> {
+pub enum ObjectState<'a> {
     Open,
     KeyOrEnd {
         open_ctx: TokenWithContext<'a>,
@@ -24,16 +34,15 @@ enum ObjectState<'a> {
         colon_ctx: TokenWithContext<'a>,
         open_ctx: TokenWithContext<'a>,
     },
-    End(Range<usize>),
+    /// waiting for an entry's value to be traversed
+    InValue {
+        colon_ctx: TokenWithContext<'a>,
+        open_ctx: TokenWithContext<'a>,
+    },
 }
 
 impl<'a> ObjectState<'a> {
-    fn process(
-        self,
-        tokens: &mut TokenStream<'a>,
-        text: &'a str,
-        visitor: &mut impl Visitor<'a>,
-    ) -> Result<'a, Self> {
+    pub fn process(self, tokens: &mut impl Tokens<'a>) -> Result<'a, Step<'a, Self>> {
         let res = match self {
             ObjectState::Open => match tokens.next_token()? {
                 Some(
@@ -42,21 +51,24 @@ impl<'a> ObjectState<'a> {
                         ..
                     },
                 ) => {
-                    visitor.on_object_open();
-                    ObjectState::KeyOrEnd {
+                    let event = EventWithRange {
+                        event: Event::StartObject,
+                        range: ctx.range.clone(),
+                    };
+                    let state = ObjectState::KeyOrEnd {
                         open_ctx: ctx,
                         last_pair: None,
-                    }
+                    };
+                    return Ok(Step::Next(state, Some(event)));
                 }
                 maybe_token => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok| ErrorKind::ExpectedOpenBrace {
                             expected: '{'.into(),
                             context: None,
                             found: tok,
                         },
                         maybe_token,
-                        text,
                     ));
                 }
             },
@@ -71,10 +83,7 @@ impl<'a> ObjectState<'a> {
                         token: Token::ClosedCurlyBrace,
                         range: closed_range,
                     }),
-                ) => {
-                    visitor.on_object_close();
-                    ObjectState::End(open_ctx.range.start..closed_range.end)
-                }
+                ) => return Ok(Step::end(Event::EndObject, open_ctx, closed_range)),
                 (
                     Some(_),
                     Some(
@@ -83,44 +92,30 @@ impl<'a> ObjectState<'a> {
                             ..
                         },
                     ),
-                ) => {
-                    visitor.on_item_delim();
-                    ObjectState::Key {
-                        comma_ctx,
-                        open_ctx,
-                    }
-                }
-                (
-                    None,
-                    Some(
-                        key_ctx @ TokenWithContext {
-                            token: Token::String(key),
-                            ..
-                        },
-                    ),
-                ) => {
-                    visitor.on_object_key(key);
-                    ObjectState::Colon { key_ctx, open_ctx }
+                ) => ObjectState::Key {
+                    comma_ctx,
+                    open_ctx,
+                },
+                (None, Some(key_ctx)) if let Some(key) = object_key(&key_ctx.token) => {
+                    return Ok(Step::key(key, key_ctx, open_ctx));
                 }
                 (Some(pair_span), maybe_token) => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok| ErrorKind::ExpectedCommaOrClosedCurlyBrace {
                             range: pair_span.clone(),
                             open_ctx: open_ctx.clone(),
                             found: tok,
                         },
                         maybe_token,
-                        text,
                     ));
                 }
                 (None, maybe_token) => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok: TokenOption| {
                             ErrorKind::expected_entry_or_closed_delimiter(open_ctx.clone(), tok)
                                 .expect("object should open with a curly brace")
                         },
                         maybe_token,
-                        text,
                     ));
                 }
             },
@@ -129,20 +124,19 @@ impl<'a> ObjectState<'a> {
                 comma_ctx,
                 open_ctx,
             } => match tokens.next_token()? {
-                Some(
-                    key_ctx @ TokenWithContext {
-                        token: Token::String(key),
-                        ..
-                    },
-                ) => {
-                    visitor.on_object_key(key);
-                    ObjectState::Colon { key_ctx, open_ctx }
+                Some(key_ctx) if let Some(key) = object_key(&key_ctx.token) => {
+                    return Ok(Step::key(key, key_ctx, open_ctx));
+                }
+                Some(TokenWithContext {
+                    token: Token::ClosedCurlyBrace,
+                    range: closed_range,
+                }) if tokens.dialect().allows_trailing_commas() => {
+                    return Ok(Step::end(Event::EndObject, open_ctx, closed_range));
                 }
                 maybe_token => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok: TokenOption| ErrorKind::ExpectedKey(comma_ctx.clone(), tok),
                         maybe_token,
-                        text,
                     ));
                 }
             },
@@ -152,18 +146,14 @@ impl<'a> ObjectState<'a> {
                         token: Token::Colon,
                         ..
                     },
-                ) => {
-                    visitor.on_object_key_val_delim();
-                    ObjectState::Value {
-                        colon_ctx,
-                        open_ctx,
-                    }
-                }
+                ) => ObjectState::Value {
+                    colon_ctx,
+                    open_ctx,
+                },
                 maybe_token => {
-                    return Err(Error::from_maybe_token_with_context(
+                    return Err(tokens.error_at(
                         |tok| ErrorKind::ExpectedColon(key_ctx.clone(), tok),
                         maybe_token,
-                        text,
                     ));
                 }
             },
@@ -172,33 +162,44 @@ impl<'a> ObjectState<'a> {
                 colon_ctx,
                 open_ctx,
             } => {
-                validate_start_of_value(text, colon_ctx.clone(), tokens.peek_token()?.cloned())?;
-
-                let value_range = parse_tokens(tokens, text, false, visitor)?;
-
-                ObjectState::KeyOrEnd {
+                validate_start_of_value(tokens, colon_ctx.clone())?;
+                return Ok(Step::Value(ObjectState::InValue {
+                    colon_ctx,
                     open_ctx,
-                    last_pair: Some(colon_ctx.range.start..value_range.end),
-                }
+                }));
             }
-            ObjectState::End(range) => ObjectState::End(range),
+            ObjectState::InValue { .. } => unreachable!("values are traversed by the caller"),
         };
 
-        Ok(res)
+        Ok(Step::Next(res, None))
     }
-}
-
-pub fn parse_object<'a>(
-    tokens: &mut TokenStream<'a>,
-    text: &'a str,
-    visitor: &mut impl Visitor<'a>,
-) -> Result<'a, Range<usize>> {
-    let mut state = ObjectState::Open;
 
-    loop {
-        state = state.process(tokens, text, visitor)?;
-        if let ObjectState::End(range) = state {
-            break Ok(range);
+    /// continues after an entry's value spanning `range`
+    pub fn value_end(self, range: Range<usize>) -> Self {
+        match self {
+            ObjectState::InValue {
+                colon_ctx,
+                open_ctx,
+            } => ObjectState::KeyOrEnd {
+                open_ctx,
+                last_pair: Some(colon_ctx.range.start..range.end),
+            },
+            state => unreachable!("no value was being traversed in {state:?}"),
         }
     }
 }
+
+impl<'a> Step<'a, ObjectState<'a>> {
+    /// found the key of an entry, so its colon comes next
+    fn key(
+        key: Cow<'a, str>,
+        key_ctx: TokenWithContext<'a>,
+        open_ctx: TokenWithContext<'a>,
+    ) -> Self {
+        let event = EventWithRange {
+            event: Event::Key(key),
+            range: key_ctx.range.clone(),
+        };
+        Step::Next(ObjectState::Colon { key_ctx, open_ctx }, Some(event))
+    }
+}
@@ -1,11 +1,17 @@
 mod diagnostic {
     use annotate_snippets::{Annotation, AnnotationKind, Group, Level, Snippet};
-    use jjpwrgem_parse::error::diagnostics::{Context, Diagnostic, Patch, Source};
+    use core::ops::Range;
+    use jjpwrgem_parse::error::diagnostics::{Context, Diagnostic, Patch, Severity, Source};
+    use std::borrow::Cow;
     fn patch_to_patch<'a>(patch: Patch<'a>) -> annotate_snippets::Patch<'a> {
         annotate_snippets::Patch::new(patch.span, patch.replacement)
     }
 
-    fn source_to_snippet<'a, T: Clone>(val: Source<'a>) -> Snippet<'a, T> {
+    fn source_to_snippet<'a, T: Clone>(
+        val: Source<'a>,
+        line_start: usize,
+        padding: &str,
+    ) -> Snippet<'a, T> {
         let (source, path) = match val {
             Source::Stdin(src) => (src, "stdin"),
             Source::File { source, path } => (
@@ -14,7 +20,16 @@ mod diagnostic {
                     .expect("diagnostic paths should be valid utf8"),
             ),
         };
-        Snippet::source(source).path(path)
+        let source: Cow<'a, str> = if padding.is_empty() {
+            source.into()
+        } else {
+            format!("{padding}{source}").into()
+        };
+        Snippet::source(source).path(path).line_start(line_start)
+    }
+
+    fn shift(span: Range<usize>, by: usize) -> Range<usize> {
+        span.start + by..span.end + by
     }
 
     fn context_to_annotation<'a>(ctx: Context<'a>) -> Annotation<'a> {
@@ -28,28 +43,63 @@ mod diagnostic {
 
     pub fn report_diagnostic<'a>(
         Diagnostic {
+            severity,
+            code,
             message,
             context,
             patches,
             source,
             range,
+            line_start,
+            column_start,
         }: Diagnostic<'a>,
     ) -> Vec<Group<'a>> {
+        // annotate-snippets counts columns from the start of the source, so
+        // when the source starts partway through the line with the error, it
+        // is padded out to where it starts
+        let (Source::Stdin(text) | Source::File { source: text, .. }) = source;
+        let padding = match &range {
+            Some(range)
+                if text
+                    .get(..range.start)
+                    .is_some_and(|before| !before.contains('\n')) =>
+            {
+                " ".repeat(column_start - 1)
+            }
+            _ => String::new(),
+        };
         let annotations = if let Some(range) = range {
-            std::iter::once(AnnotationKind::Primary.span(range))
-                .chain(context.into_iter().map(context_to_annotation))
+            std::iter::once(AnnotationKind::Primary.span(shift(range, padding.len())))
+                .chain(context.into_iter().map(|ctx| {
+                    context_to_annotation(Context {
+                        span: shift(ctx.span.clone(), padding.len()),
+                        ..ctx
+                    })
+                }))
                 .collect()
         } else {
             vec![]
         };
 
-        let error_group = Level::ERROR
-            .primary_title(message)
-            .element(source_to_snippet(source).annotations(annotations));
+        let level = match severity {
+            Severity::Error => Level::ERROR,
+            Severity::Warning => Level::WARNING,
+            Severity::Info => Level::INFO,
+            // annotate-snippets has no hint level, and help titles patches
+            Severity::Hint => Level::NOTE.with_name("hint"),
+        };
+        let title = level.primary_title(message);
+        let title = match code {
+            Some(code) => title.id(code),
+            None => title,
+        };
+        let error_group =
+            title.element(source_to_snippet(source, line_start, &padding).annotations(annotations));
+        // patches are shown without a location, so they don't need padding
         let patch_group = patches.into_iter().map(|patch| {
-            Level::HELP
-                .primary_title(patch.message.clone())
-                .element(source_to_snippet(source).patches(vec![patch_to_patch(patch)]))
+            Level::HELP.primary_title(patch.message.clone()).element(
+                source_to_snippet(source, line_start, "").patches(vec![patch_to_patch(patch)]),
+            )
         });
 
         std::iter::once(error_group).chain(patch_group).collect()
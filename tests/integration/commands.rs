@@ -1,3 +1,16 @@
 mod check;
+mod config;
+mod convert;
+mod editorconfig;
+mod explain;
 mod format;
 mod help;
+mod jobs;
+mod json5;
+mod jsonc;
+mod jsonl;
+mod lint;
+mod sequence;
+mod stream;
+mod walk;
+mod watch;
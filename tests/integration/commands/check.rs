@@ -22,8 +22,17 @@ use rstest::rstest;
 #[case(crate::fixture_tuple!(DOUBLE_QUOTE))]
 #[case(crate::fixture_tuple!(OBJECT_WITH_LINE_BREAK_VALUE))]
 #[case(crate::fixture_tuple!(OBJECT_WITH_ADJACENT_STRINGS))]
+#[case(crate::fixture_tuple!(OBJECT_MISSING_COLON_BEFORE_STRING))]
+#[case(crate::fixture_tuple!(OBJECT_KEY_MISSING_VALUE))]
+#[case(crate::fixture_tuple!(OBJECT_NUMBER_WITH_SPACE))]
 #[case(crate::fixture_tuple!(OBJECT_EMPTY_THEN_OPEN))]
 #[case(crate::fixture_tuple!(UNEXPECTED_CHARACTER))]
+#[case(crate::fixture_tuple!(MISSPELLED_KEYWORD))]
+#[case(crate::fixture_tuple!(CAPITALIZED_KEYWORD))]
+#[case(crate::fixture_tuple!(INCOMPLETE_KEYWORD))]
+#[case(crate::fixture_tuple!(NULL_WITH_EXTRA_LETTER))]
+#[case(crate::fixture_tuple!(TRUE_WITH_EXTRA_LETTER))]
+#[case(crate::fixture_tuple!(FALSE_WITH_EXTRA_LETTER))]
 #[case(crate::fixture_tuple!(UNEXPECTED_ESCAPED_CHARACTER))]
 #[case(crate::fixture_tuple!(LEADING_ZERO_MINUS_SIGN_NONZERO))]
 #[case(crate::fixture_tuple!(LEADING_ZERO_MINUS_SIGN_ZERO))]
@@ -39,8 +48,28 @@ use rstest::rstest;
 #[case(crate::fixture_tuple!(ARRAY_OPEN))]
 #[case(crate::fixture_tuple!(ARRAY_OPEN_WITH_VALUE))]
 #[case(crate::fixture_tuple!(ARRAY_MISSING_VALUE))]
+#[case(crate::fixture_tuple!(ARRAY_MISSING_COMMA))]
+#[case(crate::fixture_tuple!(ARRAY_MISSING_COMMA_BEFORE_STRING))]
+#[case(crate::fixture_tuple!(ARRAY_MISMATCHED_CLOSE))]
 #[case(crate::fixture_tuple!(INVALID_HEX_DIGIT_IN_ESCAPE))]
 #[case(crate::fixture_tuple!(INVALID_ESCAPED_CURLY))]
+#[case(crate::fixture_tuple!(LONE_HIGH_SURROGATE))]
+#[case(crate::fixture_tuple!(LONE_LOW_SURROGATE))]
+#[case(crate::fixture_tuple!(MISMATCHED_SURROGATES))]
+#[case(crate::fixture_tuple!(INVERTED_SURROGATES))]
+#[case(crate::fixture_tuple!(INVERTED_SURROGATES_BEFORE_PAIR))]
+#[case(crate::fixture_tuple!(LINE_COMMENT))]
+#[case(crate::fixture_tuple!(BLOCK_COMMENT))]
+#[case(crate::fixture_tuple!(SINGLE_QUOTED_STRING))]
+#[case(crate::fixture_tuple!(UNQUOTED_KEY))]
+#[case(crate::fixture_tuple!(HEX_NUMBER))]
+#[case(crate::fixture_tuple!(INFINITY))]
+#[case(crate::fixture_tuple!(TRAILING_DECIMAL_POINT))]
+#[case(crate::fixture_tuple!(TEMPLATE_LITERAL))]
+#[case(crate::fixture_tuple!(TEMPLATE_LITERAL_SUBSTITUTION))]
+#[case(crate::fixture_tuple!(UNDEFINED))]
+#[case(crate::fixture_tuple!(OCTAL_NUMBER))]
+#[case(crate::fixture_tuple!(BINARY_NUMBER))]
 fn annotate_test_json_failure_snapshots(#[case] (name, json): (&str, &str)) {
     let json_bytes = json.as_bytes().to_vec();
 
@@ -69,6 +98,79 @@ fn no_stdin() {
     assert_snapshot!(output.snapshot_display());
 }
 
+#[rstest]
+#[case("ijson_valid", r#"{"id": 9007199254740991, "name": "\ud83e\udd80"}"#)]
+#[case(
+    "ijson_violations",
+    r#"{
+  "id": 12345678901234567890,
+  "max": 1e400,
+  "name": "ferris\uFFFF",
+  "id": 1
+}"#
+)]
+#[case("ijson_syntax_error", r#"{"a": 1, "a": 2"#)]
+fn ijson_profile(#[case] name: &str, #[case] json: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--profile", "i-json"]),
+        Some(json.as_bytes().to_vec()),
+    );
+
+    assert_snapshot!(name, output.snapshot_display());
+}
+
+#[test]
+fn ijson_profile_checks_every_document() {
+    let output = exec_cmd(
+        cli().args(["check", "--profile", "i-json", "--jsonl"]),
+        Some(b"{\"a\": 1, \"a\": 2}\n[1]\n[1e999]\n".to_vec()),
+    );
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[rstest::rstest]
+#[case("duplicate_keys_error", "error")]
+#[case("duplicate_keys_warn", "warn")]
+#[case("duplicate_keys_keep_all", "keep-all")]
+fn duplicate_keys(#[case] name: &str, #[case] policy: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--duplicate-keys", policy]),
+        Some(b"{\"name\": \"a\", \"id\": 1, \"n\\u0061me\": \"b\", \"name\": \"c\"}".to_vec()),
+    );
+
+    assert_snapshot!(name, output.snapshot_display());
+}
+
+#[rstest::rstest]
+#[case(&[], true)]
+#[case(&["--deny-warnings"], false)]
+fn duplicate_key_warnings_with_deny_warnings(#[case] flags: &[&str], #[case] succeeds: bool) {
+    let output = exec_cmd(
+        cli()
+            .args(["check", "--duplicate-keys", "warn"])
+            .args(flags),
+        Some(br#"{"a": 1, "a": 2}"#.to_vec()),
+    );
+
+    assert_eq!(
+        output.status.success(),
+        succeeds,
+        "{}",
+        output.snapshot_display()
+    );
+}
+
+#[test]
+fn duplicate_keys_in_every_document() {
+    let output = exec_cmd(
+        cli().args(["check", "--duplicate-keys", "error", "--jsonl"]),
+        Some(b"{\"a\": 1}\n{\"a\": 1, \"a\": 2}\n[{\"b\": 1, \"b\": 2}]\n".to_vec()),
+    );
+
+    assert_snapshot!(output.snapshot_display());
+}
+
 #[rstest::rstest]
 #[case(r#"{"coolKey"}"#, "failure")]
 #[case(r#"{"hello I am valid": null} "#, "success")]
@@ -85,3 +187,18 @@ fn docs(#[case] input: &str, #[case] postfix: &str) {
         assert_snapshot!(format!("check_{postfix}"), output.docs_display_stdin());
     });
 }
+
+#[rstest]
+#[case(&[], r#"{"crab": "\ud83e"}"#)]
+#[case(&["--duplicate-keys", "warn"], r#"{"crab": "\ud83e"}"#)]
+#[case(&["--jsonl"], "[\"\\ud83e\"]\n{\"crab\": \"\\udd80\"}\n")]
+#[case(&["--seq"], "\u{1E}[\"\\ud83e\"]\n\u{1E}[\"\\udd80\"]\n")]
+#[case(&["--concatenated"], r#"["\ud83e"] ["\udd80"]"#)]
+fn lone_surrogates_can_be_allowed(#[case] flags: &[&str], #[case] json: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--allow-lone-surrogates"]).args(flags),
+        Some(json.as_bytes().to_vec()),
+    );
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
@@ -0,0 +1,162 @@
+use crate::common::{TempDir, exec_cmd};
+use insta::assert_snapshot;
+
+const PACKAGE_JSON: &str = r#"{"name":"jjpwrgem","keywords":["parser","formatter"]}"#;
+
+#[test]
+fn toml_config_with_overrides() {
+    let dir = TempDir::new("toml_config_with_overrides");
+    dir.write(
+        "jjp.toml",
+        r#"
+indent_width = 4
+
+[[overrides]]
+files = ["*.lock.json"]
+uglify = true
+
+[[overrides]]
+files = ["package.json"]
+indent_width = 2
+preferred_width = 10
+"#,
+    )
+    .write("data.json", PACKAGE_JSON)
+    .write("deps.lock.json", PACKAGE_JSON)
+    .write("nested/package.json", PACKAGE_JSON);
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            "data.json",
+            "deps.lock.json",
+            "nested/package.json",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn json_config_closest_to_file_wins() {
+    let dir = TempDir::new("json_config_closest_to_file_wins");
+    dir.write(".jjprc.json", r#"{ "uglify": true }"#)
+        .write("nested/.jjprc.json", r#"{ "indent_style": "tab" }"#)
+        .write("top.json", PACKAGE_JSON)
+        .write("nested/inner.json", PACKAGE_JSON);
+
+    let output = exec_cmd(
+        dir.cli().args(["format", "top.json", "nested/inner.json"]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn duplicate_keys_from_config() {
+    let dir = TempDir::new("duplicate_keys_from_config");
+    dir.write("jjp.toml", r#"duplicate_keys = "keep-first""#)
+        .write("data.json", r#"{"a": 1, "b": 2, "a": 3}"#);
+
+    let output = exec_cmd(dir.cli().args(["format", "data.json"]), None);
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lone_surrogates_from_config() {
+    let dir = TempDir::new("lone_surrogates_from_config");
+    dir.write("jjp.toml", "allow_lone_surrogates = true")
+        .write("data.json", r#"["\ud83e"]"#)
+        .write("data.jsonl", "[\"\\ud83e\"]\n[\"\\udd80\"]\n");
+
+    for args in [
+        &["check", "data.json"][..],
+        &["check", "--jsonl", "data.jsonl"],
+        &["format", "data.json"],
+    ] {
+        let output = exec_cmd(dir.cli().args(args), None);
+        assert!(output.status.success(), "{}", output.snapshot_display());
+    }
+}
+
+#[test]
+fn flags_override_config() {
+    let dir = TempDir::new("flags_override_config");
+    dir.write("jjp.toml", "indent_width = 8\nend_of_line = \"crlf\"")
+        .write("data.json", "[[1, 2]]");
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            "data.json",
+            "--indent-width",
+            "1",
+            "--preferred-width",
+            "0",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    // snapshots normalize newlines, so assert directly to keep the line endings
+    assert_eq!(output.stdout, "[\r\n [\r\n  1,\r\n  2\r\n ]\r\n]\r\n");
+}
+
+#[test]
+fn stdin_uses_config_in_current_dir() {
+    let dir = TempDir::new("stdin_uses_config_in_current_dir");
+    dir.write("jjp.toml", "uglify = true");
+
+    let output = exec_cmd(
+        dir.cli().arg("format"),
+        Some(PACKAGE_JSON.replace(',', ", ").into_bytes()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_eq!(output.stdout, format!("{PACKAGE_JSON}\n"));
+}
+
+#[rstest::rstest]
+#[case(
+    ".jjprc.json",
+    "{\n  \"uglify\": true\n  \"indent_width\": 2\n}",
+    "json_syntax"
+)]
+#[case(".jjprc.json", r#"["uglify"]"#, "json_not_object")]
+#[case("jjp.toml", "uglify = ", "toml_syntax")]
+#[case("jjp.toml", "prefered_width = 100", "unknown_setting")]
+#[case("jjp.toml", "end_of_line = \"lfcr\"", "invalid_enum")]
+#[case("jjp.toml", "preferred_width = -1", "negative_width")]
+#[case("jjp.toml", "[[overrides]]\nuglify = true", "override_without_files")]
+#[case("jjp.toml", "[[overrides]]\nfiles = [\"[\"]", "invalid_glob")]
+fn invalid_config(#[case] file: &str, #[case] contents: &str, #[case] label: &str) {
+    let dir = TempDir::new(&format!("invalid_config_{label}"));
+    dir.write(file, contents).write("data.json", "null");
+
+    let output = exec_cmd(dir.cli().args(["format", "data.json"]), None);
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(format!("invalid_config_{label}"), output.snapshot_display());
+}
+
+#[test]
+fn check_reports_file_paths() {
+    let dir = TempDir::new("check_reports_file_paths");
+    dir.write("valid.json", "{}")
+        .write("invalid.json", r#"{"coolKey"}"#);
+
+    let output = exec_cmd(
+        dir.cli()
+            .args(["check", "valid.json", "invalid.json", "missing.json"]),
+        None,
+    );
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
@@ -0,0 +1,78 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use insta::assert_snapshot;
+
+const SETTINGS: &str = r#"// editor settings
+{
+  tabSize: 2,
+  theme: 'dark', /* for now */
+  rulers: [80, 0x78,],
+}"#;
+
+#[test]
+fn convert_json5_to_json_reports_changes() {
+    let output = exec_cmd(
+        cli().args(["convert", "--from", "json5", "--to", "json"]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn convert_deny_warnings_fails_on_changes() {
+    let output = exec_cmd(
+        cli().args(["convert", "--from", "json5", "--deny-warnings"]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn convert_detects_dialect_from_file_name() {
+    let dir = TempDir::new("convert_detects_dialect_from_file_name");
+    dir.write("tsconfig.json", "{\n  // strict\n  \"strict\": true,\n}");
+
+    let output = exec_cmd(dir.cli().args(["convert", "tsconfig.json"]), None);
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn convert_to_jsonc_keeps_comments() {
+    let output = exec_cmd(
+        cli().args(["convert", "--from", "json5", "--to", "jsonc"]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert!(output.stdout.contains("// editor settings"));
+    assert!(!output.stderr.contains("stripped comment"));
+}
+
+#[test]
+fn convert_strict_json_has_no_warnings() {
+    let output = exec_cmd(cli().arg("convert"), Some(b"{\"a\": [1, 2]}".to_vec()));
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_eq!(output.stderr, "");
+}
+
+#[test]
+fn convert_fails_on_non_finite_numbers() {
+    let output = exec_cmd(
+        cli().args(["convert", "--from", "json5"]),
+        Some(b"[NaN]".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert!(output.stderr.contains("`NaN` can't be represented in json"));
+}
+
+#[test]
+fn convert_help_snapshot() {
+    let output = exec_cmd(cli().args(["convert", "--help"]), None);
+
+    assert_snapshot!(output.snapshot_display());
+}
@@ -0,0 +1,113 @@
+use crate::common::{TempDir, exec_cmd};
+use insta::assert_snapshot;
+
+const NESTED: &str = r#"{"rust":{"is":["a","must"]}}"#;
+
+#[test]
+fn indentation_from_matching_sections() {
+    let dir = TempDir::new("editorconfig_indentation_from_matching_sections");
+    dir.write(
+        ".editorconfig",
+        "root = true
+
+[*]
+indent_style = space
+indent_size = 4
+
+[*.json]
+indent_size = 3
+
+[tabs/**/*.json]
+indent_style = tab
+",
+    )
+    .write("spaces.json", NESTED)
+    .write("tabs/nested/tabbed.json", NESTED);
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            "--preferred-width",
+            "0",
+            "spaces.json",
+            "tabs/nested/tabbed.json",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+// Snapshots normalize newlines to LF, so we assert directly to preserve each line ending.
+#[rstest::rstest]
+#[case(
+    "crlf",
+    "end_of_line = crlf\ninsert_final_newline = true",
+    "[\r\n  null\r\n]\r\n"
+)]
+#[case(
+    "cr_no_newline",
+    "end_of_line = CR\ninsert_final_newline = false",
+    "[\r  null\r]"
+)]
+#[case("unset", "end_of_line = crlf\nend_of_line = unset", "[\n  null\n]\n")]
+#[case("invalid", "end_of_line = bogus", "[\n  null\n]\n")]
+fn line_endings(#[case] label: &str, #[case] properties: &str, #[case] expected: &str) {
+    let dir = TempDir::new(&format!("editorconfig_line_endings_{label}"));
+    dir.write(".editorconfig", &format!("[*]\n{properties}"))
+        .write("data.json", "[null]");
+
+    let output = exec_cmd(
+        dir.cli()
+            .args(["format", "--preferred-width", "0", "data.json"]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+    assert_eq!(expected, output.stdout);
+}
+
+#[test]
+fn closer_files_override_parents_until_root() {
+    let dir = TempDir::new("editorconfig_closer_files_override_parents_until_root");
+    dir.write("project/.editorconfig", "root = true\n[*]\nindent_size = 6")
+        .write("project/app/.editorconfig", "[*.json]\nindent_size = 1")
+        .write("project/app/data.json", NESTED)
+        .write("project/other.json", NESTED);
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            "--preferred-width",
+            "0",
+            "project/app/data.json",
+            "project/other.json",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn config_file_and_flags_take_precedence() {
+    let dir = TempDir::new("editorconfig_config_file_and_flags_take_precedence");
+    dir.write(".editorconfig", "[*]\nindent_size = 8\nindent_style = tab")
+        .write("jjp.toml", "indent_style = \"space\"")
+        .write("data.json", "[null]");
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            "--preferred-width",
+            "0",
+            "--indent-width",
+            "1",
+            "data.json",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+    assert_eq!("[\n null\n]\n", output.stdout);
+}
@@ -0,0 +1,50 @@
+use crate::common::{cli, exec_cmd};
+use insta::assert_snapshot;
+
+#[test]
+fn explain_prints_explanation() {
+    let output = exec_cmd(cli().args(["explain", "JJP0001"]), None);
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn explain_ignores_case() {
+    let upper = exec_cmd(cli().args(["explain", "JJP0021"]), None);
+    let lower = exec_cmd(cli().args(["explain", "jjp0021"]), None);
+    assert!(lower.status.success(), "{}", lower.snapshot_display());
+
+    assert_eq!(upper.stdout, lower.stdout);
+}
+
+#[rstest::rstest]
+#[case("unknown_code", "JJP9999")]
+#[case("not_a_code", "E0308")]
+fn explain_invalid_code(#[case] name: &str, #[case] code: &str) {
+    let output = exec_cmd(cli().args(["explain", code]), None);
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(
+        format!("explain_invalid_code_{name}"),
+        output.snapshot_display()
+    );
+}
+
+#[test]
+fn errors_show_their_code() {
+    let output = exec_cmd(
+        cli().args(["check"]),
+        Some(br#"{"name" "ferris"}"#.to_vec()),
+    );
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn explain_help() {
+    let output = exec_cmd(cli().args(["explain", "--help"]), None);
+
+    assert_snapshot!(output.snapshot_display());
+}
@@ -45,11 +45,11 @@ fn preferred_width_threshold(#[case] preferred_width: usize, #[case] label: &str
 // Snapshots normalize newlines to LF, so we assert directly to preserve each line ending.
 #[rstest::rstest]
 #[case(&["--end-of-line", "lf"], "[\n  null\n]\n")]
-#[case(&["--end-of-line", "crlf"], "[\r\n  null\r\n]\n")]
-#[case(&["--end-of-line", "cr"], "[\r  null\r]\n")]
+#[case(&["--end-of-line", "crlf"], "[\r\n  null\r\n]\r\n")]
+#[case(&["--end-of-line", "cr"], "[\r  null\r]\r")]
 #[case(&["--eol", "lf"], "[\n  null\n]\n")]
-#[case(&["--eol", "crlf"], "[\r\n  null\r\n]\n")]
-#[case(&["--eol", "cr"], "[\r  null\r]\n")]
+#[case(&["--eol", "crlf"], "[\r\n  null\r\n]\r\n")]
+#[case(&["--eol", "cr"], "[\r  null\r]\r")]
 fn preferred_line_endings(#[case] args: &[&str], #[case] expected: &str) {
     let mut cmd = cli();
     cmd.args(["format", "--preferred-width", "0"]);
@@ -129,6 +129,52 @@ fn help_subcommand() {
     assert_snapshot!("format_help", output.snapshot_display());
 }
 
+const DUPLICATE_KEYS: &str = r#"{"name": "a", "id": 1, "name": "b", "name": "c"}"#;
+
+#[rstest::rstest]
+#[case("duplicate_keys_error", &["--duplicate-keys", "error"])]
+#[case("duplicate_keys_warn", &["--duplicate-keys", "warn"])]
+#[case("duplicate_keys_keep_first", &["--duplicate-keys", "keep-first"])]
+#[case("duplicate_keys_keep_last", &["--duplicate-keys", "keep-last"])]
+#[case("duplicate_keys_keep_all", &[])]
+#[case("duplicate_keys_uglify_keep_last", &["--uglify", "--duplicate-keys", "keep-last"])]
+fn duplicate_keys(#[case] name: &str, #[case] args: &[&str]) {
+    let mut cmd = cli();
+    cmd.args(std::iter::once("format").chain(args.iter().copied()));
+
+    let output = exec_cmd(&mut cmd, Some(DUPLICATE_KEYS.as_bytes().to_vec()));
+
+    assert_snapshot!(name, output.snapshot_display());
+}
+
+#[test]
+fn duplicate_keys_keep_comments_of_kept_entries() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonc", "--duplicate-keys", "keep-last"]),
+        Some(
+            b"{\n  // old\n  \"port\": 80,\n  \"host\": \"a\",\n  // new\n  \"port\": 8080,\n}"
+                .to_vec(),
+        ),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[rstest::rstest]
+#[case(&[], "{\n  \"crab\": \"\\ud83e\"\n}\n")]
+#[case(&["--uglify"], "{\"crab\":\"\\ud83e\"}\n")]
+#[case(&["--jsonc"], "{\n  \"crab\": \"\\ud83e\"\n}\n")]
+#[case(&["--uglify", "--duplicate-keys", "keep-last"], "{\"crab\":\"\\ud83e\"}\n")]
+fn lone_surrogates_can_be_allowed(#[case] args: &[&str], #[case] expected: &str) {
+    let mut cmd = cli();
+    cmd.args(["format", "--allow-lone-surrogates"]).args(args);
+
+    let output = exec_cmd(&mut cmd, Some(br#"{"crab": "\ud83e"}"#.to_vec()));
+    assert!(output.status.success(), "{}", output.snapshot_display());
+    assert_eq!(output.stdout, expected);
+}
+
 #[test]
 fn no_stdin() {
     let mut cmd = cli();
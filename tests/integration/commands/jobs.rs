@@ -0,0 +1,35 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use insta::assert_snapshot;
+use rstest::rstest;
+
+#[rstest]
+#[case::one_job("1")]
+#[case::many_jobs("8")]
+fn outputs_keep_input_order(#[case] jobs: &str) {
+    let dir = TempDir::new(&format!("outputs_keep_input_order_{jobs}"));
+    for i in 0..50 {
+        dir.write(&format!("{i:02}.json"), &format!("[{i}]"));
+    }
+    dir.write("25.json", "[25,]");
+
+    let output = exec_cmd(
+        dir.cli().args(["format", "--uglify", ".", "-j", jobs]),
+        None,
+    );
+    assert!(!output.status.success());
+
+    let expected = (0..50)
+        .filter(|&i| i != 25)
+        .map(|i| format!("==> ./{i:02}.json <==\n[{i}]\n"))
+        .collect::<String>();
+    assert_eq!(output.stdout, expected);
+    assert!(output.stderr.contains("25.json:1:5"), "{}", output.stderr);
+}
+
+#[test]
+fn jobs_must_be_positive() {
+    let output = exec_cmd(cli().args(["check", "--jobs", "0"]), None);
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
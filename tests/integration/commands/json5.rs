@@ -0,0 +1,105 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use insta::assert_snapshot;
+use rstest::rstest;
+
+const CONFIG: &str = r#"// from https://json5.org
+{
+  unquoted: 'and you can quote me on that',
+  singleQuotes: 'I can use "double quotes" here',
+  lineBreaks: "Look, Mom! \
+No \\n's!",
+  hexadecimal: 0xdecaf,
+  leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+  positiveSign: +1,
+  trailingComma: 'in objects', andIn: ['arrays',],
+  "backwardsCompatible": "with JSON",
+}"#;
+
+#[rstest]
+#[case(CONFIG)]
+#[case("{$id: '\\x41\\v', _private: NaN, ünïcödé: [+Infinity, -.5e3]}")]
+#[case("\u{FEFF}[1,\u{A0}2]")]
+#[case("{undefined: 1}")]
+fn json5_flag_accepts_json5(#[case] json: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--json5"]),
+        Some(json.as_bytes().to_vec()),
+    );
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
+
+#[rstest]
+#[case("[foo]")]
+#[case("{'a' 1}")]
+#[case("['\\1']")]
+#[case("[0x]")]
+#[case("[.]")]
+#[case("{enabled: True}")]
+#[case("[`hi`]")]
+#[case("{parent: undefined}")]
+#[case("[0b1]")]
+fn json5_flag_rejects_invalid_json5(#[case] json: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--json5"]),
+        Some(json.as_bytes().to_vec()),
+    );
+
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn json5_misspelled_keyword() {
+    let output = exec_cmd(
+        cli().args(["check", "--json5"]),
+        Some(b"{nul: 1, parent: NULL}".to_vec()),
+    );
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_json5() {
+    let output = exec_cmd(
+        cli().args(["format", "--json5"]),
+        Some(CONFIG.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_json5_to_json() {
+    let output = exec_cmd(
+        cli().args(["format", "--json5", "--to", "json"]),
+        Some(CONFIG.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_to_json_rejects_non_finite_numbers() {
+    let output = exec_cmd(
+        cli().args(["format", "--json5", "--to", "json"]),
+        Some(b"{limits: [0, Infinity]}".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn json5_enabled_for_json5_files() {
+    let dir = TempDir::new("json5_enabled_for_json5_files");
+    dir.write("config.json5", CONFIG)
+        .write("config.json", CONFIG);
+
+    let output = exec_cmd(dir.cli().args(["check", "."]), None);
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
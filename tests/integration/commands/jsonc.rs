@@ -0,0 +1,101 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use crate::test_json::{BLOCK_COMMENT, LINE_COMMENT};
+use insta::assert_snapshot;
+use rstest::rstest;
+
+const TSCONFIG: &str = r#"{
+  // see https://aka.ms/tsconfig
+  "compilerOptions": {
+    "strict": true, /* recommended */
+  },
+}"#;
+
+#[rstest]
+#[case(LINE_COMMENT)]
+#[case(BLOCK_COMMENT)]
+#[case(TSCONFIG)]
+#[case("[1, 2, ]")]
+fn jsonc_flag_accepts_comments_and_trailing_commas(#[case] json: &str) {
+    let output = exec_cmd(
+        cli().args(["check", "--jsonc"]),
+        Some(json.as_bytes().to_vec()),
+    );
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn jsonc_unterminated_block_comment() {
+    let output = exec_cmd(
+        cli().args(["check", "--jsonc"]),
+        Some(b"{} /* done?".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn jsonc_enabled_for_known_file_names() {
+    let dir = TempDir::new("jsonc_enabled_for_known_file_names");
+    dir.write("tsconfig.json", TSCONFIG)
+        .write("tsconfig.build.json", TSCONFIG)
+        .write(".vscode/settings.json", LINE_COMMENT)
+        .write("settings.jsonc", BLOCK_COMMENT)
+        .write("package.json", LINE_COMMENT);
+
+    let output = exec_cmd(
+        dir.cli()
+            .args(["check", "tsconfig.json", "tsconfig.build.json"])
+            .args([".vscode/settings.json", "settings.jsonc", "package.json"]),
+        None,
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_keeps_comments() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonc"]),
+        Some(
+            br#"// settings
+{"editor.tabSize": 2, // spaces
+  /* theme
+     for the editor */ "workbench.colorTheme": "Default Dark+",
+  "files.exclude": {"**/.git": true,}, "search.exclude": [ /* none yet */ ],
+}"#
+            .to_vec(),
+        ),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_detects_jsonc_files() {
+    let dir = TempDir::new("format_detects_jsonc_files");
+    dir.write("tsconfig.json", TSCONFIG);
+
+    let output = exec_cmd(
+        dir.cli()
+            .args(["format", "tsconfig.json", "--indent-style", "tab"]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn uglify_removes_comments() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonc", "--uglify"]),
+        Some(TSCONFIG.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_eq!(output.stdout, "{\"compilerOptions\":{\"strict\":true}}\n");
+}
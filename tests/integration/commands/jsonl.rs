@@ -0,0 +1,82 @@
+use crate::common::{cli, exec_cmd};
+use insta::assert_snapshot;
+
+const RECORDS: &str = r#"{"name": "Gilbert", "wins": [["straight", "7♣"], ["one pair", "10♥"]]}
+{"name": "Alexa", "wins": [["two pair", "4♠"], ["two pair", "9♠"]]}
+
+{"name": "May", "wins": []}
+"#;
+
+#[test]
+fn check_jsonl() {
+    let output = exec_cmd(
+        cli().args(["check", "--jsonl"]),
+        Some(RECORDS.as_bytes().to_vec()),
+    );
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn check_jsonl_reports_every_invalid_line() {
+    let output = exec_cmd(
+        cli().args(["check", "--jsonl"]),
+        Some(b"{\"a\": 1}\n{\"b\": [1, 2}\n\"fine\"\n1 2\n".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn check_jsonl_ranks_patches_within_the_line() {
+    let output = exec_cmd(
+        cli().args(["check", "--jsonl"]),
+        Some(b"{\"a\": 1}\n{\"hi\" \"bye\": null}\n".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    // the placeholder patch leaves the line valid, so it comes first like it
+    // does for a single document
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn check_without_jsonl_rejects_multiple_documents() {
+    let output = exec_cmd(cli().args(["check"]), Some(RECORDS.as_bytes().to_vec()));
+
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn format_jsonl_uglify() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonl", "--uglify"]),
+        Some(RECORDS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_jsonl_pretty() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonl", "--preferred-width", "40"]),
+        Some(RECORDS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_jsonl_error_points_at_line() {
+    let output = exec_cmd(
+        cli().args(["format", "--jsonl"]),
+        Some(b"[1]\n\n[2,\n".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
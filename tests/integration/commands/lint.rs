@@ -0,0 +1,177 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use insta::assert_snapshot;
+
+const SETTINGS: &str = r#"{
+  "user_name": "ferris",
+  "": "anonymous",
+  "timeoutMs": 1.5e3,
+  "ports": [80, "443"],
+  "userName": "corro",
+  "user_name": "crab"
+}
+"#;
+
+#[test]
+fn lint_reports_every_rule() {
+    let output = exec_cmd(cli().args(["lint"]), Some(SETTINGS.as_bytes().to_vec()));
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_fix_prints_fixed_input() {
+    let output = exec_cmd(
+        cli().args(["lint", "--fix"]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_rule_levels_from_flags() {
+    let output = exec_cmd(
+        cli().args([
+            "lint",
+            "--rule",
+            "duplicate-keys=warning",
+            "--rule",
+            "camel-case-keys=off",
+            "--rule",
+            "empty-keys=off",
+        ]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_deep_nesting_and_long_strings() {
+    let long = "a".repeat(1001);
+    let input = format!(r#"[[[[[[[[[[[["{long}"]]]]]]]]]]]]"#);
+    let output = exec_cmd(cli().args(["lint"]), Some(input.into_bytes()));
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_info_and_hint_levels() {
+    let output = exec_cmd(
+        cli().args([
+            "lint",
+            "--rule",
+            "duplicate-keys=info",
+            "--rule",
+            "camel-case-keys=hint",
+            "--rule",
+            "empty-keys=off",
+            "--rule",
+            "exponent-numbers=off",
+            "--rule",
+            "mixed-type-arrays=off",
+        ]),
+        Some(SETTINGS.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[rstest::rstest]
+#[case("warning", false)]
+#[case("info", true)]
+fn lint_deny_warnings(#[case] level: &str, #[case] succeeds: bool) {
+    let output = exec_cmd(
+        cli().args([
+            "lint",
+            "--deny-warnings",
+            "--rule",
+            &format!("camel-case-keys={level}"),
+        ]),
+        Some(br#"{"user_name": "ferris"}"#.to_vec()),
+    );
+
+    assert_eq!(
+        output.status.success(),
+        succeeds,
+        "{}",
+        output.snapshot_display()
+    );
+}
+
+#[rstest::rstest]
+#[case("unknown_rule", "no-such-rule=off")]
+#[case("unknown_level", "camel-case-keys=loud")]
+#[case("missing_level", "camel-case-keys")]
+fn lint_invalid_rule_flag(#[case] name: &str, #[case] rule: &str) {
+    let output = exec_cmd(cli().args(["lint", "--rule", rule]), Some(b"{}".to_vec()));
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(
+        format!("lint_invalid_rule_flag_{name}"),
+        output.snapshot_display()
+    );
+}
+
+#[test]
+fn lint_syntax_error() {
+    let output = exec_cmd(cli().args(["lint"]), Some(br#"{"a": 1,}"#.to_vec()));
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_levels_from_config_and_flags() {
+    let dir = TempDir::new("lint_levels_from_config_and_flags");
+    dir.write(
+        "jjp.toml",
+        r#"
+[lint]
+camel-case-keys = "error"
+mixed-type-arrays = "off"
+"#,
+    )
+    .write(
+        "data.json",
+        r#"{"first_name": "a", "tags": [1, "b"], "e": 1e2}"#,
+    );
+
+    let output = exec_cmd(dir.cli().args(["lint", "data.json"]), None);
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+    assert_snapshot!("lint_levels_from_config", output.snapshot_display());
+
+    let output = exec_cmd(
+        dir.cli()
+            .args(["lint", "data.json", "--rule", "camel-case-keys=warning"]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+    assert_snapshot!(
+        "lint_levels_flags_override_config",
+        output.snapshot_display()
+    );
+}
+
+#[test]
+fn lint_unknown_rule_in_config() {
+    let dir = TempDir::new("lint_unknown_rule_in_config");
+    dir.write(".jjprc.json", r#"{"lint": {"no-such-rule": "off"}}"#)
+        .write("data.json", "{}");
+
+    let output = exec_cmd(dir.cli().args(["lint", "data.json"]), None);
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn lint_help() {
+    let output = exec_cmd(cli().args(["lint", "--help"]), None);
+
+    assert_snapshot!(output.snapshot_display());
+}
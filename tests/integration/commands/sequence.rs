@@ -0,0 +1,84 @@
+use crate::common::{cli, exec_cmd};
+use insta::assert_snapshot;
+use rstest::rstest;
+
+const CONCATENATED: &str = r#"{"level": "info", "msg": "started"}{"level": "warn", "msg": "slow"}
+  {"level": "info",
+   "msg": "stopped"} 42 "done""#;
+
+const SEQUENCE: &str = "\u{1E}{\"level\": \"info\"}\n\u{1E}[1, 2]\n\u{1E}\n\u{1E}\"done\"\n";
+
+#[rstest]
+#[case("--concatenated", CONCATENATED)]
+#[case("--seq", SEQUENCE)]
+fn check_multiple_documents(#[case] flag: &str, #[case] input: &str) {
+    let output = exec_cmd(cli().args(["check", flag]), Some(input.as_bytes().to_vec()));
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn format_concatenated_uglify() {
+    let output = exec_cmd(
+        cli().args(["format", "--concatenated", "--uglify"]),
+        Some(CONCATENATED.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_concatenated_pretty() {
+    let output = exec_cmd(
+        cli().args(["format", "--concatenated"]),
+        Some(CONCATENATED.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn format_seq() {
+    let output = exec_cmd(
+        cli().args(["format", "--seq", "--final-newline", "false"]),
+        Some(SEQUENCE.as_bytes().to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn check_concatenated_stops_at_first_error() {
+    let output = exec_cmd(
+        cli().args(["check", "--concatenated"]),
+        Some(b"[1] {\"a\" 1} [2,".to_vec()),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn check_seq_reports_every_invalid_record() {
+    let output = exec_cmd(
+        cli().args(["check", "--seq"]),
+        Some(
+            "{}\n\u{1E}[1,]\n\u{1E}true\n\u{1E}1 2\n"
+                .as_bytes()
+                .to_vec(),
+        ),
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn document_flags_conflict() {
+    let output = exec_cmd(cli().args(["check", "--seq", "--jsonl"]), None);
+
+    assert!(!output.status.success(), "{}", output.snapshot_display());
+}
@@ -0,0 +1,74 @@
+use crate::common::{cli, exec_cmd};
+use insta::assert_snapshot;
+
+/// an array larger than the buffer stdin is read through
+fn large_array(last: &str) -> String {
+    let items = "\"padding padding padding padding\",\n".repeat(10_000);
+    format!("[\n{items}{last}\n]\n")
+}
+
+#[test]
+fn check_large_stdin() {
+    let output = exec_cmd(cli().args(["check"]), Some(large_array("1").into_bytes()));
+
+    assert!(output.status.success(), "{}", output.snapshot_display());
+}
+
+#[test]
+fn check_large_stdin_reports_error_at_its_line() {
+    let output = exec_cmd(
+        cli().args(["check"]),
+        Some(large_array("[1 2]").into_bytes()),
+    );
+    assert!(!output.status.success());
+
+    // the input is too large to be worth keeping in the snapshot
+    assert_snapshot!(output.stderr);
+}
+
+#[test]
+fn format_uglify_large_stdin() {
+    let input = large_array("{\"a\": [true, null]}");
+    let output = exec_cmd(
+        cli().args(["format", "--uglify"]),
+        Some(input.clone().into_bytes()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    let expected = jjpwrgem_parse::format::uglify_str(&input).unwrap();
+    assert_eq!(output.stdout, expected + "\n");
+}
+
+#[test]
+fn format_uglify_stdin_without_final_newline() {
+    let output = exec_cmd(
+        cli().args(["format", "--uglify", "--final-newline", "false"]),
+        Some(b"{ \"a\": [1, 2] }\n".to_vec()),
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_eq!(output.stdout, "{\"a\":[1,2]}");
+}
+
+#[test]
+fn check_stdin_rejects_invalid_utf8() {
+    let output = exec_cmd(cli().args(["check"]), Some(b"[\"\xff\"]".to_vec()));
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn check_large_single_line_stdin_reports_error_at_its_column() {
+    let items = "\"padding padding padding padding\", ".repeat(10_000);
+    let input = format!("[{items}[1 2]]");
+    let output = exec_cmd(cli().args(["check"]), Some(input.clone().into_bytes()));
+    assert!(!output.status.success());
+
+    let column = input.find("2]").unwrap() + 1;
+    assert!(
+        output.stderr.contains(&format!("--> stdin:1:{column}\n")),
+        "{}",
+        output.stderr
+    );
+}
@@ -0,0 +1,93 @@
+use crate::common::{TempDir, exec_cmd};
+use insta::assert_snapshot;
+
+const INVALID: &str = "{";
+
+#[test]
+fn directories_skip_ignored_files() {
+    let dir = TempDir::new("directories_skip_ignored_files");
+    dir.write(".gitignore", "dist/\n")
+        .write(".jjpignore", "fixtures/*.json\n!fixtures/keep.json\n")
+        .write("a.json", "[]")
+        .write("nested/b.json", "{}")
+        .write("nested/notes.txt", INVALID)
+        .write("dist/bundle.json", INVALID)
+        .write("fixtures/broken.json", INVALID)
+        .write("fixtures/keep.json", "null")
+        .write("node_modules/pkg/package.json", INVALID)
+        .write("target/debug/out.json", INVALID);
+
+    let output = exec_cmd(dir.cli().args(["format", "."]), None);
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn explicit_files_are_never_excluded() {
+    let dir = TempDir::new("explicit_files_are_never_excluded");
+    dir.write(".jjpignore", "*.json\n")
+        .write("node_modules/package.json", INVALID);
+
+    let output = exec_cmd(
+        dir.cli().args(["check", ".", "node_modules/package.json"]),
+        None,
+    );
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn include_and_exclude_globs() {
+    let dir = TempDir::new("include_and_exclude_globs");
+    dir.write("data.json", "[]")
+        .write("settings.jsonc", "{}")
+        .write("generated/schema.json", INVALID)
+        .write("src/generated/types.jsonc", INVALID)
+        .write("src/skip.jsonc", INVALID);
+
+    let output = exec_cmd(
+        dir.cli().args([
+            "format",
+            ".",
+            "--include",
+            "*.json",
+            "--include",
+            "*.jsonc",
+            "--exclude",
+            "generated",
+            "--exclude",
+            "src/skip.jsonc",
+        ]),
+        None,
+    );
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn invalid_glob() {
+    let dir = TempDir::new("invalid_glob");
+    dir.write("data.json", "[]");
+
+    let output = exec_cmd(dir.cli().args(["check", ".", "--exclude", "[a"]), None);
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
+
+#[test]
+fn clean_check_prints_nothing() {
+    let dir = TempDir::new("clean_check_prints_nothing");
+    dir.write("a.json", "[]")
+        .write("nested/b.json", "{}")
+        .write("c.json", "null");
+
+    let output = exec_cmd(dir.cli().args(["check", "."]), None);
+    assert!(output.status.success(), "{}", output.snapshot_display());
+
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.stderr, "");
+}
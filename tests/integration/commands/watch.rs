@@ -0,0 +1,107 @@
+use crate::common::{TempDir, cli, exec_cmd};
+use insta::assert_snapshot;
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+const WATCHING: &str = "watching for changes, press ctrl+c to stop";
+
+/// kills the watcher even if an assertion fails
+struct Watcher(Child);
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// collects stderr lines until the next run finishes
+fn next_run(lines: &Receiver<String>) -> String {
+    let mut run = String::new();
+    loop {
+        let line = lines
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap_or_else(|_| panic!("watcher did not finish a run, got {run:?}"));
+        if line == WATCHING {
+            return run;
+        }
+        run.push_str(&line);
+        run.push('\n');
+    }
+}
+
+/// starts `jjp check --watch` on `path` in `dir`, returning its stderr lines
+fn watch_check(dir: &TempDir, path: &str) -> (Watcher, Receiver<String>) {
+    let mut child = dir
+        .cli()
+        .args(["check", "--watch", path])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("watcher should start");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let watcher = Watcher(child);
+
+    let (tx, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    (watcher, lines)
+}
+
+#[test]
+fn reruns_when_matched_files_change() {
+    let dir = TempDir::new("reruns_when_matched_files_change");
+    dir.write("data.json", "[1,]")
+        .write("node_modules/package.json", "{}");
+    let (_watcher, lines) = watch_check(&dir, ".");
+
+    let first = next_run(&lines);
+    assert!(first.contains("data.json:1:4"), "{first}");
+
+    // excluded files do not trigger a run, waiting well past the debounce
+    // window so a run they wrongly triggered would have printed by now
+    dir.write("node_modules/package.json", "{");
+    let excluded_run = lines.recv_timeout(Duration::from_secs(1));
+    assert!(excluded_run.is_err(), "{excluded_run:?}");
+
+    dir.write("data.json", "[1]");
+
+    let second = next_run(&lines);
+    assert_eq!(second, "");
+}
+
+#[test]
+fn watched_files_rerun_when_settings_next_to_them_change() {
+    let dir = TempDir::new("watched_files_rerun_when_settings_next_to_them_change");
+    dir.write("data.json", r#"["\ud83e"]"#);
+    let (_watcher, lines) = watch_check(&dir, "data.json");
+
+    let first = next_run(&lines);
+    assert!(first.contains("data.json:1:3"), "{first}");
+
+    // changes are told apart from the run's own reads by modification time,
+    // which the filesystem may only update every few milliseconds
+    thread::sleep(Duration::from_millis(100));
+    dir.write("jjp.toml", "allow_lone_surrogates = true");
+
+    let second = next_run(&lines);
+    assert_eq!(second, "");
+}
+
+#[test]
+fn watch_requires_paths() {
+    let output = exec_cmd(cli().args(["check", "--watch"]), None);
+    assert!(!output.status.success());
+
+    assert_snapshot!(output.snapshot_display());
+}
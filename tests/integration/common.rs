@@ -2,6 +2,7 @@ use core::fmt::Debug;
 use std::{
     env, fs,
     io::Write,
+    path::{Path, PathBuf},
     process::{Command, ExitStatus, Stdio},
 };
 
@@ -21,6 +22,41 @@ pub fn cli() -> Command {
     Command::new(exe)
 }
 
+/// a scratch directory for tests that read files, removed on drop
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` should be unique per test since tests run in parallel
+    pub fn new(name: &str) -> Self {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("failed to create temp dir");
+        Self(path)
+    }
+
+    pub fn write(&self, relative: &str, contents: &str) -> &Self {
+        let path = self.0.join(relative);
+        fs::create_dir_all(path.parent().expect("files have a parent"))
+            .expect("failed to create parent dirs");
+        fs::write(path, contents).expect("failed to write temp file");
+        self
+    }
+
+    /// runs the cli from this directory so paths in snapshots are relative
+    pub fn cli(&self) -> Command {
+        let mut cmd = cli();
+        cmd.current_dir(&self.0);
+        cmd
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Output {
     pub args: Vec<String>,
@@ -70,16 +106,22 @@ pub fn exec_cmd(cmd: &mut Command, stdin: Option<Vec<u8>>) -> Output {
         .spawn()
         .expect("test command failed");
 
-    if let Some(stdin) = &stdin {
-        child
-            .stdin
-            .take()
-            .expect("should have stdin")
-            .write_all(stdin)
-            .expect("failed to write to stdin");
-    }
+    // written from another thread since commands that stream can fill the
+    // stdout pipe before all of stdin is written, or stop reading at an error
+    let writer = stdin.clone().map(|stdin| {
+        let mut pipe = child.stdin.take().expect("should have stdin");
+        std::thread::spawn(move || match pipe.write_all(&stdin) {
+            Err(error) if error.kind() != std::io::ErrorKind::BrokenPipe => {
+                panic!("failed to write to stdin: {error}")
+            }
+            _ => {}
+        })
+    });
 
     let output = child.wait_with_output().expect("failed to wait on child");
+    if let Some(writer) = writer {
+        writer.join().expect("stdin writer should not panic");
+    }
 
     let fmt_bytes = |xs: Option<Vec<u8>>| {
         if let Some(xs) = xs {
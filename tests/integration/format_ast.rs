@@ -1,7 +1,12 @@
 use crate::common::format_template;
 use crate::test_json::*;
 use insta::assert_snapshot;
-use jjpwrgem_parse::{ast::parse_str, format::uglify_value};
+use jjpwrgem_parse::{
+    ast::parse_str,
+    format::{
+        LineEnding, prettify_str, prettify_to_writer, uglify_str, uglify_to_writer, uglify_value,
+    },
+};
 
 #[rstest_reuse::apply(format_template)]
 fn uglify(#[case] (name, input): (&str, &str)) {
@@ -10,3 +15,17 @@ fn uglify(#[case] (name, input): (&str, &str)) {
 
     assert_snapshot!(format!("uglify_{name}"), out);
 }
+
+#[rstest_reuse::apply(format_template)]
+fn writers_match_strings(#[case] (_name, input): (&str, &str)) {
+    let mut pretty = Vec::new();
+    prettify_to_writer(input, 80, LineEnding::Lf, &mut pretty).unwrap();
+    assert_eq!(
+        String::from_utf8(pretty).unwrap(),
+        prettify_str(input, 80, LineEnding::Lf).unwrap()
+    );
+
+    let mut ugly = Vec::new();
+    uglify_to_writer(input, &mut ugly).unwrap();
+    assert_eq!(String::from_utf8(ugly).unwrap(), uglify_str(input).unwrap());
+}
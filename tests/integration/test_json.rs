@@ -13,10 +13,19 @@ pub const OBJECT_OPEN_CURLY: &str = r#"{"#;
 pub const CLOSED_CURLY: &str = r#"}"#;
 pub const EMPTY_INPUT: &str = r#""#;
 pub const UNEXPECTED_CHARACTER: &str = r#"🦀"#;
+pub const MISSPELLED_KEYWORD: &str = "[trbe]";
+pub const CAPITALIZED_KEYWORD: &str = r#"{"parent": NULL}"#;
+pub const INCOMPLETE_KEYWORD: &str = "[fals";
+pub const NULL_WITH_EXTRA_LETTER: &str = "[nulll]";
+pub const TRUE_WITH_EXTRA_LETTER: &str = r#"{"enabled": truee}"#;
+pub const FALSE_WITH_EXTRA_LETTER: &str = "[falsey]";
 pub const UNEXPECTED_ESCAPED_CHARACTER: &str = "\u{B}";
 pub const DOUBLE_QUOTE: &str = r#"""#;
 pub const OBJECT_WITH_LINE_BREAK_VALUE: &str = "{\"hi\": \"line\nbreak\"}";
 pub const OBJECT_WITH_ADJACENT_STRINGS: &str = r#"{"hi": "bye" "ferris": null"#;
+pub const OBJECT_MISSING_COLON_BEFORE_STRING: &str = r#"{"hi" "bye"}"#;
+pub const OBJECT_KEY_MISSING_VALUE: &str = r#"{"hi" "bye": null}"#;
+pub const OBJECT_NUMBER_WITH_SPACE: &str = r#"{"hi": 1 000}"#;
 pub const OBJECT_EMPTY_THEN_OPEN: &str = r#"{}{"#;
 pub const MINUS_SIGN: &str = "-";
 pub const LEADING_ZERO_MINUS_SIGN_ZERO: &str = "-000";
@@ -46,6 +55,9 @@ pub const ARRAY_SUBARRAYS: &str = "[[\"a\"], [true, false]]";
 pub const ARRAY_OPEN: &str = "[";
 pub const ARRAY_OPEN_WITH_VALUE: &str = "[1, [";
 pub const ARRAY_MISSING_VALUE: &str = "[1, ]";
+pub const ARRAY_MISSING_COMMA: &str = "[1 2]";
+pub const ARRAY_MISSING_COMMA_BEFORE_STRING: &str = r#"[1 "two"]"#;
+pub const ARRAY_MISMATCHED_CLOSE: &str = "[1, 2}";
 pub const ARRAY_OBJECTS_WITH_INCREASING_KEYS: &str = r#"[
     {},
     {"alpha": 1},
@@ -97,6 +109,11 @@ pub const ARRAYS_NESTED_FIVE_LEVELS_WITH_OBJECT: &str = r#"[[[[[
 ]]]]]"#;
 pub const INVALID_HEX_DIGIT_IN_ESCAPE: &str = r#""\u1FZA""#;
 pub const INVALID_ESCAPED_CURLY: &str = r#""\{""#;
+pub const LONE_HIGH_SURROGATE: &str = r#"{"crab": "\ud83e"}"#;
+pub const LONE_LOW_SURROGATE: &str = r#"["\udd80 crab"]"#;
+pub const MISMATCHED_SURROGATES: &str = r#""\ud83e\u0041""#;
+pub const INVERTED_SURROGATES: &str = r#""\udd80\ud83e""#;
+pub const INVERTED_SURROGATES_BEFORE_PAIR: &str = r#""\udc00\ud800\udc00""#;
 pub const OBJECT_WITH_LONG_KEYS: &str = r#"{
     "this is a very very very long key name with spaces and punctuation like --- ???": "value",
     "another extremely verbose key used for stress testing": {
@@ -216,3 +233,15 @@ pub const TSCONFIG: &str = r#"
   ]
 }
 "#;
+pub const LINE_COMMENT: &str = "{\n  // the answer\n  \"answer\": 42\n}";
+pub const BLOCK_COMMENT: &str = "[1, /* two\n  and three */ 2, 3]";
+pub const SINGLE_QUOTED_STRING: &str = r#"{"name": 'ferris'}"#;
+pub const UNQUOTED_KEY: &str = "{\n  name: \"ferris\"\n}";
+pub const HEX_NUMBER: &str = "[0xFF]";
+pub const INFINITY: &str = r#"{"max": -Infinity}"#;
+pub const TRAILING_DECIMAL_POINT: &str = "[5.]";
+pub const TEMPLATE_LITERAL: &str = "{\"greeting\": `say \"hi\"`}";
+pub const TEMPLATE_LITERAL_SUBSTITUTION: &str = "[`hi ${name}`]";
+pub const UNDEFINED: &str = r#"{"parent": undefined}"#;
+pub const OCTAL_NUMBER: &str = r#"{"mode": 0o644}"#;
+pub const BINARY_NUMBER: &str = "[-0b101]";
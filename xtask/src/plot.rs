@@ -36,7 +36,7 @@ impl<'a> TryFrom<&Value<'a>> for BenchmarkResult {
         let ast::Value::String(value) = command_value else {
             bail!("command must be a string");
         };
-        let command = (*value).to_owned();
+        let command = value.to_string();
 
         let times_value = entry
             .get("times")